@@ -23,26 +23,38 @@
 (defn load
   "Parse a YAML string and return a Clojure data structure.
 
-  Supports YAML 1.2 core schema with standard types:
+  Supports the YAML 1.2 core schema by default, with standard types:
   - Scalars: strings, integers, floats, booleans, null
   - Collections: maps (mappings) and vectors (sequences)
   - Anchors and aliases
 
   Args:
     yaml-str: A string containing YAML content
+    opts: Optional map; {:tagged true} returns the tagged encoding used by
+          the language bindings (see yamlstar.constructor/construct-node),
+          {:schema name} resolves plain scalars with the failsafe, json,
+          core or yaml11 schema (see yamlstar.resolver/infer-scalar-tag),
+          and {:resolvers [{:tag tag :pattern regex}]} tags the plain
+          scalars a regex matches first (see yamlstar.resolver/user-resolvers),
+          {:merge true} makes a plain << key merge mappings in any schema,
+          as the yaml11 schema always does,
+          and {:include {:root dir :file path}} loads `!include other.yaml`
+          from files under dir (see yamlstar.include/load-include)
 
   Returns:
     A Clojure data structure representing the YAML document
 
   Throws:
     Exception if the YAML is malformed"
-  [yaml-str]
-  (when yaml-str
-    (-> yaml-str
-        parser/parse
-        composer/compose
-        resolver/resolve
-        constructor/construct)))
+  ([yaml-str]
+   (load yaml-str {}))
+  ([yaml-str opts]
+   (when yaml-str
+     (-> yaml-str
+         parser/parse
+         composer/compose
+         (resolver/resolve opts)
+         (constructor/construct opts)))))
 
 (defn load-all
   "Parse a multi-document YAML string and return a sequence of documents.
@@ -52,36 +64,48 @@
 
   Args:
     yaml-str: A string containing one or more YAML documents
+    opts: Optional map, as for `load`
 
   Returns:
     A sequence of Clojure data structures, one per YAML document
 
   Throws:
     Exception if the YAML is malformed"
-  [yaml-str]
-  (when yaml-str
-    (-> yaml-str
-        parser/parse
-        composer/compose-all
-        resolver/resolve-all
-        constructor/construct-all)))
+  ([yaml-str]
+   (load-all yaml-str {}))
+  ([yaml-str opts]
+   (when yaml-str
+     (-> yaml-str
+         parser/parse
+         composer/compose-all
+         (resolver/resolve-all opts)
+         (constructor/construct-all opts)))))
 
 (defn dump
-  "Dump a JSON-compatible Clojure value to a YAML string."
-  [value]
-  (-> value
-      representer/represent
-      desolver/desolve
-      serializer/serialize
-      emitter/emit))
+  "Dump a JSON-compatible Clojure value to a YAML string.
+
+  With {:tagged true} in `opts` the value is read in the tagged encoding
+  produced by `(load yaml-str {:tagged true})`. With {:schema name},
+  {:resolvers [...]} and {:merge true} strings are quoted where needed to
+  load back as strings with those options."
+  ([value]
+   (dump value {}))
+  ([value opts]
+   (-> value
+       (representer/represent opts)
+       (desolver/desolve opts)
+       serializer/serialize
+       emitter/emit)))
 
 (defn dump-all
   "Dump a sequence of JSON-compatible Clojure values to a YAML stream."
-  [values]
-  (-> (mapv representer/represent values)
-      desolver/desolve-all
-      serializer/serialize-all
-      (emitter/emit true)))
+  ([values]
+   (dump-all values {}))
+  ([values opts]
+   (-> (mapv #(representer/represent % opts) values)
+       (desolver/desolve-all opts)
+       serializer/serialize-all
+       (emitter/emit true))))
 
 (defn version
   "Return the YAMLStar version string"
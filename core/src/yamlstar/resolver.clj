@@ -7,15 +7,11 @@
   - Output: Node tree with all tags resolved"
   (:refer-clojure :exclude [resolve]))
 
-(defn infer-scalar-tag
-  "Infer the tag for an untagged scalar based on YAML 1.2 core schema.
-
-  Args:
-    value: The string value of the scalar
+(def schemas
+  "The schemas that implicit tags can be resolved with."
+  #{"failsafe" "json" "core" "yaml11"})
 
-  Returns:
-    A tag string (!!null, !!bool, !!int, !!float, or !!str)"
-  [value]
+(defn- infer-core-tag [value]
   (cond
     ;; null values
     (or (= value "")
@@ -24,8 +20,11 @@
     ;; booleans
     (re-matches #"true|True|TRUE|false|False|FALSE" value) "!!bool"
 
-    ;; integers (decimal only for now)
-    (re-matches #"[-+]?[0-9]+" value) "!!int"
+    ;; integers: decimal, 0o octal and 0x hex
+    (or (re-matches #"[-+]?[0-9]+" value)
+        (re-matches #"0o[0-7]+" value)
+        (re-matches #"0x[0-9a-fA-F]+" value))
+    "!!int"
 
     ;; floats (including special values)
     (or (re-matches #"[-+]?(\.[0-9]+|[0-9]+(\.[0-9]*)?)([eE][-+]?[0-9]+)?" value)
@@ -36,58 +35,163 @@
     ;; default to string
     :else "!!str"))
 
+(defn- infer-json-tag [value]
+  (cond
+    (= value "null") "!!null"
+    (re-matches #"true|false" value) "!!bool"
+    (re-matches #"-?(0|[1-9][0-9]*)" value) "!!int"
+    (re-matches #"-?(0|[1-9][0-9]*)(\.[0-9]*)?([eE][-+]?[0-9]+)?" value) "!!float"
+    :else (throw (ex-info (str "Plain scalar '" value "' is not a JSON schema "
+                               "value; quote it to load it as a string")
+                          {:value value :schema "json"}))))
+
+(defn- infer-yaml11-tag [value]
+  (cond
+    (or (= value "")
+        (re-matches #"null|Null|NULL|~" value)) "!!null"
+
+    ;; y and n stay strings, as in most YAML 1.1 loaders
+    (re-matches #"yes|Yes|YES|no|No|NO|true|True|TRUE|false|False|FALSE|on|On|ON|off|Off|OFF"
+                value)
+    "!!bool"
+
+    (or (re-matches #"[-+]?0b[01_]+" value)
+        (re-matches #"[-+]?0[0-7_]+" value)
+        (re-matches #"[-+]?(0|[1-9][0-9_]*)" value)
+        (re-matches #"[-+]?0x[0-9a-fA-F_]+" value)
+        (re-matches #"[-+]?[1-9][0-9_]*(:[0-5]?[0-9])+" value))
+    "!!int"
+
+    (or (re-matches #"[-+]?([0-9][0-9_]*\.[0-9_]*|\.[0-9_]+)([eE][-+]?[0-9]+)?" value)
+        (re-matches #"[-+]?[0-9][0-9_]*(:[0-5]?[0-9])+\.[0-9_]*" value)
+        (re-matches #"[+-]?(\.inf|\.Inf|\.INF)" value)
+        (re-matches #"\.nan|\.NaN|\.NAN" value))
+    "!!float"
+
+    (= value "<<") "!!merge"
+
+    :else "!!str"))
+
+(defn infer-scalar-tag
+  "Infer the tag for an untagged scalar.
+
+  Args:
+    value: The string value of the scalar
+    schema: One of `schemas`; defaults to the YAML 1.2 core schema. The
+            failsafe schema makes every scalar a string, and the JSON schema
+            throws on plain scalars JSON would not accept.
+
+  Returns:
+    A tag string (!!null, !!bool, !!int, !!float, !!str, or !!merge in the
+    YAML 1.1 schema)"
+  ([value]
+   (infer-scalar-tag value "core"))
+  ([value schema]
+   (case schema
+     "failsafe" "!!str"
+     "json" (infer-json-tag value)
+     "core" (infer-core-tag value)
+     "yaml11" (infer-yaml11-tag value))))
+
+(defn user-resolvers
+  "Compile the user-defined implicit resolvers in {:resolvers [...]} of
+  `opts`. Each is a map {:tag tag :pattern regex-string}, and becomes a
+  [regex tag] pair. They are tried in order, before the schema.
+
+  With {:merge true}, a plain << resolves to !!merge in every schema, ahead
+  of the user's resolvers."
+  [opts]
+  (cond->> (mapv (fn [{:keys [tag pattern] :as resolver}]
+                   (when-not (and (string? tag) (string? pattern))
+                     (throw (ex-info "A resolver needs a string tag and pattern"
+                                     {:resolver resolver})))
+                   [(re-pattern pattern) tag])
+                 (:resolvers opts))
+    (:merge opts) (into [[#"<<" "!!merge"]])))
+
+(defn user-tag
+  "Return the tag of the first user resolver whose regex matches all of
+  `value`, or nil."
+  [value resolvers]
+  (some (fn [[regex tag]]
+          (when (re-matches regex value)
+            tag))
+        resolvers))
+
 (defn resolve-node
   "Add resolved tag to a node.
 
-  For untagged nodes, infers the tag based on YAML 1.2 core schema.
+  For untagged plain scalars, uses the first of `resolvers` that matches
+  (see user-tag), or infers the tag with `schema` (see infer-scalar-tag)
+  and marks the node :implicit.
   For the explicit non-specific tag (!), uses the kind-specific default tag.
   For other already-tagged nodes, leaves the tag unchanged.
   Recursively processes child nodes.
 
   Args:
     node: A node from the composer
+    schema: One of `schemas`
+    resolvers: User resolvers, as returned by user-resolvers
 
   Returns:
     The node with :tag field populated"
-  [node]
-  (when node
-    (case (:kind node)
-      :scalar
-      (let [tag (cond
-                  (= "!" (:tag node)) "!!str"
-                  (:tag node) (:tag node)
-                  (:style node) "!!str"
-                  :else (infer-scalar-tag (:value node)))]
-        (assoc node :tag tag))
-
-      :mapping
-      (let [tag (if (= "!" (:tag node))
-                  "!!map"
-                  (or (:tag node) "!!map"))
-            pairs (:value node)
-            resolved-pairs (mapv (fn [[k v]]
-                                   [(resolve-node k) (resolve-node v)])
-                                 pairs)]
-        (assoc node
-               :tag tag
-               :value resolved-pairs))
-
-      :sequence
-      (let [tag (if (= "!" (:tag node))
-                  "!!seq"
-                  (or (:tag node) "!!seq"))
-            items (:value node)
-            resolved-items (mapv resolve-node items)]
-        (assoc node
-               :tag tag
-               :value resolved-items))
-
-      :alias
-      ;; Aliases remain unchanged - will be resolved in constructor
-      node
-
-      ;; Default - return node unchanged
-      node)))
+  ([node]
+   (resolve-node node "core" []))
+  ([node schema]
+   (resolve-node node schema []))
+  ([node schema resolvers]
+   (when node
+     (case (:kind node)
+       :scalar
+       (let [plain (not (or (:tag node) (:style node)))
+             user (when plain
+                    (user-tag (:value node) resolvers))
+             tag (cond
+                   (= "!" (:tag node)) "!!str"
+                   (:tag node) (:tag node)
+                   (:style node) "!!str"
+                   user user
+                   :else (infer-scalar-tag (:value node) schema))]
+         (cond-> (assoc node :tag tag)
+           (and plain (not user)) (assoc :implicit true)))
+
+       :mapping
+       (let [tag (if (= "!" (:tag node))
+                   "!!map"
+                   (or (:tag node) "!!map"))
+             pairs (:value node)
+             resolved-pairs (mapv (fn [[k v]]
+                                    [(resolve-node k schema resolvers)
+                                     (resolve-node v schema resolvers)])
+                                  pairs)]
+         (assoc node
+                :tag tag
+                :value resolved-pairs))
+
+       :sequence
+       (let [tag (if (= "!" (:tag node))
+                   "!!seq"
+                   (or (:tag node) "!!seq"))
+             items (:value node)
+             resolved-items (mapv #(resolve-node % schema resolvers) items)]
+         (assoc node
+                :tag tag
+                :value resolved-items))
+
+       :alias
+       ;; Aliases remain unchanged - will be resolved in constructor
+       node
+
+       ;; Default - return node unchanged
+       node))))
+
+(defn schema
+  "Return the schema named by {:schema name} in `opts`, core by default."
+  [opts]
+  (let [name (or (:schema opts) "core")]
+    (when-not (contains? schemas name)
+      (throw (ex-info (str "Unknown schema: " name) {:schema name})))
+    name))
 
 (defn resolve
   "Resolve tags for a node tree.
@@ -96,19 +200,29 @@
 
   Args:
     node: A node tree from composer
+    opts: Optional map; {:schema name} picks the schema, one of `schemas`,
+          {:resolvers [...]} adds user resolvers and {:merge true} makes
+          << the merge key (see user-resolvers)
 
   Returns:
     The same node tree structure with all tags resolved"
-  [node]
-  (resolve-node node))
+  ([node]
+   (resolve node {}))
+  ([node opts]
+   (resolve-node node (schema opts) (user-resolvers opts))))
 
 (defn resolve-all
   "Resolve tags for multiple node trees.
 
   Args:
     nodes: Sequence of node trees from composer
+    opts: Optional map, as for `resolve`
 
   Returns:
     Sequence of node trees with tags resolved"
-  [nodes]
-  (map resolve-node nodes))
+  ([nodes]
+   (resolve-all nodes {}))
+  ([nodes opts]
+   (let [schema (schema opts)
+         resolvers (user-resolvers opts)]
+     (map #(resolve-node % schema resolvers) nodes))))
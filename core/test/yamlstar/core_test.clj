@@ -44,6 +44,32 @@
     (is (= -42 (yaml/load "-42")))
     (is (= 0 (yaml/load "0")))))
 
+(deftest test-load-octal-and-hex-integers
+  (testing "Load core schema octal and hex integers"
+    (is (= 15 (yaml/load "0o17")))
+    (is (= 0 (yaml/load "0o0")))
+    (is (= 31 (yaml/load "0x1F")))
+    (is (= 255 (yaml/load "0xff")))
+    (is (= 31 (yaml/load "!!int 0x1F")))
+    (is (= 12 (yaml/load "012"))))
+  (testing "Leave other forms as strings"
+    (doseq [value ["0o18" "0xG" "-0x1F" "+0o17" "0b101" "1_000" "0X1F"]]
+      (is (= value (yaml/load value)))))
+  (testing "Reject octal and hex integers outside JSON-safe exact range"
+    (is (thrown-with-msg?
+         clojure.lang.ExceptionInfo
+         #"YAML integer out of supported range"
+         (yaml/load "0x20000000000000"))))
+  (testing "Tagged load converts octal and hex of any size"
+    (is (= [9223372036854775807 {"!int" "18446744073709551615"}]
+           (yaml/load "[0x7FFFFFFFFFFFFFFF, 0xFFFFFFFFFFFFFFFF]"
+                      {:tagged true})))))
+
+(deftest test-dump-octal-and-hex-strings
+  (testing "Quote strings that would load as octal or hex integers"
+    (is (= "- '0o17'\n- '0x1F'\n- 0o18\n"
+           (yaml/dump ["0o17" "0x1F" "0o18"])))))
+
 (deftest test-load-safe-integer-range
   (testing "Load integers within JSON-safe exact range"
     (is (= 9007199254740991 (yaml/load "9007199254740991")))
@@ -70,6 +96,8 @@
     (is (= 1.23e10 (yaml/load "1.23e10")))
     (is (= Double/POSITIVE_INFINITY (yaml/load ".inf")))
     (is (= Double/NEGATIVE_INFINITY (yaml/load "-.inf")))
+    (is (= Double/NEGATIVE_INFINITY (yaml/load "-.Inf")))
+    (is (= Double/POSITIVE_INFINITY (yaml/load "+.INF")))
     (is (Double/isNaN (yaml/load ".nan")))))
 
 ;; Mapping Tests
@@ -227,6 +255,13 @@
     (is (= "'9999999999999999999'\n"
            (yaml/dump "9999999999999999999")))))
 
+(deftest test-dump-non-finite-floats
+  (testing "Dump infinite and NaN floats as YAML 1.2 special values"
+    (is (= ".inf\n" (yaml/dump Double/POSITIVE_INFINITY)))
+    (is (= "-.inf\n" (yaml/dump Double/NEGATIVE_INFINITY)))
+    (is (= ".nan\n" (yaml/dump Double/NaN)))
+    (is (= "ratio: -.inf\n" (yaml/dump {"ratio" Double/NEGATIVE_INFINITY})))))
+
 (deftest test-dump-simple-mapping
   (testing "Dump a simple mapping"
     (is (= "key: value\n" (yaml/dump {"key" "value"})))))
@@ -331,3 +366,229 @@
          clojure.lang.ExceptionInfo
          #"string map keys"
          (yaml/dump {1 "one"})))))
+
+(deftest test-load-tagged
+  (testing "Tagged load keeps key types, float text and unknown tags"
+    (is (= {"!map" [[1 "one"]
+                    ["ratio" {"!float" ".inf"}]
+                    ["big" 9007199254740993]
+                    ["secret" {"!tag" ["!vault" "abc"]}]
+                    [[1 2] nil]]}
+           (yaml/load (str "1: one\nratio: .inf\nbig: 9007199254740993\n"
+                           "secret: !vault abc\n? [1, 2]\n: null\n")
+                      {:tagged true})))
+    (is (= {"!tag" ["!point" {"!map" [["x" 1]]}]}
+           (yaml/load "!point {x: 1}" {:tagged true})))
+    (is (= [{"!float" "1.5"} "text"]
+           (yaml/load-all "--- 1.5\n--- text\n" {:tagged true}))))
+  (testing "Tagged load keeps the digits of integers beyond 64 bits"
+    (is (= [9223372036854775807
+            {"!int" "9223372036854775808"}
+            {"!int" "-123456789012345678901234567890"}]
+           (yaml/load (str "[9223372036854775807, 9223372036854775808, "
+                           "-123456789012345678901234567890]")
+                      {:tagged true})))))
+
+(deftest test-dump-tagged
+  (testing "Tagged dump reads the tagged encoding"
+    (is (= "1: one\nratio: .inf\nsecret: !vault abc\n"
+           (yaml/dump {"!map" [[1 "one"]
+                               ["ratio" {"!float" ".inf"}]
+                               ["secret" {"!tag" ["!vault" "abc"]}]]}
+                      {:tagged true})))
+    (is (= "- 18446744073709551615\n- -123456789012345678901234567890\n"
+           (yaml/dump [{"!int" "18446744073709551615"}
+                       {"!int" "-123456789012345678901234567890"}]
+                      {:tagged true})))
+    (let [yaml-str "a: 1\nb:\n- 2.5\n- !x y\n"]
+      (is (= yaml-str
+             (yaml/dump (yaml/load yaml-str {:tagged true}) {:tagged true}))))))
+
+(deftest test-load-schemas
+  (testing "The failsafe schema loads every plain scalar as a string"
+    (is (= {"a" "1" "b" "true" "c" "null" "d" ""}
+           (yaml/load "a: 1\nb: true\nc: null\nd:\n" {:schema "failsafe"})))
+    (is (= 1 (yaml/load "!!int 1" {:schema "failsafe"}))))
+  (testing "The JSON schema only accepts JSON values as plain scalars"
+    (is (= {"a" 1 "b" -1.5 "c" true "d" nil}
+           (yaml/load "\"a\": 1\n\"b\": -1.5\n\"c\": true\n\"d\": null\n"
+                      {:schema "json"})))
+    (doseq [value ["True" "~" "0x1F" "+1" "hello"]]
+      (is (thrown-with-msg?
+           clojure.lang.ExceptionInfo
+           #"is not a JSON schema value"
+           (yaml/load value {:schema "json"})))))
+  (testing "The YAML 1.1 schema reads its booleans, integers and floats"
+    (is (= [true true false false "y" "n"]
+           (yaml/load "[yes, On, NO, off, y, n]" {:schema "yaml11"})))
+    (is (= [8 -8 5 31 1000000 5400 -5400 0]
+           (yaml/load (str "- 010\n- -010\n- 0b101\n- 0x1_F\n- 1_000_000\n"
+                           "- 1:30:00\n- -1:30:00\n- 0\n")
+                      {:schema "yaml11"})))
+    (is (= [1000.5 5400.5 0.5]
+           (yaml/load "- 1_000.5\n- 1:30:00.5\n- .5\n" {:schema "yaml11"})))
+    (is (= ["1e3" "0o17"]
+           (yaml/load "[1e3, 0o17]" {:schema "yaml11"})))
+    (is (= [{"!float" "5400.5"} 9223372036854775807 {"!int" "18446744073709551615"}]
+           (yaml/load (str "- 1:30:00.5\n- 0x7FFF_FFFF_FFFF_FFFF\n"
+                           "- 0xFFFF_FFFF_FFFF_FFFF\n")
+                      {:schema "yaml11" :tagged true}))))
+  (testing "The YAML 1.1 schema merges mappings with <<"
+    (is (= {"x" 1 "y" 2 "z" 3}
+           (get (yaml/load (str "base: &b {x: 0, y: 2}\n"
+                                "other: &o {z: 3, x: 9}\n"
+                                "m:\n  <<: [*b, *o]\n  x: 1\n")
+                           {:schema "yaml11"})
+                "m")))
+    (is (= {"!map" [["y" 2] ["x" 1]]}
+           (get-in (yaml/load "b: &b {x: 0, y: 2}\nm: {<<: *b, x: 1}\n"
+                              {:schema "yaml11" :tagged true})
+                   ["!map" 1 1]))))
+  (testing "Unknown schemas are rejected"
+    (is (thrown-with-msg?
+         clojure.lang.ExceptionInfo
+         #"Unknown schema: yaml10"
+         (yaml/load "a" {:schema "yaml10"})))))
+
+(deftest test-dump-schemas
+  (testing "Strings are quoted to load back with the schema"
+    (is (= "- 'yes'\n- '010'\n- '1:30'\n- maybe\n"
+           (yaml/dump ["yes" "010" "1:30" "maybe"] {:schema "yaml11"})))
+    (is (= "- yes\n- maybe\n"
+           (yaml/dump ["yes" "maybe"])))
+    (is (= "\"a\": \"b\"\n\"n\": 1\n"
+           (yaml/dump {"a" "b" "n" 1} {:schema "json"})))
+    (is (= "- true\n- 1\n"
+           (yaml/dump ["true" "1"] {:schema "failsafe"})))))
+
+(deftest test-user-resolvers
+  (let [opts {:tagged true
+              :resolvers [{:tag "!duration" :pattern "\\d+(ms|s|m|h)"}
+                          {:tag "!port" :pattern ":\\d+"}]}]
+    (testing "Plain scalars a resolver matches get its tag"
+      (is (= {"!map" [["timeout" {"!tag" ["!duration" "10s"]}]
+                      ["listen" {"!tag" ["!port" ":8080"]}]
+                      ["count" 10]]}
+             (yaml/load "timeout: 10s\nlisten: :8080\ncount: 10\n" opts))))
+    (testing "Quoted and explicitly tagged scalars are left alone"
+      (is (= ["10s" "10s"]
+             (yaml/load "['10s', !!str 10s]" opts))))
+    (testing "Resolvers are tried before the schema, in order"
+      (is (= [{"!tag" ["!small" "1"]} {"!tag" ["!number" "12"]}]
+             (yaml/load "[1, 12]"
+                        {:tagged true
+                         :resolvers [{:tag "!small" :pattern "\\d"}
+                                     {:tag "!number" :pattern "\\d+"}]}))))
+    (testing "Strings a resolver matches are quoted when dumped"
+      (is (= "- '10s'\n- 10x\n- !duration 5m\n"
+             (yaml/dump ["10s" "10x" {"!tag" ["!duration" "5m"]}] opts))))
+    (testing "A resolver needs a tag and a pattern"
+      (is (thrown-with-msg?
+           clojure.lang.ExceptionInfo
+           #"A resolver needs a string tag and pattern"
+           (yaml/load "a" {:resolvers [{:tag "!x"}]}))))))
+
+(deftest test-plain-scalars
+  (testing "Scalars typed by the schema carry their text with {:plain true}"
+    (is (= [{"!plain" ["10" 10]} "10" {"!tag" ["!duration" "10s"]}
+            {"!plain" ["yes" "yes"]}]
+           (yaml/load "[10, '10', 10s, yes]"
+                      {:tagged true :plain true
+                       :resolvers [{:tag "!duration" :pattern "\\d+s"}]}))))
+  (testing "The non-specific tag ! dumps a string that is never plain"
+    (is (= "- 'abc'\n- abc\n"
+           (yaml/dump [{"!tag" ["!" "abc"]} "abc"] {:tagged true})))))
+
+(defn- include-fixture
+  "Write `files`, a map of relative path to text, under a new temporary
+  directory and return the directory's canonical path."
+  [files]
+  (let [dir (.getCanonicalPath
+             (.toFile (java.nio.file.Files/createTempDirectory
+                       "yamlstar-include"
+                       (make-array java.nio.file.attribute.FileAttribute 0))))]
+    (doseq [[path text] files]
+      (let [file (java.io.File. dir ^String path)]
+        (.mkdirs (.getParentFile file))
+        (spit file text)))
+    dir))
+
+(deftest test-include
+  (let [root (include-fixture
+              {"main.yaml" "db: !include common/db.yaml\n"
+               "common/db.yaml" "host: localhost\nport: !include port.yaml\n"
+               "common/port.yaml" "5432\n"
+               "loop/a.yaml" "b: !include b.yaml\n"
+               "loop/b.yaml" "a: !include a.yaml\n"
+               "bad.yaml" "x: *nope\n"})
+        opts (fn [file] {:include {:root root :file (str root "/" file)}})]
+    (testing "Includes are spliced in relative to the including file"
+      (is (= {"db" {"host" "localhost" "port" 5432}}
+             (yaml/load (slurp (str root "/main.yaml")) (opts "main.yaml"))))
+      (is (= {"!map" [["db" {"!map" [["host" "localhost"] ["port" 5432]]}]]}
+             (yaml/load (slurp (str root "/main.yaml"))
+                        (assoc (opts "main.yaml") :tagged true)))))
+    (testing "Without a file, includes start from the root"
+      (is (= [5432] (yaml/load "[!include common/port.yaml]" {:include {:root root}}))))
+    (testing "Without the include option, !include is an unknown tag"
+      (is (= {"!tag" ["!include" "common/port.yaml"]}
+             (yaml/load "!include common/port.yaml" {:tagged true}))))
+    (testing "Paths outside the root are rejected"
+      (is (thrown-with-msg?
+           clojure.lang.ExceptionInfo
+           #"escapes the include root: \.\./x\.yaml \(include chain: main\.yaml -> "
+           (yaml/load "!include ../x.yaml" (opts "main.yaml"))))
+      (is (thrown-with-msg?
+           clojure.lang.ExceptionInfo
+           #"must be relative"
+           (yaml/load "!include /etc/passwd" (opts "main.yaml")))))
+    (testing "Symlinks out of the root are rejected"
+      (let [outside (include-fixture {"secret.yaml" "s: 1\n"})]
+        (java.nio.file.Files/createSymbolicLink
+         (.toPath (java.io.File. root "link.yaml"))
+         (.toPath (java.io.File. outside "secret.yaml"))
+         (make-array java.nio.file.attribute.FileAttribute 0))
+        (is (thrown-with-msg?
+             clojure.lang.ExceptionInfo
+             #"escapes the include root: link\.yaml"
+             (yaml/load "!include link.yaml" (opts "main.yaml"))))))
+    (testing "Cycles are reported with the chain"
+      (is (thrown-with-msg?
+           clojure.lang.ExceptionInfo
+           #"Include cycle \(include chain: loop/a\.yaml -> loop/b\.yaml -> loop/a\.yaml\)"
+           (yaml/load (slurp (str root "/loop/a.yaml")) (opts "loop/a.yaml")))))
+    (testing "Errors in included files carry the chain"
+      (is (thrown-with-msg?
+           clojure.lang.ExceptionInfo
+           #"\(include chain: main\.yaml -> bad\.yaml\)"
+           (yaml/load "!include bad.yaml" (opts "main.yaml"))))
+      (is (thrown-with-msg?
+           clojure.lang.ExceptionInfo
+           #"Cannot read included file missing\.yaml: .*\(include chain: main\.yaml -> "
+           (yaml/load "!include missing.yaml" (opts "main.yaml")))))))
+
+(deftest test-merge-keys
+  (let [yaml (str "base: &base {image: app, port: 80}\n"
+                  "extra: &extra {port: 8080, debug: true}\n"
+                  "one:\n  <<: *base\n  image: web\n"
+                  "many:\n  <<: [*extra, *base]\n  name: svc\n")]
+    (testing "<< is an ordinary key unless merging is turned on"
+      (is (= {"<<" {"image" "app" "port" 80} "image" "web"}
+             (get (yaml/load yaml) "one"))))
+    (testing "A single alias is merged, and explicit keys win"
+      (is (= {"image" "web" "port" 80}
+             (get (yaml/load yaml {:merge true}) "one"))))
+    (testing "A sequence of aliases is merged, earlier ones first"
+      (is (= {"port" 8080 "debug" true "image" "app" "name" "svc"}
+             (get (yaml/load yaml {:merge true}) "many")))
+      (is (= {"!map" [["port" 8080] ["debug" true] ["image" "app"] ["name" "svc"]]}
+             (get-in (yaml/load yaml {:merge true :tagged true})
+                     ["!map" 3 1]))))
+    (testing "Merging works with any schema, but not for a quoted <<"
+      (is (= {"a" "1" "b" "2"}
+             (yaml/load "<<: {a: 1}\nb: 2\n" {:merge true :schema "failsafe"})))
+      (is (= {"<<" {"a" 1}}
+             (yaml/load "'<<': {a: 1}\n" {:merge true}))))
+    (testing "<< is quoted when dumped, so it loads back as a key"
+      (is (= "'<<': 1\n"
+             (yaml/dump {"<<" 1} {:merge true}))))))
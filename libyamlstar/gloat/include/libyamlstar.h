@@ -11,7 +11,16 @@ char *yamlstar_load(graal_isolatethread_t *thread, const char *yaml);
 char *yamlstar_load_all(graal_isolatethread_t *thread, const char *yaml);
 char *yamlstar_dump(graal_isolatethread_t *thread, const char *data_json);
 char *yamlstar_dump_all(graal_isolatethread_t *thread, const char *data_json);
+char *yamlstar_load_tagged(graal_isolatethread_t *thread, const char *yaml);
+char *yamlstar_load_all_tagged(graal_isolatethread_t *thread, const char *yaml);
+char *yamlstar_dump_tagged(graal_isolatethread_t *thread, const char *data_json);
+char *yamlstar_dump_all_tagged(graal_isolatethread_t *thread, const char *data_json);
+char *yamlstar_load_with_options(graal_isolatethread_t *thread, const char *yaml, const char *options_json);
+char *yamlstar_load_all_with_options(graal_isolatethread_t *thread, const char *yaml, const char *options_json);
+char *yamlstar_dump_with_options(graal_isolatethread_t *thread, const char *data_json, const char *options_json);
+char *yamlstar_dump_all_with_options(graal_isolatethread_t *thread, const char *data_json, const char *options_json);
 char *yamlstar_version(graal_isolatethread_t *thread);
+void yamlstar_free(graal_isolatethread_t *thread, char *ptr);
 
 #ifdef __cplusplus
 }
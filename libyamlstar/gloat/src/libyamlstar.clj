@@ -2,6 +2,7 @@
   "Shared library bridge - Gloat EXPORT-based C API for YAMLStar"
   (:require [clojure.string :as str]
             [yamlstar.api :as yaml]
+            [yamlstar.numbers :as numbers]
             [ys.json :as json]))
 
 (def EXPORT
@@ -15,7 +16,16 @@
    "yamlstar-load-all"        [:int :str :str]
    "yamlstar-dump"            [:int :str :str]
    "yamlstar-dump-all"        [:int :str :str]
-   "yamlstar-version"         [:int :str]})
+   "yamlstar-load-tagged"     [:int :str :str]
+   "yamlstar-load-all-tagged" [:int :str :str]
+   "yamlstar-dump-tagged"     [:int :str :str]
+   "yamlstar-dump-all-tagged" [:int :str :str]
+   "yamlstar-load-with-options"     [:int :str :str :str]
+   "yamlstar-load-all-with-options" [:int :str :str :str]
+   "yamlstar-dump-with-options"     [:int :str :str :str]
+   "yamlstar-dump-all-with-options" [:int :str :str :str]
+   "yamlstar-version"         [:int :str]
+   "yamlstar-free"            [:int :ptr :void]})
 
 ;; The public C API historically exposed GraalVM isolate lifecycle functions.
 ;; Glojure runs inside Go's process-wide runtime and needs no isolate, so these
@@ -41,6 +51,49 @@
     (sequential? x) (map nil-keys->string x)
     :else x))
 
+(defn- bang-key?
+  "Whether k is a string key starting with !."
+  [k]
+  (and (string? k) (str/starts-with? k "!")))
+
+(defn plain-encode
+  "Prepare loaded data for plain JSON from the *-with-options exports.
+  Replace infinite and NaN floats, which JSON cannot express, with the
+  {\"!float\" text} sentinel of the tagged encoding, and put another ! in
+  front of string keys starting with !, so that no mapping reads as the
+  sentinel. The plain exports leave data as it is, for the bindings that
+  read them."
+  [x]
+  (cond
+    (numbers/non-finite-text x) {"!float" (numbers/non-finite-text x)}
+    (map? x) (apply array-map
+                    (mapcat (fn [[k v]]
+                              [(if (bang-key? k) (str "!" k) k)
+                               (plain-encode v)])
+                            x))
+    (vector? x) (mapv plain-encode x)
+    (sequential? x) (map plain-encode x)
+    :else x))
+
+(defn plain-decode
+  "Undo plain-encode on plain JSON data to dump: turn {\"!float\" text}
+  sentinels back into infinite and NaN floats and take the extra ! off
+  keys."
+  [x]
+  (cond
+    (and (map? x) (= 1 (count x)) (string? (get x "!float"))
+         (numbers/non-finite-value (get x "!float")))
+    (numbers/non-finite-value (get x "!float"))
+
+    (map? x) (apply array-map
+                    (mapcat (fn [[k v]]
+                              [(if (bang-key? k) (subs k 1) k)
+                               (plain-decode v)])
+                            x))
+    (vector? x) (mapv plain-decode x)
+    (sequential? x) (map plain-decode x)
+    :else x))
+
 (defn yamlstar-load
   "Load YAML string, return JSON string with {:data ...} or {:error ...}"
   [_thread yaml-str]
@@ -91,7 +144,137 @@
                           :type "Exception"
                           :message (str e)}}))))
 
+;; The tagged exports speak the encoding of yamlstar.api/load with
+;; {:tagged true}: mappings are {"!map": [[key, value], ...]}, floats are
+;; {"!float": text} and nodes with other tags are {"!tag": [tag, value]}.
+;; Language bindings use it to keep everything JSON cannot express.
+
+(defn yamlstar-load-tagged
+  "Load YAML string in the tagged encoding, return JSON string with {:data ...} or {:error ...}"
+  [_thread yaml-str]
+  (try
+    (json/dump {:data (yaml/load yaml-str {:tagged true})})
+    (catch #?(:glj go/any :lg Exception) e
+      (json/dump {:error {:cause (str e)
+                          :type "Exception"
+                          :message (str e)}}))))
+
+(defn yamlstar-load-all-tagged
+  "Load all YAML documents in the tagged encoding, return JSON string with {:data [...]} or {:error ...}"
+  [_thread yaml-str]
+  (try
+    (json/dump {:data (yaml/load-all yaml-str {:tagged true})})
+    (catch #?(:glj go/any :lg Exception) e
+      (json/dump {:error {:cause (str e)
+                          :type "Exception"
+                          :message (str e)}}))))
+
+(defn yamlstar-dump-tagged
+  "Dump one value in the tagged encoding to YAML, return JSON string with {:data ...} or {:error ...}"
+  [_thread data-json]
+  (try
+    (json/dump {:data (yaml/dump (json/load data-json) {:tagged true})})
+    (catch #?(:glj go/any :lg Exception) e
+      (json/dump {:error {:cause (str e)
+                          :type "Exception"
+                          :message (str e)}}))))
+
+(defn yamlstar-dump-all-tagged
+  "Dump documents in the tagged encoding to YAML, return JSON string with {:data ...} or {:error ...}"
+  [_thread data-json]
+  (try
+    (json/dump {:data (yaml/dump-all (json/load data-json) {:tagged true})})
+    (catch #?(:glj go/any :lg Exception) e
+      (json/dump {:error {:cause (str e)
+                          :type "Exception"
+                          :message (str e)}}))))
+
+;; The *-with-options exports take a JSON object of yamlstar.api options as
+;; their last argument, such as {"tagged": true, "schema": "yaml11"}. They
+;; speak the tagged encoding when "tagged" is true, else plain JSON with
+;; plain-encode applied.
+
+(defn- keywordize
+  "Turn the string keys of every JSON object in `x` into keywords."
+  [x]
+  (cond
+    (map? x) (into {} (map (fn [[k v]] [(keyword k) (keywordize v)])) x)
+    (sequential? x) (mapv keywordize x)
+    :else x))
+
+(defn- read-options
+  "Read an options JSON object into a yamlstar.api options map."
+  [options-json]
+  (keywordize (json/load options-json)))
+
+(defn- load-data
+  "Prepare loaded data for JSON as the options ask."
+  [result opts]
+  (if (:tagged opts)
+    result
+    (-> result nil-keys->string plain-encode)))
+
+(defn- dump-data
+  "Read JSON data to dump as the options ask."
+  [data-json opts]
+  (if (:tagged opts)
+    (json/load data-json)
+    (plain-decode (json/load data-json))))
+
+(defn yamlstar-load-with-options
+  "Load YAML string with options, return JSON string with {:data ...} or {:error ...}"
+  [_thread yaml-str options-json]
+  (try
+    (let [opts (read-options options-json)]
+      (json/dump {:data (load-data (yaml/load yaml-str opts) opts)}))
+    (catch #?(:glj go/any :lg Exception) e
+      (json/dump {:error {:cause (str e)
+                          :type "Exception"
+                          :message (str e)}}))))
+
+(defn yamlstar-load-all-with-options
+  "Load all YAML documents with options, return JSON string with {:data [...]} or {:error ...}"
+  [_thread yaml-str options-json]
+  (try
+    (let [opts (read-options options-json)]
+      (json/dump {:data (load-data (yaml/load-all yaml-str opts) opts)}))
+    (catch #?(:glj go/any :lg Exception) e
+      (json/dump {:error {:cause (str e)
+                          :type "Exception"
+                          :message (str e)}}))))
+
+(defn yamlstar-dump-with-options
+  "Dump one JSON-encoded value to YAML with options, return JSON string with {:data ...} or {:error ...}"
+  [_thread data-json options-json]
+  (try
+    (let [opts (read-options options-json)]
+      (json/dump {:data (yaml/dump (dump-data data-json opts) opts)}))
+    (catch #?(:glj go/any :lg Exception) e
+      (json/dump {:error {:cause (str e)
+                          :type "Exception"
+                          :message (str e)}}))))
+
+(defn yamlstar-dump-all-with-options
+  "Dump JSON-encoded documents to YAML with options, return JSON string with {:data ...} or {:error ...}"
+  [_thread data-json options-json]
+  (try
+    (let [opts (read-options options-json)]
+      (json/dump {:data (yaml/dump-all (dump-data data-json opts) opts)}))
+    (catch #?(:glj go/any :lg Exception) e
+      (json/dump {:error {:cause (str e)
+                          :type "Exception"
+                          :message (str e)}}))))
+
 (defn yamlstar-version
   "Return the YAMLStar version string"
   [_thread]
   (yaml/version))
+
+(defn yamlstar-free
+  "Release a string previously returned by one of the yamlstar-* exports.
+  Gloat returns :str results as C.CString buffers, allocated with C malloc,
+  so callers must hand every returned buffer back here exactly once."
+  [_thread ptr]
+  (when ptr
+    (C.free ptr))
+  nil)
@@ -3,6 +3,9 @@
 
 package libyamlstar;
 
+import java.nio.charset.StandardCharsets;
+
+import org.graalvm.nativeimage.UnmanagedMemory;
 import org.graalvm.nativeimage.c.function.CEntryPoint;
 import org.graalvm.nativeimage.c.type.CCharPointer;
 import org.graalvm.nativeimage.c.type.CTypeConversion;
@@ -30,10 +33,7 @@ public final class API {
         String json = libyamlstar.core.loadYaml(yaml);
         debug("API - java response string: " + json);
 
-        try (CTypeConversion.CCharPointerHolder holder =
-                CTypeConversion.toCString(json)) {
-            return holder.get();
-        }
+        return toUnmanagedCString(json);
     }
 
     /**
@@ -56,10 +56,7 @@ public final class API {
         String json = libyamlstar.core.loadYamlAll(yaml);
         debug("API - java response string: " + json);
 
-        try (CTypeConversion.CCharPointerHolder holder =
-                CTypeConversion.toCString(json)) {
-            return holder.get();
-        }
+        return toUnmanagedCString(json);
     }
 
     /**
@@ -82,10 +79,7 @@ public final class API {
         String json = libyamlstar.core.dumpYaml(data);
         debug("API - java response string: " + json);
 
-        try (CTypeConversion.CCharPointerHolder holder =
-                CTypeConversion.toCString(json)) {
-            return holder.get();
-        }
+        return toUnmanagedCString(json);
     }
 
     /**
@@ -108,10 +102,207 @@ public final class API {
         String json = libyamlstar.core.dumpYamlAll(data);
         debug("API - java response string: " + json);
 
-        try (CTypeConversion.CCharPointerHolder holder =
-                CTypeConversion.toCString(json)) {
-            return holder.get();
-        }
+        return toUnmanagedCString(json);
+    }
+
+    /**
+     * Load a single YAML document in the tagged encoding.
+     *
+     * @param isolateId The GraalVM isolate thread context
+     * @param yamlStr The YAML string to parse
+     * @return JSON string: {"data": ...} on success, {"error": {...}} on failure
+     */
+    @CEntryPoint(name = "yamlstar_load_tagged")
+    public static @CConst CCharPointer loadTagged(
+        @CEntryPoint.IsolateThreadContext long isolateId,
+        @CConst CCharPointer yamlStr
+    ) {
+        debug("API - called yamlstar_load_tagged");
+
+        String input = CTypeConversion.toJavaString(yamlStr);
+        debug("API - java input string: " + input);
+
+        String json = libyamlstar.core.loadYamlTagged(input);
+        debug("API - java response string: " + json);
+
+        return toUnmanagedCString(json);
+    }
+
+    /**
+     * Load all YAML documents in the tagged encoding.
+     *
+     * @param isolateId The GraalVM isolate thread context
+     * @param yamlStr The YAML string containing one or more documents
+     * @return JSON string: {"data": ...} on success, {"error": {...}} on failure
+     */
+    @CEntryPoint(name = "yamlstar_load_all_tagged")
+    public static @CConst CCharPointer loadAllTagged(
+        @CEntryPoint.IsolateThreadContext long isolateId,
+        @CConst CCharPointer yamlStr
+    ) {
+        debug("API - called yamlstar_load_all_tagged");
+
+        String input = CTypeConversion.toJavaString(yamlStr);
+        debug("API - java input string: " + input);
+
+        String json = libyamlstar.core.loadYamlAllTagged(input);
+        debug("API - java response string: " + json);
+
+        return toUnmanagedCString(json);
+    }
+
+    /**
+     * Dump one value in the tagged encoding and return YAML in JSON response envelope.
+     *
+     * @param isolateId The GraalVM isolate thread context
+     * @param dataJson The tagged JSON value to dump
+     * @return JSON string: {"data": ...} on success, {"error": {...}} on failure
+     */
+    @CEntryPoint(name = "yamlstar_dump_tagged")
+    public static @CConst CCharPointer dumpTagged(
+        @CEntryPoint.IsolateThreadContext long isolateId,
+        @CConst CCharPointer dataJson
+    ) {
+        debug("API - called yamlstar_dump_tagged");
+
+        String input = CTypeConversion.toJavaString(dataJson);
+        debug("API - java input string: " + input);
+
+        String json = libyamlstar.core.dumpYamlTagged(input);
+        debug("API - java response string: " + json);
+
+        return toUnmanagedCString(json);
+    }
+
+    /**
+     * Dump documents in the tagged encoding and return YAML in JSON response envelope.
+     *
+     * @param isolateId The GraalVM isolate thread context
+     * @param dataJson The tagged JSON array of documents to dump
+     * @return JSON string: {"data": ...} on success, {"error": {...}} on failure
+     */
+    @CEntryPoint(name = "yamlstar_dump_all_tagged")
+    public static @CConst CCharPointer dumpAllTagged(
+        @CEntryPoint.IsolateThreadContext long isolateId,
+        @CConst CCharPointer dataJson
+    ) {
+        debug("API - called yamlstar_dump_all_tagged");
+
+        String input = CTypeConversion.toJavaString(dataJson);
+        debug("API - java input string: " + input);
+
+        String json = libyamlstar.core.dumpYamlAllTagged(input);
+        debug("API - java response string: " + json);
+
+        return toUnmanagedCString(json);
+    }
+
+    /**
+     * Load a single YAML document with options and return JSON.
+     *
+     * @param isolateId The GraalVM isolate thread context
+     * @param yamlStr The YAML string to parse
+     * @param optionsJson A JSON object of options, such as {"tagged": true, "schema": "yaml11"}
+     * @return JSON string: {"data": ...} on success, {"error": {...}} on failure
+     */
+    @CEntryPoint(name = "yamlstar_load_with_options")
+    public static @CConst CCharPointer loadWithOptions(
+        @CEntryPoint.IsolateThreadContext long isolateId,
+        @CConst CCharPointer yamlStr,
+        @CConst CCharPointer optionsJson
+    ) {
+        debug("API - called yamlstar_load_with_options");
+
+        String input = CTypeConversion.toJavaString(yamlStr);
+        String options = CTypeConversion.toJavaString(optionsJson);
+        debug("API - java input string: " + input);
+        debug("API - java options string: " + options);
+
+        String json = libyamlstar.core.loadYamlWithOptions(input, options);
+        debug("API - java response string: " + json);
+
+        return toUnmanagedCString(json);
+    }
+
+    /**
+     * Load all YAML documents with options and return JSON array.
+     *
+     * @param isolateId The GraalVM isolate thread context
+     * @param yamlStr The YAML string containing one or more documents
+     * @param optionsJson A JSON object of options, such as {"tagged": true, "schema": "yaml11"}
+     * @return JSON string: {"data": ...} on success, {"error": {...}} on failure
+     */
+    @CEntryPoint(name = "yamlstar_load_all_with_options")
+    public static @CConst CCharPointer loadAllWithOptions(
+        @CEntryPoint.IsolateThreadContext long isolateId,
+        @CConst CCharPointer yamlStr,
+        @CConst CCharPointer optionsJson
+    ) {
+        debug("API - called yamlstar_load_all_with_options");
+
+        String input = CTypeConversion.toJavaString(yamlStr);
+        String options = CTypeConversion.toJavaString(optionsJson);
+        debug("API - java input string: " + input);
+        debug("API - java options string: " + options);
+
+        String json = libyamlstar.core.loadYamlAllWithOptions(input, options);
+        debug("API - java response string: " + json);
+
+        return toUnmanagedCString(json);
+    }
+
+    /**
+     * Dump one value with options and return YAML in JSON response envelope.
+     *
+     * @param isolateId The GraalVM isolate thread context
+     * @param dataJson The JSON value to dump
+     * @param optionsJson A JSON object of options, such as {"tagged": true, "schema": "yaml11"}
+     * @return JSON string: {"data": ...} on success, {"error": {...}} on failure
+     */
+    @CEntryPoint(name = "yamlstar_dump_with_options")
+    public static @CConst CCharPointer dumpWithOptions(
+        @CEntryPoint.IsolateThreadContext long isolateId,
+        @CConst CCharPointer dataJson,
+        @CConst CCharPointer optionsJson
+    ) {
+        debug("API - called yamlstar_dump_with_options");
+
+        String input = CTypeConversion.toJavaString(dataJson);
+        String options = CTypeConversion.toJavaString(optionsJson);
+        debug("API - java input string: " + input);
+        debug("API - java options string: " + options);
+
+        String json = libyamlstar.core.dumpYamlWithOptions(input, options);
+        debug("API - java response string: " + json);
+
+        return toUnmanagedCString(json);
+    }
+
+    /**
+     * Dump documents with options and return YAML in JSON response envelope.
+     *
+     * @param isolateId The GraalVM isolate thread context
+     * @param dataJson The JSON array of documents to dump
+     * @param optionsJson A JSON object of options, such as {"tagged": true, "schema": "yaml11"}
+     * @return JSON string: {"data": ...} on success, {"error": {...}} on failure
+     */
+    @CEntryPoint(name = "yamlstar_dump_all_with_options")
+    public static @CConst CCharPointer dumpAllWithOptions(
+        @CEntryPoint.IsolateThreadContext long isolateId,
+        @CConst CCharPointer dataJson,
+        @CConst CCharPointer optionsJson
+    ) {
+        debug("API - called yamlstar_dump_all_with_options");
+
+        String input = CTypeConversion.toJavaString(dataJson);
+        String options = CTypeConversion.toJavaString(optionsJson);
+        debug("API - java input string: " + input);
+        debug("API - java options string: " + options);
+
+        String json = libyamlstar.core.dumpYamlAllWithOptions(input, options);
+        debug("API - java response string: " + json);
+
+        return toUnmanagedCString(json);
     }
 
     /**
@@ -126,10 +317,37 @@ public final class API {
     ) {
         String ver = libyamlstar.core.version();
 
-        try (CTypeConversion.CCharPointerHolder holder =
-                CTypeConversion.toCString(ver)) {
-            return holder.get();
+        return toUnmanagedCString(ver);
+    }
+
+    /**
+     * Free a string returned by one of the yamlstar_* entry points.
+     *
+     * @param isolateId The GraalVM isolate thread context
+     * @param ptr A pointer previously returned by this library
+     */
+    @CEntryPoint(name = "yamlstar_free")
+    public static void free(
+        @CEntryPoint.IsolateThreadContext long isolateId,
+        CCharPointer ptr
+    ) {
+        if (ptr.isNonNull()) {
+            UnmanagedMemory.free(ptr);
+        }
+    }
+
+    /**
+     * Copy a Java string into a NUL-terminated buffer that outlives the call.
+     * The caller owns the buffer and must release it with yamlstar_free.
+     */
+    private static CCharPointer toUnmanagedCString(String s) {
+        byte[] bytes = s.getBytes(StandardCharsets.UTF_8);
+        CCharPointer ptr = UnmanagedMemory.malloc(bytes.length + 1);
+        for (int i = 0; i < bytes.length; i++) {
+            ptr.write(i, bytes[i]);
         }
+        ptr.write(bytes.length, (byte) 0);
+        return ptr;
     }
 
     private static void debug(String s) {
@@ -1,15 +1,25 @@
 (ns libyamlstar.core
   "Shared library core - bridges Clojure to C API"
   (:require [clojure.data.json :as json]
-            [yamlstar.api :as yaml])
+            [clojure.string :as str]
+            [yamlstar.api :as yaml]
+            [yamlstar.numbers :as numbers])
   (:gen-class
    :methods [^:static [loadYaml [String] String]
              ^:static [loadYamlAll [String] String]
              ^:static [dumpYaml [String] String]
              ^:static [dumpYamlAll [String] String]
+             ^:static [loadYamlTagged [String] String]
+             ^:static [loadYamlAllTagged [String] String]
+             ^:static [dumpYamlTagged [String] String]
+             ^:static [dumpYamlAllTagged [String] String]
+             ^:static [loadYamlWithOptions [String String] String]
+             ^:static [loadYamlAllWithOptions [String String] String]
+             ^:static [dumpYamlWithOptions [String String] String]
+             ^:static [dumpYamlAllWithOptions [String String] String]
              ^:static [version [] String]]))
 
-(declare json-write-str error-map debug)
+(declare json-write-str error-map debug plain-encode plain-decode)
 
 (defn -loadYaml
   "Load a single YAML document, return JSON string with result or error"
@@ -67,6 +77,98 @@
     (debug "libyamlstar dump-all - response:" resp)
     resp))
 
+(defn- respond
+  "Call f, return JSON string with its result or error"
+  [label f input]
+  (debug (str "libyamlstar " label " - input:") input)
+  (let [resp (try
+               (json-write-str {:data (f input)})
+               (catch Exception e
+                 (-> e error-map json-write-str)))]
+    (debug (str "libyamlstar " label " - response:") resp)
+    resp))
+
+(defn -loadYamlTagged
+  "Load a single YAML document in the tagged encoding"
+  [^String yaml-str]
+  (respond "load-tagged" #(yaml/load % {:tagged true}) yaml-str))
+
+(defn -loadYamlAllTagged
+  "Load all YAML documents in the tagged encoding"
+  [^String yaml-str]
+  (respond "load-all-tagged" #(yaml/load-all % {:tagged true}) yaml-str))
+
+(defn -dumpYamlTagged
+  "Dump one value in the tagged encoding to YAML"
+  [^String data-json]
+  (respond "dump-tagged" #(yaml/dump (json/read-str %) {:tagged true}) data-json))
+
+(defn -dumpYamlAllTagged
+  "Dump documents in the tagged encoding to YAML"
+  [^String data-json]
+  (respond "dump-all-tagged" #(yaml/dump-all (json/read-str %) {:tagged true}) data-json))
+
+;; The *WithOptions methods take a JSON object of yamlstar.api options, such
+;; as {"tagged": true, "schema": "yaml11"}. They speak the tagged encoding
+;; when "tagged" is true, else plain JSON with plain-encode applied.
+
+(defn- read-options
+  "Read an options JSON object into a yamlstar.api options map."
+  [^String options-json]
+  (json/read-str options-json :key-fn keyword))
+
+(defn- with-options
+  "Call f with the input and the options read from options-json, return
+  JSON string with its result or error"
+  [label f input options-json]
+  (respond label
+           (fn [input]
+             (let [opts (read-options options-json)]
+               (f input opts)))
+           input))
+
+(defn- load-data
+  "Prepare loaded data for JSON as the options ask."
+  [result opts]
+  (if (:tagged opts)
+    result
+    (plain-encode result)))
+
+(defn -loadYamlWithOptions
+  "Load a single YAML document with options"
+  [^String yaml-str ^String options-json]
+  (with-options "load-with-options"
+    #(load-data (yaml/load %1 %2) %2)
+    yaml-str options-json))
+
+(defn -loadYamlAllWithOptions
+  "Load all YAML documents with options"
+  [^String yaml-str ^String options-json]
+  (with-options "load-all-with-options"
+    #(load-data (yaml/load-all %1 %2) %2)
+    yaml-str options-json))
+
+(defn- dump-data
+  "Read JSON data to dump as the options ask."
+  [^String data-json opts]
+  (if (:tagged opts)
+    (json/read-str data-json)
+    (plain-decode (json/read-str data-json))))
+
+(defn -dumpYamlWithOptions
+  "Dump one JSON-encoded value to YAML with options"
+  [^String data-json ^String options-json]
+  (with-options "dump-with-options"
+    #(yaml/dump (dump-data %1 %2) %2)
+    data-json options-json))
+
+(defn -dumpYamlAllWithOptions
+  "Dump JSON-encoded documents to YAML with options"
+  [^String data-json ^String options-json]
+  (with-options "dump-all-with-options"
+    #(yaml/dump-all (dump-data %1 %2) %2)
+    data-json options-json))
+
 (defn -version
   "Return the YAMLStar version string"
   []
@@ -92,6 +194,49 @@
     (seq? x) (map nil-keys->string x)
     :else x))
 
+(defn- bang-key?
+  "Whether k is a string key starting with !."
+  [k]
+  (and (string? k) (str/starts-with? k "!")))
+
+(defn plain-encode
+  "Prepare loaded data for plain JSON from the *WithOptions methods.
+  Replace infinite and NaN floats, which JSON cannot express, with the
+  {\"!float\" text} sentinel of the tagged encoding, and put another ! in
+  front of string keys starting with !, so that no mapping reads as the
+  sentinel. The plain methods leave data as it is, for the bindings that
+  read them."
+  [x]
+  (cond
+    (numbers/non-finite-text x) {"!float" (numbers/non-finite-text x)}
+    (map? x) (apply array-map
+                    (mapcat (fn [[k v]]
+                              [(if (bang-key? k) (str "!" k) k)
+                               (plain-encode v)])
+                            x))
+    (vector? x) (mapv plain-encode x)
+    (sequential? x) (map plain-encode x)
+    :else x))
+
+(defn plain-decode
+  "Undo plain-encode on plain JSON data to dump: turn {\"!float\" text}
+  sentinels back into infinite and NaN floats and take the extra ! off
+  keys."
+  [x]
+  (cond
+    (and (map? x) (= 1 (count x)) (string? (get x "!float"))
+         (numbers/non-finite-value (get x "!float")))
+    (numbers/non-finite-value (get x "!float"))
+
+    (map? x) (apply array-map
+                    (mapcat (fn [[k v]]
+                              [(if (bang-key? k) (subs k 1) k)
+                               (plain-decode v)])
+                            x))
+    (vector? x) (mapv plain-decode x)
+    (sequential? x) (map plain-decode x)
+    :else x))
+
 (defn json-write-str [data]
   (json/write-str (nil-keys->string data)
                   :escape-unicode false
@@ -12,11 +12,12 @@ function defineForeignFunctionInterface() {
   return ffi.Library(libPath, {
     'graal_create_isolate': ['int', ['pointer', 'pointer', 'pointer']],
     'graal_tear_down_isolate': ['int', ['pointer']],
-    'yamlstar_load': ['string', ['pointer', 'string']],
-    'yamlstar_load_all': ['string', ['pointer', 'string']],
-    'yamlstar_dump': ['string', ['pointer', 'string']],
-    'yamlstar_dump_all': ['string', ['pointer', 'string']],
-    'yamlstar_version': ['string', ['pointer']],
+    'yamlstar_load': ['pointer', ['pointer', 'string']],
+    'yamlstar_load_all': ['pointer', ['pointer', 'string']],
+    'yamlstar_dump': ['pointer', ['pointer', 'string']],
+    'yamlstar_dump_all': ['pointer', ['pointer', 'string']],
+    'yamlstar_version': ['pointer', ['pointer']],
+    'yamlstar_free': ['void', ['pointer', 'pointer']],
   });
 }
 
@@ -37,10 +38,10 @@ class YAMLStar {
   }
 
   load(input) {
-    let dataJson = this.libyamlstar.yamlstar_load(
+    let dataJson = this.takeString(this.libyamlstar.yamlstar_load(
       this.isolatethread.deref(),
       input,
-    );
+    ));
 
     let resp = JSON.parse(dataJson);
 
@@ -56,10 +57,10 @@ class YAMLStar {
   }
 
   loadAll(input) {
-    let dataJson = this.libyamlstar.yamlstar_load_all(
+    let dataJson = this.takeString(this.libyamlstar.yamlstar_load_all(
       this.isolatethread.deref(),
       input,
-    );
+    ));
 
     let resp = JSON.parse(dataJson);
 
@@ -75,10 +76,10 @@ class YAMLStar {
   }
 
   dump(value) {
-    let dataJson = this.libyamlstar.yamlstar_dump(
+    let dataJson = this.takeString(this.libyamlstar.yamlstar_dump(
       this.isolatethread.deref(),
       JSON.stringify(value),
-    );
+    ));
 
     let resp = JSON.parse(dataJson);
 
@@ -94,10 +95,10 @@ class YAMLStar {
   }
 
   dumpAll(values) {
-    let dataJson = this.libyamlstar.yamlstar_dump_all(
+    let dataJson = this.takeString(this.libyamlstar.yamlstar_dump_all(
       this.isolatethread.deref(),
       JSON.stringify(values),
-    );
+    ));
 
     let resp = JSON.parse(dataJson);
 
@@ -113,7 +114,18 @@ class YAMLStar {
   }
 
   version() {
-    return this.libyamlstar.yamlstar_version(this.isolatethread.deref());
+    return this.takeString(
+      this.libyamlstar.yamlstar_version(this.isolatethread.deref()),
+    );
+  }
+
+  // Copy a string returned by libyamlstar and release its buffer.
+  takeString(ptr) {
+    try {
+      return ref.readCString(ptr, 0);
+    } finally {
+      this.libyamlstar.yamlstar_free(this.isolatethread.deref(), ptr);
+    }
   }
 
   close() {
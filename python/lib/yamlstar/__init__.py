@@ -77,23 +77,36 @@ libyamlstar = ctypes.CDLL(_libyamlstar_path)
 # null thread handle.
 yamlstar_load_fn = libyamlstar.yamlstar_load
 yamlstar_load_fn.argtypes = [ctypes.c_void_p, ctypes.c_char_p]
-yamlstar_load_fn.restype = ctypes.c_char_p
+yamlstar_load_fn.restype = ctypes.c_void_p
 
 yamlstar_load_all_fn = libyamlstar.yamlstar_load_all
 yamlstar_load_all_fn.argtypes = [ctypes.c_void_p, ctypes.c_char_p]
-yamlstar_load_all_fn.restype = ctypes.c_char_p
+yamlstar_load_all_fn.restype = ctypes.c_void_p
 
 yamlstar_dump_fn = libyamlstar.yamlstar_dump
 yamlstar_dump_fn.argtypes = [ctypes.c_void_p, ctypes.c_char_p]
-yamlstar_dump_fn.restype = ctypes.c_char_p
+yamlstar_dump_fn.restype = ctypes.c_void_p
 
 yamlstar_dump_all_fn = libyamlstar.yamlstar_dump_all
 yamlstar_dump_all_fn.argtypes = [ctypes.c_void_p, ctypes.c_char_p]
-yamlstar_dump_all_fn.restype = ctypes.c_char_p
+yamlstar_dump_all_fn.restype = ctypes.c_void_p
 
 yamlstar_version_fn = libyamlstar.yamlstar_version
 yamlstar_version_fn.argtypes = [ctypes.c_void_p]
-yamlstar_version_fn.restype = ctypes.c_char_p
+yamlstar_version_fn.restype = ctypes.c_void_p
+
+# Every string libyamlstar returns is a buffer the caller must hand back.
+yamlstar_free_fn = libyamlstar.yamlstar_free
+yamlstar_free_fn.argtypes = [ctypes.c_void_p, ctypes.c_void_p]
+yamlstar_free_fn.restype = None
+
+
+def take_string(thread, ptr):
+  """Copy a string returned by libyamlstar and release its buffer."""
+  try:
+    return ctypes.string_at(ptr).decode()
+  finally:
+    yamlstar_free_fn(thread, ptr)
 
 
 # The YAMLStar class is the main user facing API for this module.
@@ -140,7 +153,9 @@ class YAMLStar():
     self.error = None
     yaml_bytes = ctypes.c_char_p(bytes(yaml_input, "utf8"))
 
-    data_json = yamlstar_load_fn(self._isolatethread, yaml_bytes).decode()
+    data_json = take_string(
+      self._isolatethread,
+      yamlstar_load_fn(self._isolatethread, yaml_bytes))
 
     resp = json.loads(data_json)
     self.error = resp.get('error')
@@ -167,8 +182,9 @@ class YAMLStar():
     self.error = None
     yaml_bytes = ctypes.c_char_p(bytes(yaml_input, "utf8"))
 
-    data_json = \
-      yamlstar_load_all_fn(self._isolatethread, yaml_bytes).decode()
+    data_json = take_string(
+      self._isolatethread,
+      yamlstar_load_all_fn(self._isolatethread, yaml_bytes))
 
     resp = json.loads(data_json)
     self.error = resp.get('error')
@@ -185,7 +201,9 @@ class YAMLStar():
     self.error = None
     data_bytes = ctypes.c_char_p(bytes(json.dumps(value), "utf8"))
 
-    data_json = yamlstar_dump_fn(self._isolatethread, data_bytes).decode()
+    data_json = take_string(
+      self._isolatethread,
+      yamlstar_dump_fn(self._isolatethread, data_bytes))
 
     resp = json.loads(data_json)
     self.error = resp.get('error')
@@ -202,8 +220,9 @@ class YAMLStar():
     self.error = None
     data_bytes = ctypes.c_char_p(bytes(json.dumps(values), "utf8"))
 
-    data_json = \
-      yamlstar_dump_all_fn(self._isolatethread, data_bytes).decode()
+    data_json = take_string(
+      self._isolatethread,
+      yamlstar_dump_all_fn(self._isolatethread, data_bytes))
 
     resp = json.loads(data_json)
     self.error = resp.get('error')
@@ -221,7 +240,9 @@ class YAMLStar():
     Returns:
       Version string
     """
-    return yamlstar_version_fn(self._isolatethread).decode()
+    return take_string(
+      self._isolatethread,
+      yamlstar_version_fn(self._isolatethread))
 
   def __del__(self):
     if hasattr(self, '_isolatethread'):
@@ -67,6 +67,7 @@ class YAMLStar
     extern "char* yamlstar_dump(void* thread, char* json)"
     extern "char* yamlstar_dump_all(void* thread, char* json)"
     extern "char* yamlstar_version(void* thread)"
+    extern "void yamlstar_free(void* thread, char* ptr)"
   end
 
   def self.load(yaml)
@@ -114,7 +115,7 @@ class YAMLStar
   end
 
   def version
-    LibYAMLStar.yamlstar_version(@thread).to_s
+    take_string(LibYAMLStar.yamlstar_version(@thread))
   end
 
   def close
@@ -129,11 +130,18 @@ class YAMLStar
   private
 
   def call_yaml(function, input)
-    handle_response(LibYAMLStar.public_send(function, @thread, input.to_s).to_s)
+    handle_response(take_string(LibYAMLStar.public_send(function, @thread, input.to_s)))
   end
 
   def call_json(function, value)
-    handle_response(LibYAMLStar.public_send(function, @thread, JSON.generate(value)).to_s)
+    handle_response(take_string(LibYAMLStar.public_send(function, @thread, JSON.generate(value))))
+  end
+
+  # Copy a string returned by libyamlstar and release its buffer.
+  def take_string(ptr)
+    ptr.to_s
+  ensure
+    LibYAMLStar.yamlstar_free(@thread, ptr)
   end
 
   def handle_response(json)
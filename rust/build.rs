@@ -0,0 +1,145 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Link libyamlstar at build time when the `static-link` feature is enabled.
+//!
+//! The library is found through `YAMLSTAR_LIB_DIR` if it is set, or else
+//! through `pkg-config`. Set `YAMLSTAR_STATIC=1` to link `libyamlstar.a`
+//! instead of the shared library; a directory holding only the archive is
+//! linked statically as well. A shared library's directory is added to the
+//! runtime search path of the binaries this package builds.
+//!
+//! With the `bundled` feature, the library file named by `YAMLSTAR_BUNDLE`
+//! (by default the development build in `../libyamlstar/lib`) is embedded in
+//! the crate.
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=YAMLSTAR_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=YAMLSTAR_STATIC");
+    println!("cargo:rerun-if-env-changed=YAMLSTAR_BUNDLE");
+
+    #[cfg(feature = "static-link")]
+    link();
+
+    #[cfg(feature = "bundled")]
+    bundle();
+}
+
+#[cfg(feature = "bundled")]
+fn bundle() {
+    use std::path::PathBuf;
+
+    let path = match std::env::var_os("YAMLSTAR_BUNDLE") {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let extension = match std::env::var("CARGO_CFG_TARGET_OS").as_deref() {
+                Ok("macos") => "dylib",
+                Ok("windows") => "dll",
+                _ => "so",
+            };
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("../libyamlstar/lib")
+                .join(format!("libyamlstar.{extension}.{}", env!("CARGO_PKG_VERSION")))
+        }
+    };
+    let path = std::fs::canonicalize(&path).unwrap_or_else(|err| {
+        panic!(
+            "bundled: cannot read {}: {err}\n\
+            Set YAMLSTAR_BUNDLE to the libyamlstar file to embed \
+            (try: cd libyamlstar && make native).",
+            path.display()
+        )
+    });
+
+    println!("cargo:rerun-if-changed={}", path.display());
+    println!("cargo:rustc-env=YAMLSTAR_BUNDLE_PATH={}", path.display());
+
+    // The checksum names the cache directory the library is extracted to,
+    // so the crate does not hash the embedded bytes at runtime.
+    let bytes = std::fs::read(&path)
+        .unwrap_or_else(|err| panic!("bundled: cannot read {}: {err}", path.display()));
+    let digest = <sha2::Sha256 as sha2::Digest>::digest(&bytes);
+    let hex = digest.iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+    println!("cargo:rustc-env=YAMLSTAR_BUNDLE_SHA256={hex}");
+}
+
+#[cfg(feature = "static-link")]
+fn link() {
+    use std::path::Path;
+
+    let want_static = std::env::var_os("YAMLSTAR_STATIC").is_some_and(|x| x != "0");
+
+    if let Some(dir) = std::env::var_os("YAMLSTAR_LIB_DIR") {
+        let dir = Path::new(&dir);
+        let archive_only = dir.join("libyamlstar.a").is_file()
+            && !["so", "dylib", "dll"]
+                .iter()
+                .any(|ext| dir.join(format!("libyamlstar.{ext}")).is_file());
+        let kind = if want_static || archive_only { "static" } else { "dylib" };
+
+        println!("cargo:rustc-link-search=native={}", dir.display());
+        println!("cargo:rustc-link-lib={kind}=yamlstar");
+        if kind == "static" {
+            link_runtime_deps();
+        } else {
+            rpath(dir);
+        }
+        return;
+    }
+
+    let found = pkg_config::Config::new()
+        .atleast_version(env!("CARGO_PKG_VERSION"))
+        .statik(want_static)
+        .probe("yamlstar");
+    let library = found.unwrap_or_else(|err| {
+        panic!(
+            "static-link: libyamlstar not found.\n\
+            Set YAMLSTAR_LIB_DIR to the directory holding it \
+            (try: cd libyamlstar && make native), or install yamlstar.pc.\n\
+            pkg-config said: {err}"
+        )
+    });
+    if want_static {
+        link_runtime_deps();
+    } else {
+        for dir in &library.link_paths {
+            rpath(dir);
+        }
+    }
+}
+
+/// Add `dir` to the runtime search path of ELF and Mach-O binaries, so they
+/// find the shared library without `LD_LIBRARY_PATH`. Cargo passes link
+/// arguments only to this package's own binaries, tests and examples, not to
+/// packages that depend on it.
+#[cfg(feature = "static-link")]
+fn rpath(dir: &std::path::Path) {
+    if std::env::var("CARGO_CFG_TARGET_FAMILY").is_ok_and(|family| family.contains("unix")) {
+        let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        println!("cargo:rustc-link-arg=-Wl,-rpath,{}", dir.display());
+    }
+}
+
+/// Link the system libraries the Go runtime inside libyamlstar needs.
+#[cfg(feature = "static-link")]
+fn link_runtime_deps() {
+    match std::env::var("CARGO_CFG_TARGET_OS").as_deref() {
+        Ok("linux") => {
+            for lib in ["pthread", "dl", "m"] {
+                println!("cargo:rustc-link-lib=dylib={lib}");
+            }
+        }
+        Ok("macos") => {
+            for framework in ["CoreFoundation", "Security"] {
+                println!("cargo:rustc-link-lib=framework={framework}");
+            }
+            println!("cargo:rustc-link-lib=dylib=resolv");
+        }
+        Ok("windows") => {
+            for lib in ["ws2_32", "winmm", "ntdll"] {
+                println!("cargo:rustc-link-lib=dylib={lib}");
+            }
+        }
+        _ => {}
+    }
+}
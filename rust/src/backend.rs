@@ -0,0 +1,250 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! The interface between [`crate::YAMLStar`] and the engine that does the work.
+
+use std::sync::Arc;
+
+use crate::Error;
+
+/// An engine that loads and dumps YAML for a [`crate::YAMLStar`] instance.
+///
+/// The data methods take and return JSON text in the libyamlstar response
+/// format: a successful call returns `{"data": ...}` and a failed one returns
+/// `{"error": {"cause": ..., "type": ...}}`. `Err` is reserved for failures
+/// to reach the engine at all.
+///
+/// The default backend calls libyamlstar. Use
+/// [`crate::YAMLStar::with_backend`] to plug in another one, such as
+/// [`crate::MockBackend`].
+pub trait Backend: Send + Sync {
+    /// Load the first document of a YAML stream.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called.
+    fn load(&self, yaml: &str) -> Result<String, Error>;
+
+    /// Load every document of a YAML stream.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called.
+    fn load_all(&self, yaml: &str) -> Result<String, Error>;
+
+    /// Dump a JSON value to YAML.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called.
+    fn dump(&self, data_json: &str) -> Result<String, Error>;
+
+    /// Dump a JSON array of values to a multi-document YAML stream.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called.
+    fn dump_all(&self, data_json: &str) -> Result<String, Error>;
+
+    /// Report the engine version as a plain string, not an envelope.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called.
+    fn version(&self) -> Result<String, Error>;
+
+    /// Load the first document of a YAML stream in the tagged encoding; see
+    /// [`Capabilities::tagged`].
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called. The default returns
+    /// [`Error::Unsupported`].
+    fn load_tagged(&self, _yaml: &str) -> Result<String, Error> {
+        Err(Error::Unsupported("load_tagged"))
+    }
+
+    /// Load every document of a YAML stream in the tagged encoding.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called. The default returns
+    /// [`Error::Unsupported`].
+    fn load_all_tagged(&self, _yaml: &str) -> Result<String, Error> {
+        Err(Error::Unsupported("load_all_tagged"))
+    }
+
+    /// Dump a value in the tagged encoding to YAML.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called. The default returns
+    /// [`Error::Unsupported`].
+    fn dump_tagged(&self, _data_json: &str) -> Result<String, Error> {
+        Err(Error::Unsupported("dump_tagged"))
+    }
+
+    /// Dump an array of values in the tagged encoding to a multi-document
+    /// YAML stream.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called. The default returns
+    /// [`Error::Unsupported`].
+    fn dump_all_tagged(&self, _data_json: &str) -> Result<String, Error> {
+        Err(Error::Unsupported("dump_all_tagged"))
+    }
+
+    /// Load the first document of a YAML stream with a JSON object of
+    /// options; see [`Capabilities::options`]. The response is in the tagged
+    /// encoding if the options have `"tagged": true`.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called. The default returns
+    /// [`Error::Unsupported`].
+    fn load_with_options(&self, _yaml: &str, _options_json: &str) -> Result<String, Error> {
+        Err(Error::Unsupported("load_with_options"))
+    }
+
+    /// Load every document of a YAML stream with a JSON object of options.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called. The default returns
+    /// [`Error::Unsupported`].
+    fn load_all_with_options(&self, _yaml: &str, _options_json: &str) -> Result<String, Error> {
+        Err(Error::Unsupported("load_all_with_options"))
+    }
+
+    /// Dump a value to YAML with a JSON object of options. The value is in
+    /// the tagged encoding if the options have `"tagged": true`.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called. The default returns
+    /// [`Error::Unsupported`].
+    fn dump_with_options(&self, _data_json: &str, _options_json: &str) -> Result<String, Error> {
+        Err(Error::Unsupported("dump_with_options"))
+    }
+
+    /// Dump an array of values to a multi-document YAML stream with a JSON
+    /// object of options.
+    ///
+    /// # Errors
+    /// Returns an error if the engine cannot be called. The default returns
+    /// [`Error::Unsupported`].
+    fn dump_all_with_options(&self, _data_json: &str, _options_json: &str) -> Result<String, Error> {
+        Err(Error::Unsupported("dump_all_with_options"))
+    }
+
+    /// Report which operations the engine supports. The default is every
+    /// operation but the tagged ones and those with options.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::PLAIN
+    }
+}
+
+/// Lets a backend be shared, for example to inspect a [`crate::MockBackend`]
+/// after handing it to [`crate::YAMLStar::with_backend`].
+impl<B: Backend + ?Sized> Backend for Arc<B> {
+    fn load(&self, yaml: &str) -> Result<String, Error> {
+        (**self).load(yaml)
+    }
+
+    fn load_all(&self, yaml: &str) -> Result<String, Error> {
+        (**self).load_all(yaml)
+    }
+
+    fn dump(&self, data_json: &str) -> Result<String, Error> {
+        (**self).dump(data_json)
+    }
+
+    fn dump_all(&self, data_json: &str) -> Result<String, Error> {
+        (**self).dump_all(data_json)
+    }
+
+    fn version(&self) -> Result<String, Error> {
+        (**self).version()
+    }
+
+    fn load_tagged(&self, yaml: &str) -> Result<String, Error> {
+        (**self).load_tagged(yaml)
+    }
+
+    fn load_all_tagged(&self, yaml: &str) -> Result<String, Error> {
+        (**self).load_all_tagged(yaml)
+    }
+
+    fn dump_tagged(&self, data_json: &str) -> Result<String, Error> {
+        (**self).dump_tagged(data_json)
+    }
+
+    fn dump_all_tagged(&self, data_json: &str) -> Result<String, Error> {
+        (**self).dump_all_tagged(data_json)
+    }
+
+    fn load_with_options(&self, yaml: &str, options_json: &str) -> Result<String, Error> {
+        (**self).load_with_options(yaml, options_json)
+    }
+
+    fn load_all_with_options(&self, yaml: &str, options_json: &str) -> Result<String, Error> {
+        (**self).load_all_with_options(yaml, options_json)
+    }
+
+    fn dump_with_options(&self, data_json: &str, options_json: &str) -> Result<String, Error> {
+        (**self).dump_with_options(data_json, options_json)
+    }
+
+    fn dump_all_with_options(&self, data_json: &str, options_json: &str) -> Result<String, Error> {
+        (**self).dump_all_with_options(data_json, options_json)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        (**self).capabilities()
+    }
+}
+
+/// The operations supported by a [`crate::YAMLStar`] backend.
+///
+/// Older libraries may lack some exports; the matching [`crate::YAMLStar`]
+/// methods then return [`Error::Unsupported`].
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// [`crate::YAMLStar::load`] is available.
+    pub load: bool,
+    /// [`crate::YAMLStar::load_all`] is available.
+    pub load_all: bool,
+    /// [`crate::YAMLStar::dump`] is available.
+    pub dump: bool,
+    /// [`crate::YAMLStar::dump_all`] is available.
+    pub dump_all: bool,
+    /// [`crate::YAMLStar::version`] is available.
+    pub version: bool,
+    /// Returned buffers are freed rather than leaked.
+    pub free: bool,
+    /// [`crate::ThreadMode::PerThread`] is available.
+    pub per_thread: bool,
+    /// The tagged load and dump operations are available, so values keep
+    /// what JSON cannot express: non-string keys, `.inf` and `.nan`, 64-bit
+    /// integers and custom tags. Without them [`crate::YAMLStar`] falls back
+    /// to plain JSON.
+    pub tagged: bool,
+    /// The load and dump operations that take options are available, so
+    /// [`crate::Builder::schema`] can pick a [`crate::Schema`] other than
+    /// the core one. Without the tagged operations, plain JSON then goes
+    /// through them too, as they carry `.inf` and `.nan`.
+    pub options: bool,
+}
+
+impl Capabilities {
+    /// Every operation is available.
+    pub const ALL: Self = Self {
+        load: true,
+        load_all: true,
+        dump: true,
+        dump_all: true,
+        version: true,
+        free: true,
+        per_thread: true,
+        tagged: true,
+        options: true,
+    };
+
+    /// Every operation but the tagged ones and those with options, as in a
+    /// library that predates them.
+    pub const PLAIN: Self = Self {
+        tagged: false,
+        options: false,
+        ..Self::ALL
+    };
+}
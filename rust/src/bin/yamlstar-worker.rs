@@ -0,0 +1,8 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! A worker process for `BackendKind::Worker`; see `Builder::worker_program`.
+
+fn main() {
+    yamlstar::run_worker();
+}
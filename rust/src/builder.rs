@@ -0,0 +1,546 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Configuration for creating a [`YAMLStar`] instance.
+
+#[cfg(not(feature = "static-link"))]
+use std::path::Path;
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use crate::{
+    env::{Env, EnvProvider, StdEnv},
+    ffi::FfiBackend,
+    worker::{WorkerBackend, WorkerConfig, DEFAULT_TIMEOUT},
+    Backend, CliBackend, Diagnostics, DuplicateKeys, Error, Resolver, Schema, ThreadMode,
+    VersionPolicy, YAMLStar,
+};
+#[cfg(not(feature = "static-link"))]
+use crate::{LIBYAMLSTAR_BASENAME, LIBYAMLSTAR_EXTENSION, LIBYAMLSTAR_VERSION};
+
+/// The environment variable that names the library file or its directory.
+pub const LIBRARY_ENV_VAR: &str = "YAMLSTAR_LIBRARY";
+
+/// Which [`crate::Backend`] a [`Builder`] creates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackendKind {
+    /// Use libyamlstar, or the `yaml` CLI if the library is not found.
+    #[default]
+    Auto,
+    /// Use libyamlstar only.
+    Library,
+    /// Use the `yaml` CLI only; see [`CliBackend`].
+    Cli,
+    /// Use libyamlstar in a pool of sandboxed worker processes; see
+    /// [`Builder::worker_program`].
+    Worker,
+}
+
+/// A builder for [`YAMLStar`] instances.
+///
+/// The library is searched for in this order:
+/// 1. Paths given to [`Builder::library_path`].
+/// 2. The file or directory named by `YAMLSTAR_LIBRARY`.
+/// 3. Directories given to [`Builder::search_dir`].
+/// 4. The executable's directory and `../lib` next to it.
+/// 5. `libyamlstar/lib` relative to the crate, for development.
+/// 6. `LD_LIBRARY_PATH` (`PATH` on Windows).
+/// 7. `/usr/local/lib` and `~/.local/lib`.
+///
+/// Every directory is searched for `libyamlstar.so.<version>` first. With
+/// [`Builder::unversioned_fallback`], they are then searched again for
+/// `libyamlstar.so`.
+///
+/// With the `static-link` feature the library is linked at build time, and
+/// the search settings are ignored.
+///
+/// If the library is not found, the `yaml` CLI is used instead; see
+/// [`Builder::backend`].
+///
+/// ```no_run
+/// let ys = yamlstar::YAMLStar::builder()
+///     .search_dir("/opt/yamlstar/lib")
+///     .unversioned_fallback(true)
+///     .build()
+///     .unwrap();
+/// ```
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "static-link", allow(dead_code))]
+pub struct Builder {
+    /// Library files to try before any directory search.
+    library_paths: Vec<PathBuf>,
+    /// Extra directories to search.
+    search_dirs: Vec<PathBuf>,
+    /// Whether to also look for an unversioned library file.
+    unversioned_fallback: bool,
+    /// How isolate threads are assigned to OS threads.
+    thread_mode: ThreadMode,
+    /// Which library versions are accepted.
+    version_policy: VersionPolicy,
+    /// Which backend to create.
+    backend: BackendKind,
+    /// The `yaml` executable for the CLI backend.
+    cli_path: Option<PathBuf>,
+    /// The executable for worker processes.
+    worker_program: Option<PathBuf>,
+    /// The most worker processes to run at once.
+    workers: Option<usize>,
+    /// CPU time allowed for each worker request.
+    worker_cpu_time: Option<Duration>,
+    /// Address space allowed for each worker process.
+    worker_address_space: Option<u64>,
+    /// Wall-clock time allowed for each worker request.
+    worker_timeout: Option<Duration>,
+    /// Whether integers that do not fit in an `i64` may be loaded.
+    arbitrary_precision: bool,
+    /// Whether enum variants that hold data dump as `!Variant` tags.
+    tagged_enums: bool,
+    /// How plain scalars are typed.
+    schema: Schema,
+    /// User resolvers, tried in order before the schema.
+    resolvers: Vec<Resolver>,
+    /// Whether a plain `<<` key merges mappings in any schema.
+    merge_keys: bool,
+    /// What to do with duplicate mapping keys.
+    duplicate_keys: DuplicateKeys,
+    /// The collector for load warnings, if the caller gave one.
+    diagnostics: Option<Diagnostics>,
+    /// The directory `!include` files must be in, if includes are followed.
+    include_root: Option<PathBuf>,
+    /// Where `!env` and `${NAME}` are looked up, if they are expanded.
+    env: Option<Env>,
+}
+
+impl Builder {
+    /// Create a builder with the default configuration.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Try the library file at `path` before searching any directory.
+    ///
+    /// May be called several times; paths are tried in order.
+    #[must_use]
+    pub fn library_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.library_paths.push(path.into());
+        self
+    }
+
+    /// Search `dir` for the library, after `YAMLSTAR_LIBRARY`.
+    ///
+    /// May be called several times; directories are searched in order.
+    #[must_use]
+    pub fn search_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.search_dirs.push(dir.into());
+        self
+    }
+
+    /// Search every directory in `dirs` for the library.
+    #[must_use]
+    pub fn search_dirs<I, P>(mut self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.search_dirs.extend(dirs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Also accept an unversioned `libyamlstar.so` when no versioned file is
+    /// found.
+    #[must_use]
+    pub fn unversioned_fallback(mut self, enabled: bool) -> Self {
+        self.unversioned_fallback = enabled;
+        self
+    }
+
+    /// Set how isolate threads are assigned to OS threads.
+    #[must_use]
+    pub fn thread_mode(mut self, mode: ThreadMode) -> Self {
+        self.thread_mode = mode;
+        self
+    }
+
+    /// Set which library versions are accepted. Defaults to
+    /// [`VersionPolicy::Exact`]. A library without `yamlstar_version` is only
+    /// accepted with [`VersionPolicy::Any`].
+    #[must_use]
+    pub fn version_policy(mut self, policy: VersionPolicy) -> Self {
+        self.version_policy = policy;
+        self
+    }
+
+    /// Set which backend to create. Defaults to [`BackendKind::Auto`].
+    #[must_use]
+    pub fn backend(mut self, kind: BackendKind) -> Self {
+        self.backend = kind;
+        self
+    }
+
+    /// Set the `yaml` executable for the CLI backend. Defaults to
+    /// `YAMLSTAR_CLI` or `yaml` on the `PATH`.
+    #[must_use]
+    pub fn cli_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cli_path = Some(path.into());
+        self
+    }
+
+    /// Set the executable for [`BackendKind::Worker`] processes.
+    ///
+    /// It must serve the worker protocol, either by calling
+    /// [`crate::run_worker`] from `main` like the `yamlstar-worker` binary of
+    /// this crate, or [`crate::run_worker_if_requested`] at its start.
+    /// Defaults to the `yamlstar-worker` binary next to the current
+    /// executable, or else on `PATH`.
+    #[must_use]
+    pub fn worker_program(mut self, path: impl Into<PathBuf>) -> Self {
+        self.worker_program = Some(path.into());
+        self
+    }
+
+    /// Set the most worker processes to run at once. Defaults to the number
+    /// of CPUs.
+    #[must_use]
+    pub fn workers(mut self, count: usize) -> Self {
+        self.workers = Some(count);
+        self
+    }
+
+    /// Limit the CPU time a worker may spend on one request, rounded up to
+    /// whole seconds. A worker over the limit is killed and the call returns
+    /// [`Error::WorkerCrashed`]. Only enforced on Unix.
+    #[must_use]
+    pub fn worker_cpu_time(mut self, limit: Duration) -> Self {
+        self.worker_cpu_time = Some(limit);
+        self
+    }
+
+    /// Limit the wall-clock time a worker may take to answer one request,
+    /// or to start. A worker that has not answered in time, whether it is
+    /// busy or blocked, is killed and the call returns
+    /// [`Error::WorkerTimedOut`]; the next call starts a new worker.
+    /// Defaults to 60 seconds.
+    #[must_use]
+    pub fn worker_timeout(mut self, limit: Duration) -> Self {
+        self.worker_timeout = Some(limit);
+        self
+    }
+
+    /// Limit the address space of each worker process, in bytes. The Go
+    /// runtime reserves address space up front, so leave generous headroom.
+    /// Only enforced on Unix.
+    #[must_use]
+    pub fn worker_address_space(mut self, bytes: u64) -> Self {
+        self.worker_address_space = Some(bytes);
+        self
+    }
+
+    /// Load integers of any size instead of failing on those that do not fit
+    /// in an `i64`. They deserialize into `u64`, `i128`, `u128` or
+    /// [`crate::Number`], and load into a [`crate::Value`] as
+    /// [`crate::Value::BigInt`].
+    ///
+    /// This needs a library with the tagged exports; see
+    /// [`crate::Capabilities::tagged`].
+    #[cfg(feature = "arbitrary_precision")]
+    #[must_use]
+    pub fn arbitrary_precision(mut self, enabled: bool) -> Self {
+        self.arbitrary_precision = enabled;
+        self
+    }
+
+    /// Dump enum variants that hold data as their data tagged with `!` and
+    /// the variant name, such as `!Circle {r: 1.0}`, instead of as
+    /// single-entry mappings such as `Circle: {r: 1.0}`. Loading accepts
+    /// both forms either way.
+    ///
+    /// This needs a library with the tagged exports; see
+    /// [`crate::Capabilities::tagged`].
+    #[must_use]
+    pub fn tagged_enums(mut self, enabled: bool) -> Self {
+        self.tagged_enums = enabled;
+        self
+    }
+
+    /// Set how plain scalars are typed when loading, and so which strings
+    /// are quoted when dumping. Defaults to [`Schema::Core`].
+    ///
+    /// Any other schema needs a library with the operations that take
+    /// options; see [`crate::Capabilities::options`].
+    #[must_use]
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Add an implicit resolver that tags the plain scalars it matches
+    /// before the schema types them; see [`Resolver`].
+    ///
+    /// May be called several times; pattern resolvers are tried in order,
+    /// then predicate resolvers in order. They apply to the instance built
+    /// only. Resolvers need a library with the tagged exports and the
+    /// operations that take options; see [`crate::Capabilities`].
+    #[must_use]
+    pub fn resolver(mut self, resolver: Resolver) -> Self {
+        self.resolvers.push(resolver);
+        self
+    }
+
+    /// Treat a plain `<<` mapping key as the YAML 1.1 merge key in any
+    /// schema, as [`Schema::Yaml11`] always does. Its value, a mapping or a
+    /// sequence of mappings (usually aliases), is merged into the mapping
+    /// holding it: the mapping's own keys win, then earlier mappings in the
+    /// sequence over later ones. A quoted `"<<"` stays an ordinary key, and
+    /// dumping quotes `<<` so that it loads back as one.
+    ///
+    /// This needs a library with the operations that take options; see
+    /// [`crate::Capabilities::options`].
+    #[must_use]
+    pub fn merge_keys(mut self, enabled: bool) -> Self {
+        self.merge_keys = enabled;
+        self
+    }
+
+    /// Set what to do when a mapping has the same key twice; see
+    /// [`DuplicateKeys`]. Defaults to [`DuplicateKeys::Last`].
+    ///
+    /// Any other policy needs a library with the tagged exports, which keep
+    /// every key; see [`crate::Capabilities::tagged`].
+    #[must_use]
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Record load warnings in `diagnostics` instead of a collector of the
+    /// instance's own. Keep a clone to inspect them; see [`Diagnostics`].
+    #[must_use]
+    pub fn diagnostics(mut self, diagnostics: Diagnostics) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// Follow `!include path` tags, which load the first document of
+    /// another file in their place. The path is relative to the including
+    /// file for [`YAMLStar::load_file`], and to `root` otherwise.
+    ///
+    /// Every included file must be under `root` once symlinks are resolved.
+    /// An include that escapes it, an include cycle, or a file that fails
+    /// to load is an error whose message ends with the chain of includes.
+    /// This needs a library with the operations that take options; see
+    /// [`crate::Capabilities::options`].
+    #[must_use]
+    pub fn include_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.include_root = Some(root.into());
+        self
+    }
+
+    /// Expand environment variables when loading, from the process
+    /// environment: `!env NAME` becomes the variable's value, typed like a
+    /// plain scalar of the [`Builder::schema`], and `${NAME}` in a string is
+    /// replaced by it. Both take a default, as in `${NAME:-fallback}`. A variable that is not set and
+    /// has no default is an [`Error::Env`].
+    ///
+    /// This needs a library with the tagged exports; see
+    /// [`crate::Capabilities::tagged`].
+    #[must_use]
+    pub fn env(self, enabled: bool) -> Self {
+        if enabled {
+            self.env_provider(StdEnv)
+        } else {
+            Self { env: None, ..self }
+        }
+    }
+
+    /// Expand environment variables as for [`Builder::env`], looking them
+    /// up in `provider`.
+    #[must_use]
+    pub fn env_provider(mut self, provider: impl EnvProvider + 'static) -> Self {
+        self.env = Some(Env(Arc::new(provider)));
+        self
+    }
+
+    /// Open the library and create the [`YAMLStar`] instance.
+    ///
+    /// # Errors
+    /// Returns an error if we fail to open the library.
+    /// Returns [`Error::NotFound`] with every path tried if neither the library
+    /// nor the `yaml` CLI can be found.
+    /// Returns [`Error::VersionMismatch`] if the library version does not
+    /// satisfy the [`VersionPolicy`].
+    /// Returns an error if the first [`BackendKind::Worker`] process cannot be
+    /// started or does not report its capabilities.
+    pub fn build(self) -> Result<YAMLStar, Error> {
+        match self.backend {
+            BackendKind::Library => self.build_library(),
+            BackendKind::Cli => self.build_cli(),
+            BackendKind::Worker => self.build_worker(),
+            BackendKind::Auto => match self.build_library() {
+                Err(Error::NotFound(tried)) => match self.build_cli() {
+                    // The CLI could not be run either.
+                    Err(Error::Io(_)) => Err(Error::NotFound(tried)),
+                    result => result,
+                },
+                result => result,
+            },
+        }
+    }
+
+    /// Create an instance with this configuration around `backend`, such as a
+    /// [`crate::MockBackend`]. The library search and backend settings are
+    /// ignored.
+    ///
+    /// # Errors
+    /// Returns [`Error::VersionMismatch`] if the backend version does not
+    /// satisfy the [`VersionPolicy`].
+    /// Returns [`Error::Unsupported`] if the backend lacks an operation the
+    /// configuration needs.
+    /// Returns [`Error::Io`] if the include root cannot be found.
+    pub fn build_with_backend(self, backend: impl Backend + 'static) -> Result<YAMLStar, Error> {
+        self.finish(backend)
+    }
+
+    /// Create the instance around `backend` and apply the configuration.
+    fn finish(&self, backend: impl Backend + 'static) -> Result<YAMLStar, Error> {
+        let mut ys = YAMLStar::with_backend(backend);
+        ys.check_version(self.version_policy)?;
+        if self.arbitrary_precision && !ys.tagged {
+            return Err(Error::Unsupported("arbitrary_precision"));
+        }
+        ys.arbitrary_precision = self.arbitrary_precision;
+        if self.tagged_enums && !ys.tagged {
+            return Err(Error::Unsupported("tagged_enums"));
+        }
+        ys.tagged_enums = self.tagged_enums;
+        ys.options.schema = self.schema;
+        if self.env.is_some() && !ys.tagged {
+            return Err(Error::Unsupported("env"));
+        }
+        ys.env.clone_from(&self.env);
+        ys.options.resolvers.clone_from(&self.resolvers);
+        if self.merge_keys && !ys.capabilities().options {
+            return Err(Error::Unsupported("merge_keys"));
+        }
+        ys.options.merge = self.merge_keys;
+        if self.duplicate_keys != DuplicateKeys::Last && !ys.tagged {
+            return Err(Error::Unsupported("duplicate_keys"));
+        }
+        ys.options.duplicate_keys = self.duplicate_keys;
+        if let Some(diagnostics) = &self.diagnostics {
+            ys.diagnostics = diagnostics.clone();
+        }
+        if let Some(root) = &self.include_root {
+            if !ys.capabilities().options {
+                return Err(Error::Unsupported("include_root"));
+            }
+            ys.options.include_root = Some(root.canonicalize()?);
+        }
+        if !self.resolvers.is_empty() && (!ys.tagged || !ys.capabilities().options) {
+            return Err(Error::Unsupported("resolver"));
+        }
+        if !ys.options.is_default() && !ys.capabilities().options {
+            return Err(Error::Unsupported("schema"));
+        }
+        Ok(ys)
+    }
+
+    /// Create an instance that uses libyamlstar.
+    fn build_library(&self) -> Result<YAMLStar, Error> {
+        #[cfg(not(feature = "static-link"))]
+        let backend = {
+            let handle = FfiBackend::open_library(&self.candidates())?;
+            FfiBackend::from_library(handle, self.thread_mode)?
+        };
+        #[cfg(feature = "static-link")]
+        let backend = FfiBackend::from_linked(self.thread_mode)?;
+
+        self.finish(backend)
+    }
+
+    /// Create an instance that uses a pool of worker processes.
+    fn build_worker(&self) -> Result<YAMLStar, Error> {
+        // Resolve the library here, so that every worker opens the same file.
+        #[cfg(not(feature = "static-link"))]
+        let library = self.candidates().into_iter().find(|path| path.is_file());
+        #[cfg(feature = "static-link")]
+        let library = None;
+
+        let workers = self.workers.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+        });
+        let backend = WorkerBackend::new(WorkerConfig {
+            program: self.worker_program.clone(),
+            workers,
+            cpu_time: self.worker_cpu_time,
+            address_space: self.worker_address_space,
+            timeout: self.worker_timeout.unwrap_or(DEFAULT_TIMEOUT),
+            library,
+        })?;
+        self.finish(backend)
+    }
+
+    /// Create an instance that uses the `yaml` CLI.
+    fn build_cli(&self) -> Result<YAMLStar, Error> {
+        let backend = match &self.cli_path {
+            Some(path) => CliBackend::new(path),
+            None => CliBackend::default(),
+        };
+        self.finish(backend)
+    }
+
+    /// List the library files to try, in order.
+    #[cfg(not(feature = "static-link"))]
+    fn candidates(&self) -> Vec<PathBuf> {
+        let mut files = self.library_paths.clone();
+        let mut dirs = Vec::new();
+
+        // An explicit file or directory from the environment.
+        if let Some(path) = std::env::var_os(LIBRARY_ENV_VAR).filter(|x| !x.is_empty()) {
+            let path = PathBuf::from(path);
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+
+        dirs.extend(self.search_dirs.iter().cloned());
+
+        // Next to the executable, for relocatable deployments.
+        if let Some(exe_dir) = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        {
+            let lib_dir = exe_dir.join("../lib");
+            dirs.push(exe_dir);
+            dirs.push(lib_dir);
+        }
+
+        // Check relative to crate for development
+        if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
+            dirs.push(Path::new(&manifest_dir).join("../libyamlstar/lib"));
+        }
+
+        // Check LD_LIBRARY_PATH (Unix) or PATH (Windows)
+        let path_var = if cfg!(windows) { "PATH" } else { "LD_LIBRARY_PATH" };
+        if let Some(library_path) = std::env::var_os(path_var) {
+            dirs.extend(std::env::split_paths(&library_path));
+        }
+
+        // Standard locations
+        dirs.push(PathBuf::from("/usr/local/lib"));
+        if let Some(home) = std::env::var_os("HOME") {
+            dirs.push(Path::new(&home).join(".local/lib"));
+        }
+
+        let versioned =
+            format!("{LIBYAMLSTAR_BASENAME}.{LIBYAMLSTAR_EXTENSION}.{LIBYAMLSTAR_VERSION}");
+        files.extend(dirs.iter().map(|dir| dir.join(&versioned)));
+        if self.unversioned_fallback {
+            let unversioned = format!("{LIBYAMLSTAR_BASENAME}.{LIBYAMLSTAR_EXTENSION}");
+            files.extend(dirs.iter().map(|dir| dir.join(&unversioned)));
+        }
+        files
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! The libyamlstar embedded by the `bundled` feature.
+//!
+//! `dlopen` needs a file, so the embedded bytes are written once to
+//! `$XDG_CACHE_HOME/yamlstar/<sha256>/` (or `~/.cache/yamlstar/<sha256>/`),
+//! where `build.rs` computes the checksum. The directory is only accessible
+//! by the current user, and the file is compared with the embedded bytes the
+//! first time a process uses it.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+use crate::{Error, LIBYAMLSTAR_BASENAME, LIBYAMLSTAR_EXTENSION, LIBYAMLSTAR_VERSION};
+
+/// The library bytes, embedded at build time from `YAMLSTAR_BUNDLE`.
+static LIBRARY: &[u8] = include_bytes!(env!("YAMLSTAR_BUNDLE_PATH"));
+
+/// The hex-encoded SHA-256 of [`LIBRARY`].
+const DIGEST: &str = env!("YAMLSTAR_BUNDLE_SHA256");
+
+/// Write the bundled library to the cache, if needed, and return its path.
+///
+/// The path is remembered for the rest of the process. If extracting fails,
+/// the error is returned and the next call tries again.
+pub(crate) fn extract() -> Result<PathBuf, Error> {
+    static EXTRACTED: OnceLock<PathBuf> = OnceLock::new();
+    static INIT: Mutex<()> = Mutex::new(());
+
+    if let Some(path) = EXTRACTED.get() {
+        return Ok(path.clone());
+    }
+    let _guard = INIT.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(path) = EXTRACTED.get() {
+        return Ok(path.clone());
+    }
+    let path = write_library()?;
+    Ok(EXTRACTED.get_or_init(|| path).clone())
+}
+
+/// Write the bundled library to the cache unless an identical file is
+/// already there, and return its path.
+fn write_library() -> Result<PathBuf, Error> {
+    let root = cache_root()?.join("yamlstar");
+    create_private_dir(&root)?;
+    let dir = root.join(DIGEST);
+    create_private_dir(&dir)?;
+
+    let file_name = format!("{LIBYAMLSTAR_BASENAME}.{LIBYAMLSTAR_EXTENSION}.{LIBYAMLSTAR_VERSION}");
+    let path = dir.join(&file_name);
+    if path.is_file() && fs::read(&path)? == LIBRARY {
+        return Ok(path);
+    }
+
+    // Write to a temporary file and rename it into place, so that concurrent
+    // processes never open a partially written library.
+    let tmp = dir.join(format!(".{file_name}.{}.tmp", std::process::id()));
+    let _ = fs::remove_file(&tmp);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&tmp)?;
+    file.write_all(LIBRARY)?;
+    file.sync_all()?;
+    drop(file);
+    #[cfg(unix)]
+    fs::set_permissions(&tmp, std::os::unix::fs::PermissionsExt::from_mode(0o500))?;
+    fs::rename(&tmp, &path)?;
+
+    if fs::read(&path)? != LIBRARY {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("extracted {} does not match the bundled library", path.display()),
+        )
+        .into());
+    }
+    Ok(path)
+}
+
+/// Find the user's cache directory.
+fn cache_root() -> Result<PathBuf, Error> {
+    if let Some(dir) = std::env::var_os("XDG_CACHE_HOME").filter(|x| !x.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(home) = std::env::var_os("HOME").filter(|x| !x.is_empty()) {
+        return Ok(Path::new(&home).join(".cache"));
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "neither XDG_CACHE_HOME nor HOME is set; cannot extract the bundled libyamlstar",
+    )
+    .into())
+}
+
+/// Create `dir` readable only by the current user, or check that an
+/// existing `dir` is.
+fn create_private_dir(dir: &Path) -> Result<(), Error> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let meta = fs::symlink_metadata(dir)?;
+        let uid = unsafe { libc::getuid() };
+        if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "{} must be a directory owned by the current user with mode 0700",
+                    dir.display()
+                ),
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
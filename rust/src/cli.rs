@@ -0,0 +1,170 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! A [`Backend`] that runs the `yaml` command-line tool.
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::{Backend, Error, Value};
+
+/// The environment variable that names the `yaml` executable.
+pub const CLI_ENV_VAR: &str = "YAMLSTAR_CLI";
+
+/// A backend that runs the `yaml` executable once per call.
+///
+/// The input is piped through stdin. Loading reads the CLI's JSON output
+/// (`-s` for every document) and dumping its YAML output (`-Y`). When the CLI
+/// reports an error, it is returned as an [`Error::YAMLStar`]; any other
+/// failure becomes [`Error::Cli`].
+///
+/// This is much slower than the shared library, but needs no native code in
+/// the process.
+#[derive(Clone, Debug)]
+pub struct CliBackend {
+    /// The `yaml` executable.
+    program: PathBuf,
+}
+
+impl Default for CliBackend {
+    /// Use the executable named by `YAMLSTAR_CLI`, the development build in
+    /// `cli/bin` relative to the crate, or `yaml` on the `PATH`.
+    fn default() -> Self {
+        let exe = format!("yaml{}", std::env::consts::EXE_SUFFIX);
+        let program = std::env::var_os(CLI_ENV_VAR)
+            .filter(|x| !x.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR")?;
+                Some(Path::new(&manifest_dir).join("../cli/bin").join(&exe))
+                    .filter(|path| path.is_file())
+            })
+            .unwrap_or_else(|| PathBuf::from(exe));
+        Self { program }
+    }
+}
+
+impl CliBackend {
+    /// Create a backend that runs `program`.
+    #[must_use]
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+
+    /// The `yaml` executable this backend runs.
+    #[must_use]
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// Run the CLI with `args`, feeding it `input`, and return its stdout.
+    ///
+    /// A run that exits with status 1 and an `Error:` message is a YAML error
+    /// and returned as `Ok(Err(message))`.
+    fn run(&self, args: &[&str], input: &str) -> Result<Result<String, String>, Error> {
+        let mut child = Command::new(&self.program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Write from another thread so a full stdout pipe cannot deadlock us.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let output = std::thread::scope(|scope| {
+            let writer = scope.spawn(move || stdin.write_all(input.as_bytes()));
+            let output = child.wait_with_output();
+            match writer.join() {
+                // The CLI may exit early without reading all of its input.
+                Ok(Err(e)) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e),
+                _ => output,
+            }
+        })?;
+
+        let stdout = String::from_utf8(output.stdout).map_err(|e| e.utf8_error())?;
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if output.status.success() {
+            return Ok(Ok(stdout));
+        }
+        match stderr.strip_prefix("Error:") {
+            Some(message) if output.status.code() == Some(1) => {
+                Ok(Err(message.trim().to_string()))
+            }
+            _ => Err(Error::Cli {
+                status: output.status.code(),
+                stderr,
+            }),
+        }
+    }
+
+    /// Run a load command and wrap its JSON output in a response envelope.
+    fn load_with(&self, args: &[&str], yaml: &str) -> Result<String, Error> {
+        Ok(match self.run(args, yaml)? {
+            Ok(json) => format!("{{\"data\":{}}}", json.trim()),
+            Err(cause) => error_envelope(&cause),
+        })
+    }
+
+    /// Run a dump command on YAML `input` and wrap its output in a response
+    /// envelope.
+    fn dump_with(&self, args: &[&str], input: &str) -> Result<String, Error> {
+        Ok(match self.run(args, input)? {
+            // The CLI prints the dumped YAML followed by a newline.
+            Ok(yaml) => {
+                let yaml = yaml.strip_suffix('\n').unwrap_or(&yaml);
+                serde_json::json!({ "data": yaml }).to_string()
+            }
+            Err(cause) => error_envelope(&cause),
+        })
+    }
+}
+
+impl Backend for CliBackend {
+    fn load(&self, yaml: &str) -> Result<String, Error> {
+        self.load_with(&[], yaml)
+    }
+
+    fn load_all(&self, yaml: &str) -> Result<String, Error> {
+        self.load_with(&["-s"], yaml)
+    }
+
+    fn dump(&self, data_json: &str) -> Result<String, Error> {
+        // JSON is YAML, so the CLI can read the value as it is.
+        self.dump_with(&["-Y"], data_json)
+    }
+
+    fn dump_all(&self, data_json: &str) -> Result<String, Error> {
+        // Turn the JSON array into a YAML stream with one document per value.
+        // `Value` keeps the keys of each object in order.
+        let values = serde_json::from_str::<Vec<Value>>(data_json)?;
+        let mut stream = String::new();
+        for value in &values {
+            stream.push_str("--- ");
+            stream.push_str(&serde_json::to_string(value)?);
+            stream.push('\n');
+        }
+        self.dump_with(&["-Y", "-s"], &stream)
+    }
+
+    fn version(&self) -> Result<String, Error> {
+        let output = self.run(&["--version"], "")?.map_err(|stderr| Error::Cli {
+            status: Some(1),
+            stderr,
+        })?;
+        let output = output.trim();
+        Ok(output
+            .strip_prefix("yamlstar version ")
+            .unwrap_or(output)
+            .to_string())
+    }
+}
+
+/// Build an error response envelope as libyamlstar would.
+fn error_envelope(cause: &str) -> String {
+    serde_json::json!({ "error": { "cause": cause, "type": "Exception" } }).to_string()
+}
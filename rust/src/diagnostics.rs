@@ -0,0 +1,128 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Duplicate mapping keys, and the warnings a load can leave behind.
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// What to do when a mapping has the same key twice; see
+/// [`crate::Builder::duplicate_keys`].
+///
+/// ```no_run
+/// use yamlstar::{DuplicateKeys, Error, Value, YAMLStar};
+///
+/// let ys = YAMLStar::builder().duplicate_keys(DuplicateKeys::Error).build().unwrap();
+/// let err = ys.load::<Value>("timeout: 5\nretries: 3\ntimeout: 50\n").unwrap_err();
+/// assert!(matches!(err, Error::DuplicateKey(_)));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DuplicateKeys {
+    /// Fail with [`crate::Error::DuplicateKey`].
+    Error,
+    /// Keep the first value.
+    First,
+    /// Keep the last value, in the place of the first key.
+    #[default]
+    Last,
+    /// Keep the last value, as for [`DuplicateKeys::Last`], and record a
+    /// [`Diagnostic::DuplicateKey`] in the instance's [`Diagnostics`].
+    Warn,
+}
+
+/// A key found twice in one mapping.
+///
+/// libyamlstar does not report line numbers, so both places are given as
+/// the index of their entry in the mapping, counting from `0` in document
+/// order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateKey {
+    /// The key, as text.
+    pub key: String,
+    /// Where the mapping is in its document, as a JSON Pointer.
+    pub path: String,
+    /// The index of the document in the stream.
+    pub document: usize,
+    /// The entry where the key first appears.
+    pub first: usize,
+    /// The entry where it appears again.
+    pub second: usize,
+}
+
+impl fmt::Display for DuplicateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() { "/" } else { &self.path };
+        write!(
+            f,
+            "Duplicate key {} in the mapping at {path} in document {} (entries {} and {})",
+            self.key, self.document, self.first, self.second
+        )
+    }
+}
+
+/// A warning from a load.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Diagnostic {
+    /// A duplicate key, with [`DuplicateKeys::Warn`].
+    DuplicateKey(DuplicateKey),
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::DuplicateKey(duplicate) => duplicate.fmt(f),
+        }
+    }
+}
+
+/// Collects the [`Diagnostic`]s of every load made with an instance.
+///
+/// Clones share their diagnostics, so a collector given to
+/// [`crate::Builder::diagnostics`] can be inspected while the instance is in
+/// use elsewhere. [`crate::YAMLStar::diagnostics`] returns an instance's
+/// collector.
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+    /// The diagnostics not yet taken, oldest first.
+    entries: Arc<Mutex<Vec<Diagnostic>>>,
+}
+
+impl Diagnostics {
+    /// Create an empty collector.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove and return the diagnostics collected so far, oldest first.
+    #[must_use]
+    pub fn take(&self) -> Vec<Diagnostic> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// The number of diagnostics collected and not yet taken.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether there are no diagnostics to take.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Record `diagnostics`.
+    pub(crate) fn extend(&self, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+        self.lock().extend(diagnostics);
+    }
+
+    /// Lock the diagnostics. A panic while they were locked cannot leave
+    /// them inconsistent, so poisoning is ignored.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Diagnostic>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Environment variables in loaded documents, turned on with
+//! [`crate::Builder::env`] or [`crate::Builder::env_provider`].
+//!
+//! `!env NAME` is replaced by the variable's value, typed as a plain scalar
+//! of the instance's [`crate::Schema`] would be, so that `port: !env PORT`
+//! loads as an integer. `${NAME}` in any string is replaced by the variable's value as
+//! text, and `$${` is a literal `${`. Both accept a default, as in
+//! `${NAME:-fallback}`, used when the variable is unset or empty.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::BuildHasher,
+    sync::Arc,
+};
+
+use crate::{
+    value::{key_token, push_pointer_token},
+    Error, Schema, TaggedValue, Value,
+};
+
+/// The tag of a node replaced by an environment variable.
+const ENV_TAG: &str = "!env";
+
+/// A source of environment variables.
+///
+/// [`StdEnv`] reads the process environment. A map of names to values is a
+/// provider too, which suits tests.
+///
+/// ```no_run
+/// use std::collections::HashMap;
+///
+/// let env = HashMap::from([("PORT".to_string(), "8080".to_string())]);
+/// let ys = yamlstar::YAMLStar::builder().env_provider(env).build().unwrap();
+/// let port: u16 = ys.load("!env PORT").unwrap();
+/// assert_eq!(port, 8080);
+/// ```
+pub trait EnvProvider: Send + Sync {
+    /// The value of the variable `name`, or `None` if it is not set.
+    fn var(&self, name: &str) -> Option<String>;
+}
+
+/// The process environment, read with [`std::env::var`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdEnv;
+
+impl EnvProvider for StdEnv {
+    fn var(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+}
+
+impl<S: BuildHasher + Send + Sync> EnvProvider for HashMap<String, String, S> {
+    fn var(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+impl EnvProvider for BTreeMap<String, String> {
+    fn var(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+/// The environment provider of a [`crate::YAMLStar`] instance.
+#[derive(Clone)]
+pub(crate) struct Env(pub(crate) Arc<dyn EnvProvider>);
+
+impl fmt::Debug for Env {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Env(..)")
+    }
+}
+
+impl Env {
+    /// Replace `!env` nodes and `${NAME}` references in the document
+    /// numbered `document`, typing `!env` values with `schema`.
+    pub(crate) fn expand(
+        &self,
+        value: Value,
+        document: usize,
+        schema: Schema,
+    ) -> Result<Value, Error> {
+        self.expand_at(value, &mut String::new(), document, schema)
+    }
+
+    /// Expand the node at `path` and its children.
+    fn expand_at(
+        &self,
+        value: Value,
+        path: &mut String,
+        document: usize,
+        schema: Schema,
+    ) -> Result<Value, Error> {
+        Ok(match value {
+            Value::String(text) => Value::String(self.interpolate(&text, path, document)?),
+            Value::Sequence(seq) => Value::Sequence(
+                seq.into_iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        self.expand_child(item, path, &i.to_string(), document, schema)
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Mapping(map) => Value::Mapping(
+                map.into_iter()
+                    .map(|(k, v)| {
+                        let token = key_token(&k);
+                        let key = self.expand_child(k, path, &token, document, schema)?;
+                        let value = self.expand_child(v, path, &token, document, schema)?;
+                        Ok((key, value))
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+            Value::Tagged(tagged) if tagged.tag == ENV_TAG => match tagged.value {
+                Value::String(reference) => {
+                    let text = self.lookup(&reference, path, document)?;
+                    schema.plain_scalar(&text)
+                }
+                _ => {
+                    return Err(Error::Value(format!(
+                        "!env at {} in document {document} needs a variable name",
+                        display_path(path)
+                    )))
+                }
+            },
+            Value::Tagged(tagged) => {
+                let TaggedValue { tag, value } = *tagged;
+                TaggedValue::new(tag, self.expand_at(value, path, document, schema)?).into()
+            }
+            other => other,
+        })
+    }
+
+    /// Expand the child of the node at `path` found by the reference `token`.
+    fn expand_child(
+        &self,
+        value: Value,
+        path: &mut String,
+        token: &str,
+        document: usize,
+        schema: Schema,
+    ) -> Result<Value, Error> {
+        let len = path.len();
+        push_pointer_token(path, token);
+        let result = self.expand_at(value, path, document, schema);
+        path.truncate(len);
+        result
+    }
+
+    /// Replace each `${NAME}` or `${NAME:-default}` in `text`.
+    fn interpolate(&self, text: &str, path: &str, document: usize) -> Result<String, Error> {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            out.push_str(&rest[..start]);
+            let after = &rest[start..];
+            if let Some(escaped) = after.strip_prefix("$${") {
+                out.push_str("${");
+                rest = escaped;
+            } else if let Some((reference, tail)) = after
+                .strip_prefix("${")
+                .and_then(|inner| inner.split_once('}'))
+            {
+                out.push_str(&self.lookup(reference, path, document)?);
+                rest = tail;
+            } else {
+                out.push('$');
+                rest = &after[1..];
+            }
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// The value of the reference `NAME` or `NAME:-default`.
+    fn lookup(&self, reference: &str, path: &str, document: usize) -> Result<String, Error> {
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        match (self.0.var(name), default) {
+            (Some(value), Some(default)) if value.is_empty() => Ok(default.to_string()),
+            (Some(value), _) => Ok(value),
+            (None, Some(default)) => Ok(default.to_string()),
+            (None, None) => Err(Error::Env {
+                name: name.to_string(),
+                path: path.to_string(),
+                document,
+            }),
+        }
+    }
+}
+
+/// A JSON Pointer for messages, with `/` for the whole document.
+fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else {
+        path
+    }
+}
@@ -4,6 +4,7 @@
 use std::{
     error::Error as StdError,
     fmt::{Debug, Display},
+    path::PathBuf,
     str::Utf8Error,
 };
 
@@ -11,38 +12,135 @@ use serde::Deserialize;
 
 /// An error with the binding.
 pub enum Error {
-    /// The library was not found.
-    NotFound,
+    /// The library was not found. Holds every path that was tried.
+    NotFound(Vec<PathBuf>),
     /// An error while loading the library.
     Load(dlopen::Error),
-    /// An error with GraalVM.
+    /// An I/O error, such as while extracting the bundled library.
+    Io(std::io::Error),
+    /// The library version does not satisfy the [`crate::VersionPolicy`].
+    VersionMismatch {
+        /// The version this binding works with.
+        expected: String,
+        /// The version the library reported.
+        found: String,
+    },
+    /// The loaded library does not support this operation. Holds the name of
+    /// the missing capability, such as `"dump"`.
+    Unsupported(&'static str),
+    /// An error with `GraalVM`.
     GraalVM(i32),
+    /// An instance in [`crate::ThreadMode::Single`] mode was used off the OS
+    /// thread that created it.
+    WrongThread,
     /// An error in the FFI while calling a libyamlstar function.
     Ffi(String),
+    /// The `yaml` CLI failed without reporting a YAML error.
+    Cli {
+        /// The exit status, or `None` if it was killed by a signal.
+        status: Option<i32>,
+        /// What it wrote to stderr.
+        stderr: String,
+    },
+    /// A worker process exited while handling a request, for example because
+    /// of a runtime panic or a resource limit. Holds how it exited.
+    WorkerCrashed(std::process::ExitStatus),
+    /// A worker process did not answer within the time set by
+    /// [`crate::Builder::worker_timeout`], and was stopped. Holds that time.
+    WorkerTimedOut(std::time::Duration),
     /// An error from the libyamlstar library.
     YAMLStar(LibYSError),
-    /// An error with serde_json while deserializing.
+    /// An error with `serde_json` while deserializing.
     Serde(serde_json::Error),
     /// An error while decoding strings returned from libyamlstar.
     Utf8(Utf8Error),
+    /// An error while converting between a [`crate::Value`] and another type.
+    Value(String),
+    /// A constructor registered with [`crate::YAMLStar::register_tag`]
+    /// failed.
+    Tag {
+        /// The tag of the node, such as `"!color"`, or in full for `!!`
+        /// tags.
+        tag: String,
+        /// Where the node is in its document, as a JSON Pointer such as
+        /// `/servers/0/color` that [`crate::Value::pointer`] accepts.
+        /// libyamlstar does not report line numbers.
+        path: String,
+        /// The index of the document in the stream, `0` for [`crate::YAMLStar::load`].
+        document: usize,
+        /// The error the constructor returned.
+        source: Box<dyn StdError + Send + Sync>,
+    },
+    /// A mapping has the same key twice, with [`crate::DuplicateKeys::Error`].
+    DuplicateKey(crate::DuplicateKey),
+    /// An environment variable named by `!env` or `${NAME}` is not set, and
+    /// has no default.
+    Env {
+        /// The name of the variable.
+        name: String,
+        /// Where the reference is in its document, as a JSON Pointer.
+        path: String,
+        /// The index of the document in the stream.
+        document: usize,
+    },
 }
 
 impl Debug for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::NotFound => write!(
+            Self::NotFound(tried) => {
+                write!(
+                    f,
+                    "Shared library file 'libyamlstar.so.{0}' not found\n\
+                    Try: cd libyamlstar && make native\n\
+                    Or set {1} to the library location",
+                    &super::LIBYAMLSTAR_VERSION,
+                    super::LIBRARY_ENV_VAR,
+                )?;
+                write!(f, "\nSearched:")?;
+                for path in tried {
+                    write!(f, "\n  {}", path.display())?;
+                }
+                Ok(())
+            }
+            Error::Load(e) => write!(f, "Error::Load({e:?})"),
+            Error::Io(e) => write!(f, "Error::Io({e:?})"),
+            Error::VersionMismatch { expected, found } => write!(
                 f,
-                "Shared library file 'libyamlstar.so.{0}' not found\n\
-                Try: cd libyamlstar && make native\n\
-                Or set LD_LIBRARY_PATH to include the library location",
-                &super::LIBYAMLSTAR_VERSION
+                "Error::VersionMismatch {{ expected: {expected:?}, found: {found:?} }}"
             ),
-            Error::Load(e) => write!(f, "Error::Load({e:?})"),
+            Error::Unsupported(e) => write!(f, "Error::Unsupported({e:?})"),
             Error::GraalVM(e) => write!(f, "Error::GraalVM({e:?})"),
+            Error::WrongThread => write!(f, "Error::WrongThread"),
             Error::Ffi(e) => write!(f, "Error::Ffi({e:?})"),
+            Error::Cli { status, stderr } => write!(
+                f,
+                "Error::Cli {{ status: {status:?}, stderr: {stderr:?} }}"
+            ),
+            Error::WorkerCrashed(e) => write!(f, "Error::WorkerCrashed({e:?})"),
+            Error::WorkerTimedOut(e) => write!(f, "Error::WorkerTimedOut({e:?})"),
             Error::YAMLStar(e) => write!(f, "Error::YAMLStar({e:?})"),
             Error::Serde(e) => write!(f, "Error::Serde({e:?})"),
             Error::Utf8(e) => write!(f, "Error::Utf8({e:?})"),
+            Error::Value(e) => write!(f, "Error::Value({e:?})"),
+            Error::Tag {
+                tag,
+                path,
+                document,
+                source,
+            } => write!(
+                f,
+                "Error::Tag {{ tag: {tag:?}, path: {path:?}, document: {document}, source: {source:?} }}"
+            ),
+            Error::DuplicateKey(e) => write!(f, "Error::DuplicateKey({e:?})"),
+            Error::Env {
+                name,
+                path,
+                document,
+            } => write!(
+                f,
+                "Error::Env {{ name: {name:?}, path: {path:?}, document: {document} }}"
+            ),
         }
     }
 }
@@ -50,17 +148,76 @@ impl Debug for Error {
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::NotFound => write!(
+            Self::NotFound(tried) => {
+                write!(
+                    f,
+                    "Shared library file 'libyamlstar.so.{0}' not found",
+                    &super::LIBYAMLSTAR_VERSION
+                )?;
+                if !tried.is_empty() {
+                    let tried = tried
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect::<Vec<_>>();
+                    write!(f, " (tried: {})", tried.join(", "))?;
+                }
+                Ok(())
+            }
+            Error::Load(e) => write!(f, "Failed to load library: {e}"),
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+            Error::VersionMismatch { expected, found } => write!(
                 f,
-                "Shared library file 'libyamlstar.so.{0}' not found",
-                &super::LIBYAMLSTAR_VERSION
+                "Incompatible libyamlstar version {found} (expected {expected})"
             ),
-            Error::Load(e) => write!(f, "Failed to load library: {e}"),
+            Error::Unsupported(what) => {
+                write!(f, "The loaded libyamlstar does not support '{what}'")
+            }
             Error::GraalVM(code) => write!(f, "GraalVM error (code {code})"),
+            Error::WrongThread => write!(
+                f,
+                "This YAMLStar instance can only be used on the thread that created it"
+            ),
             Error::Ffi(msg) => write!(f, "FFI error: {msg}"),
+            Error::Cli { status, stderr } => {
+                match status {
+                    Some(code) => write!(f, "The yaml CLI exited with status {code}")?,
+                    None => write!(f, "The yaml CLI was killed by a signal")?,
+                }
+                if !stderr.is_empty() {
+                    write!(f, ": {stderr}")?;
+                }
+                Ok(())
+            }
+            Error::WorkerCrashed(status) => write!(f, "The yamlstar worker crashed ({status})"),
+            Error::WorkerTimedOut(limit) => {
+                write!(f, "The yamlstar worker did not answer within {limit:?} and was stopped")
+            }
             Error::YAMLStar(e) => write!(f, "YAML parsing error: {}", e.cause),
             Error::Serde(e) => write!(f, "JSON deserialization error: {e}"),
             Error::Utf8(e) => write!(f, "UTF-8 decoding error: {e}"),
+            Error::Value(msg) => write!(f, "Value conversion error: {msg}"),
+            Error::Tag {
+                tag,
+                path,
+                document,
+                source,
+            } => {
+                let tag = crate::value::display::tag_text(tag);
+                let path = if path.is_empty() { "/" } else { path };
+                write!(f, "Failed to construct {tag} at {path} in document {document}: {source}")
+            }
+            Error::DuplicateKey(e) => write!(f, "{e}"),
+            Error::Env {
+                name,
+                path,
+                document,
+            } => {
+                let path = if path.is_empty() { "/" } else { path };
+                write!(
+                    f,
+                    "Environment variable {name} is not set (at {path} in document {document})"
+                )
+            }
         }
     }
 }
@@ -69,13 +226,27 @@ impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
             Error::Load(e) => Some(e),
+            Error::Io(e) => Some(e),
             Error::Serde(e) => Some(e),
             Error::Utf8(e) => Some(e),
+            Error::Tag { source, .. } => Some(source.as_ref()),
             _ => None,
         }
     }
 }
 
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Value(msg.to_string())
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Value(msg.to_string())
+    }
+}
+
 /// An error from libyamlstar.
 #[allow(clippy::module_name_repetitions)]
 #[derive(Deserialize, Debug)]
@@ -95,6 +266,12 @@ impl From<dlopen::Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(value: serde_json::Error) -> Self {
         Self::Serde(value)
@@ -0,0 +1,417 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! The default [`Backend`], which calls into libyamlstar.
+
+use std::{ffi::CStr, ptr::NonNull, str::Utf8Error, sync::Arc};
+#[cfg(not(feature = "static-link"))]
+use std::{mem::ManuallyDrop, path::PathBuf};
+
+#[cfg(not(feature = "static-link"))]
+use dlopen::symbor::Library;
+use libc::{c_int, c_void as void};
+
+#[cfg(feature = "bundled")]
+use crate::bundled;
+#[cfg(feature = "static-link")]
+use crate::linked;
+use crate::{isolate::Isolate, Backend, Capabilities, Error, ThreadMode};
+
+/// Prototype of the `graal_create_isolate` function.
+pub(crate) type CreateIsolateFn =
+    unsafe extern "C" fn(*mut void, *const *mut void, *const *mut void) -> c_int;
+/// Prototype of the `graal_tear_down_isolate` function.
+pub(crate) type TearDownIsolateFn = unsafe extern "C" fn(*mut void) -> c_int;
+/// Prototype of the `graal_attach_thread` function.
+pub(crate) type AttachThreadFn = unsafe extern "C" fn(*mut void, *mut *mut void) -> c_int;
+/// Prototype of the `graal_detach_thread` function.
+pub(crate) type DetachThreadFn = unsafe extern "C" fn(*mut void) -> c_int;
+/// Prototype of the `yamlstar_load` function.
+type YamlstarLoadFn = unsafe extern "C" fn(*mut void, *const u8) -> *mut i8;
+/// Prototype of the `yamlstar_load_all` function.
+type YamlstarLoadAllFn = unsafe extern "C" fn(*mut void, *const u8) -> *mut i8;
+/// Prototype of the `yamlstar_dump` function.
+type YamlstarDumpFn = unsafe extern "C" fn(*mut void, *const u8) -> *mut i8;
+/// Prototype of the `yamlstar_dump_all` function.
+type YamlstarDumpAllFn = unsafe extern "C" fn(*mut void, *const u8) -> *mut i8;
+/// Prototype of the `yamlstar_*_tagged` functions.
+type YamlstarTaggedFn = unsafe extern "C" fn(*mut void, *const u8) -> *mut i8;
+/// Prototype of the `yamlstar_*_with_options` functions.
+type YamlstarOptionsFn = unsafe extern "C" fn(*mut void, *const u8, *const u8) -> *mut i8;
+/// Prototype of the `yamlstar_version` function.
+type YamlstarVersionFn = unsafe extern "C" fn(*mut void) -> *mut i8;
+/// Prototype of the `yamlstar_free` function.
+type YamlstarFreeFn = unsafe extern "C" fn(*mut void, *mut i8);
+
+/// A backend that calls the functions exported by libyamlstar.
+pub(crate) struct FfiBackend {
+    /// A handle to the opened dynamic library.
+    // Go c-shared libraries cannot be safely unloaded on macOS. Keep the
+    // process-wide library loaded until process exit.
+    #[cfg(not(feature = "static-link"))]
+    _handle: ManuallyDrop<Library>,
+    /// A `GraalVM` isolate, shared by all threads using this instance.
+    isolate: Arc<Isolate>,
+    /// Pointer to the `yamlstar_load` function, if the library has it.
+    load_fn: Option<YamlstarLoadFn>,
+    /// Pointer to the `yamlstar_load_all` function, if the library has it.
+    load_all_fn: Option<YamlstarLoadAllFn>,
+    /// Pointer to the `yamlstar_dump` function, if the library has it.
+    dump_fn: Option<YamlstarDumpFn>,
+    /// Pointer to the `yamlstar_dump_all` function, if the library has it.
+    dump_all_fn: Option<YamlstarDumpAllFn>,
+    /// Pointers to the `yamlstar_load_tagged`, `yamlstar_load_all_tagged`,
+    /// `yamlstar_dump_tagged` and `yamlstar_dump_all_tagged` functions, if
+    /// the library has them all.
+    tagged_fns: Option<TaggedFns>,
+    /// Pointers to the `yamlstar_*_with_options` functions, if the library
+    /// has them all.
+    options_fns: Option<OptionsFns>,
+    /// Pointer to the `yamlstar_version` function, if the library has it.
+    version_fn: Option<YamlstarVersionFn>,
+    /// Pointer to the `yamlstar_free` function, if the library has it.
+    /// Without it, returned buffers are leaked.
+    free_fn: Option<YamlstarFreeFn>,
+}
+
+/// The exports that speak the tagged encoding; see [`crate::Value`].
+#[derive(Clone, Copy)]
+struct TaggedFns {
+    /// `yamlstar_load_tagged`.
+    load: YamlstarTaggedFn,
+    /// `yamlstar_load_all_tagged`.
+    load_all: YamlstarTaggedFn,
+    /// `yamlstar_dump_tagged`.
+    dump: YamlstarTaggedFn,
+    /// `yamlstar_dump_all_tagged`.
+    dump_all: YamlstarTaggedFn,
+}
+
+/// The exports that take a JSON object of options; see [`crate::Schema`].
+#[derive(Clone, Copy)]
+struct OptionsFns {
+    /// `yamlstar_load_with_options`.
+    load: YamlstarOptionsFn,
+    /// `yamlstar_load_all_with_options`.
+    load_all: YamlstarOptionsFn,
+    /// `yamlstar_dump_with_options`.
+    dump: YamlstarOptionsFn,
+    /// `yamlstar_dump_all_with_options`.
+    dump_all: YamlstarOptionsFn,
+}
+
+// SAFETY: The raw pointers held by `FfiBackend` are opaque handles that are
+// only ever passed back to libyamlstar; the binding never dereferences them.
+// Every call goes through `Isolate::current_thread`, which only hands out an
+// isolate thread of the calling OS thread: each OS thread attaches its own in
+// `ThreadMode::PerThread` mode, the default whenever the library can attach,
+// and calls off the creating thread fail in `ThreadMode::Single` mode. Every
+// buffer the library returns is freshly allocated and owned by exactly one
+// `YsString`, which frees it with the isolate thread it was returned on.
+unsafe impl Send for FfiBackend {}
+unsafe impl Sync for FfiBackend {}
+
+impl FfiBackend {
+    /// Create a backend from the library linked at build time.
+    #[cfg(feature = "static-link")]
+    pub(crate) fn from_linked(mode: ThreadMode) -> Result<Self, Error> {
+        let isolate = Isolate::create(
+            mode,
+            linked::graal_create_isolate,
+            Some((linked::graal_attach_thread, linked::graal_detach_thread)),
+            linked::graal_tear_down_isolate,
+        )?;
+
+        Ok(Self {
+            isolate,
+            load_fn: Some(linked::yamlstar_load),
+            load_all_fn: Some(linked::yamlstar_load_all),
+            dump_fn: Some(linked::yamlstar_dump),
+            dump_all_fn: Some(linked::yamlstar_dump_all),
+            tagged_fns: Some(TaggedFns {
+                load: linked::yamlstar_load_tagged,
+                load_all: linked::yamlstar_load_all_tagged,
+                dump: linked::yamlstar_dump_tagged,
+                dump_all: linked::yamlstar_dump_all_tagged,
+            }),
+            options_fns: Some(OptionsFns {
+                load: linked::yamlstar_load_with_options,
+                load_all: linked::yamlstar_load_all_with_options,
+                dump: linked::yamlstar_dump_with_options,
+                dump_all: linked::yamlstar_dump_all_with_options,
+            }),
+            version_fn: Some(linked::yamlstar_version),
+            free_fn: Some(linked::yamlstar_free),
+        })
+    }
+
+    /// Create a backend from an opened library.
+    ///
+    /// Only the isolate lifecycle functions are required. Any other missing
+    /// symbol disables the methods that need it; see
+    /// [`crate::YAMLStar::capabilities`].
+    #[cfg(not(feature = "static-link"))]
+    pub(crate) fn from_library(handle: Library, mode: ThreadMode) -> Result<Self, Error> {
+        // Fetch symbols.
+        let create_isolate_fn =
+            unsafe { Self::symbol::<CreateIsolateFn>(&handle, "graal_create_isolate") };
+        let tear_down_isolate_fn =
+            unsafe { Self::symbol::<TearDownIsolateFn>(&handle, "graal_tear_down_isolate") };
+        let (Some(create_isolate_fn), Some(tear_down_isolate_fn)) =
+            (create_isolate_fn, tear_down_isolate_fn)
+        else {
+            return Err(Error::Load(dlopen::Error::NullSymbol));
+        };
+
+        // Per-thread isolate threads need both halves of the pair.
+        let attach_thread_fn =
+            unsafe { Self::symbol::<AttachThreadFn>(&handle, "graal_attach_thread") };
+        let detach_thread_fn =
+            unsafe { Self::symbol::<DetachThreadFn>(&handle, "graal_detach_thread") };
+        let thread_fns = attach_thread_fn.zip(detach_thread_fn);
+
+        let load_fn = unsafe { Self::symbol::<YamlstarLoadFn>(&handle, "yamlstar_load") };
+        let load_all_fn =
+            unsafe { Self::symbol::<YamlstarLoadAllFn>(&handle, "yamlstar_load_all") };
+        let dump_fn = unsafe { Self::symbol::<YamlstarDumpFn>(&handle, "yamlstar_dump") };
+        let dump_all_fn =
+            unsafe { Self::symbol::<YamlstarDumpAllFn>(&handle, "yamlstar_dump_all") };
+        let tagged_fn = |name| unsafe { Self::symbol::<YamlstarTaggedFn>(&handle, name) };
+        let tagged_fns = (|| {
+            Some(TaggedFns {
+                load: tagged_fn("yamlstar_load_tagged")?,
+                load_all: tagged_fn("yamlstar_load_all_tagged")?,
+                dump: tagged_fn("yamlstar_dump_tagged")?,
+                dump_all: tagged_fn("yamlstar_dump_all_tagged")?,
+            })
+        })();
+        let options_fn = |name| unsafe { Self::symbol::<YamlstarOptionsFn>(&handle, name) };
+        let options_fns = (|| {
+            Some(OptionsFns {
+                load: options_fn("yamlstar_load_with_options")?,
+                load_all: options_fn("yamlstar_load_all_with_options")?,
+                dump: options_fn("yamlstar_dump_with_options")?,
+                dump_all: options_fn("yamlstar_dump_all_with_options")?,
+            })
+        })();
+        let version_fn = unsafe { Self::symbol::<YamlstarVersionFn>(&handle, "yamlstar_version") };
+        let free_fn = unsafe { Self::symbol::<YamlstarFreeFn>(&handle, "yamlstar_free") };
+
+        // Create `GraalVM` isolate.
+        let isolate = Isolate::create(mode, create_isolate_fn, thread_fns, tear_down_isolate_fn)?;
+
+        Ok(Self {
+            _handle: ManuallyDrop::new(handle),
+            isolate,
+            load_fn,
+            load_all_fn,
+            dump_fn,
+            dump_all_fn,
+            tagged_fns,
+            options_fns,
+            version_fn,
+            free_fn,
+        })
+    }
+
+    /// Look up a function in the library, or `None` if it is missing.
+    ///
+    /// # Safety
+    /// `T` must be the function pointer type matching the symbol's prototype.
+    #[cfg(not(feature = "static-link"))]
+    unsafe fn symbol<T: Copy>(handle: &Library, name: &str) -> Option<T> {
+        unsafe { handle.symbol::<T>(name) }.ok().map(|symbol| *symbol)
+    }
+
+    /// Open the first library file in `paths` that exists.
+    #[cfg(not(feature = "static-link"))]
+    pub(crate) fn open_library(paths: &[PathBuf]) -> Result<Library, Error> {
+        // A bundled library takes precedence over any installed one.
+        #[cfg(feature = "bundled")]
+        let mut first_error = match bundled::extract().and_then(|path| Ok(Library::open(path)?)) {
+            Ok(x) => return Ok(x),
+            Err(x) => Some(x),
+        };
+        #[cfg(not(feature = "bundled"))]
+        let mut first_error: Option<Error> = None;
+
+        for path in paths {
+            if !path.is_file() {
+                continue;
+            }
+            let library = Library::open(path);
+
+            match library {
+                Ok(x) => return Ok(x),
+                Err(x) => {
+                    if first_error.is_none() {
+                        first_error = Some(x.into());
+                    }
+                }
+            }
+        }
+
+        match first_error {
+            Some(x) => Err(x),
+            None => Err(Error::NotFound(paths.to_vec())),
+        }
+    }
+
+    /// Call one of the `yamlstar_*` functions that take a string argument.
+    fn call(
+        &self,
+        name: &'static str,
+        func: Option<YamlstarLoadFn>,
+        input: &str,
+    ) -> Result<String, Error> {
+        let input = std::ffi::CString::new(input)
+            .map_err(|_| Error::Ffi(format!("{name}: input contains a nil-byte")))?;
+        let func = func.ok_or(Error::Unsupported(name))?;
+        let thread = self.isolate.current_thread()?;
+        let json = unsafe { (func)(thread, input.as_bytes().as_ptr()) };
+        let raw = YsString::new(self, thread, json)
+            .ok_or_else(|| Error::Ffi(format!("yamlstar_{name}: returned null")))?;
+        Ok(raw.to_str()?.to_string())
+    }
+
+    /// Call one of the `yamlstar_*_with_options` functions.
+    fn call_with_options(
+        &self,
+        name: &'static str,
+        func: Option<YamlstarOptionsFn>,
+        input: &str,
+        options_json: &str,
+    ) -> Result<String, Error> {
+        let input = std::ffi::CString::new(input)
+            .map_err(|_| Error::Ffi(format!("{name}: input contains a nil-byte")))?;
+        let options = std::ffi::CString::new(options_json)
+            .map_err(|_| Error::Ffi(format!("{name}: options contain a nil-byte")))?;
+        let func = func.ok_or(Error::Unsupported(name))?;
+        let thread = self.isolate.current_thread()?;
+        let json = unsafe {
+            (func)(
+                thread,
+                input.as_bytes().as_ptr(),
+                options.as_bytes().as_ptr(),
+            )
+        };
+        let raw = YsString::new(self, thread, json)
+            .ok_or_else(|| Error::Ffi(format!("yamlstar_{name}: returned null")))?;
+        Ok(raw.to_str()?.to_string())
+    }
+}
+
+impl Backend for FfiBackend {
+    fn load(&self, yaml: &str) -> Result<String, Error> {
+        self.call("load", self.load_fn, yaml)
+    }
+
+    fn load_all(&self, yaml: &str) -> Result<String, Error> {
+        self.call("load_all", self.load_all_fn, yaml)
+    }
+
+    fn dump(&self, data_json: &str) -> Result<String, Error> {
+        self.call("dump", self.dump_fn, data_json)
+    }
+
+    fn dump_all(&self, data_json: &str) -> Result<String, Error> {
+        self.call("dump_all", self.dump_all_fn, data_json)
+    }
+
+    fn load_tagged(&self, yaml: &str) -> Result<String, Error> {
+        self.call("load_tagged", self.tagged_fns.map(|fns| fns.load), yaml)
+    }
+
+    fn load_all_tagged(&self, yaml: &str) -> Result<String, Error> {
+        self.call("load_all_tagged", self.tagged_fns.map(|fns| fns.load_all), yaml)
+    }
+
+    fn dump_tagged(&self, data_json: &str) -> Result<String, Error> {
+        self.call("dump_tagged", self.tagged_fns.map(|fns| fns.dump), data_json)
+    }
+
+    fn dump_all_tagged(&self, data_json: &str) -> Result<String, Error> {
+        self.call("dump_all_tagged", self.tagged_fns.map(|fns| fns.dump_all), data_json)
+    }
+
+    fn load_with_options(&self, yaml: &str, options_json: &str) -> Result<String, Error> {
+        let func = self.options_fns.map(|fns| fns.load);
+        self.call_with_options("load_with_options", func, yaml, options_json)
+    }
+
+    fn load_all_with_options(&self, yaml: &str, options_json: &str) -> Result<String, Error> {
+        let func = self.options_fns.map(|fns| fns.load_all);
+        self.call_with_options("load_all_with_options", func, yaml, options_json)
+    }
+
+    fn dump_with_options(&self, data_json: &str, options_json: &str) -> Result<String, Error> {
+        let func = self.options_fns.map(|fns| fns.dump);
+        self.call_with_options("dump_with_options", func, data_json, options_json)
+    }
+
+    fn dump_all_with_options(&self, data_json: &str, options_json: &str) -> Result<String, Error> {
+        let func = self.options_fns.map(|fns| fns.dump_all);
+        self.call_with_options("dump_all_with_options", func, data_json, options_json)
+    }
+
+    fn version(&self) -> Result<String, Error> {
+        let version_fn = self.version_fn.ok_or(Error::Unsupported("version"))?;
+        let thread = self.isolate.current_thread()?;
+        let raw = unsafe { (version_fn)(thread) };
+        let raw = YsString::new(self, thread, raw)
+            .ok_or_else(|| Error::Ffi("yamlstar_version: returned null".to_string()))?;
+        Ok(raw.to_str()?.to_string())
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            load: self.load_fn.is_some(),
+            load_all: self.load_all_fn.is_some(),
+            dump: self.dump_fn.is_some(),
+            dump_all: self.dump_all_fn.is_some(),
+            version: self.version_fn.is_some(),
+            free: self.free_fn.is_some(),
+            per_thread: self.isolate.supports_per_thread(),
+            tagged: self.tagged_fns.is_some(),
+            options: self.options_fns.is_some(),
+        }
+    }
+}
+
+/// A string buffer allocated by libyamlstar.
+///
+/// Every `yamlstar_*` function returns a freshly allocated C string. This
+/// wrapper owns it and hands it back to `yamlstar_free` when dropped.
+struct YsString<'a> {
+    /// The backend whose library allocated the buffer.
+    backend: &'a FfiBackend,
+    /// The isolate thread the buffer was returned on.
+    thread: *mut void,
+    /// The nul-terminated buffer.
+    ptr: NonNull<i8>,
+}
+
+impl<'a> YsString<'a> {
+    /// Take ownership of a buffer returned by `backend`, or `None` if it is
+    /// null.
+    fn new(backend: &'a FfiBackend, thread: *mut void, ptr: *mut i8) -> Option<Self> {
+        NonNull::new(ptr).map(|ptr| Self {
+            backend,
+            thread,
+            ptr,
+        })
+    }
+
+    /// Borrow the buffer as a UTF-8 string.
+    fn to_str(&self) -> Result<&str, Utf8Error> {
+        unsafe { CStr::from_ptr(self.ptr.as_ptr()) }.to_str()
+    }
+}
+
+impl Drop for YsString<'_> {
+    fn drop(&mut self) {
+        if let Some(free_fn) = self.backend.free_fn {
+            unsafe { (free_fn)(self.thread, self.ptr.as_ptr()) };
+        }
+    }
+}
@@ -0,0 +1,239 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Isolate and isolate-thread management.
+//!
+//! A `GraalVM` isolate thread may only be used by the OS thread it belongs to.
+//! In [`ThreadMode::PerThread`] mode every OS thread attaches its own isolate
+//! thread on first use, and a thread-local guard detaches it when the OS
+//! thread exits.
+
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Weak,
+    },
+    thread::ThreadId,
+};
+
+use libc::c_void as void;
+
+use crate::{
+    ffi::{AttachThreadFn, CreateIsolateFn, DetachThreadFn, TearDownIsolateFn},
+    Error,
+};
+
+/// How a [`crate::YAMLStar`] instance assigns isolate threads to OS threads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ThreadMode {
+    /// [`ThreadMode::PerThread`] if the library exports
+    /// `graal_attach_thread` and `graal_detach_thread`, as every build of
+    /// libyamlstar does, and [`ThreadMode::Single`] otherwise.
+    #[default]
+    Auto,
+    /// Use only the isolate thread created along with the isolate, on the
+    /// OS thread that created it. Calls from any other OS thread fail with
+    /// [`Error::WrongThread`].
+    Single,
+    /// Share one isolate, but attach a separate isolate thread for each OS
+    /// thread on first use and detach it when that thread exits. Use this
+    /// when a `GraalVM` engine build is called from several threads.
+    PerThread,
+}
+
+/// Source of unique isolate identifiers.
+///
+/// The Glojure engine reports a null isolate for every instance, so the
+/// isolate pointer cannot tell instances apart.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// Isolate threads attached by the current OS thread.
+    static ATTACHED: RefCell<Vec<AttachedThread>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A `GraalVM` isolate owned by a [`crate::YAMLStar`] instance.
+pub(crate) struct Isolate {
+    /// Unique identifier of this isolate within the process.
+    id: u64,
+    /// The isolate handle.
+    handle: *mut void,
+    /// The isolate thread created along with the isolate.
+    thread: *mut void,
+    /// The OS thread that created the isolate and owns `thread`.
+    owner: ThreadId,
+    /// How isolate threads are assigned to OS threads. Never
+    /// [`ThreadMode::Auto`].
+    mode: ThreadMode,
+    /// Pointers to the `graal_attach_thread` and `graal_detach_thread`
+    /// functions, if the library has both.
+    thread_fns: Option<(AttachThreadFn, DetachThreadFn)>,
+    /// Pointer to the `graal_tear_down_isolate` function.
+    tear_down_isolate_fn: TearDownIsolateFn,
+}
+
+// SAFETY: The isolate handles are opaque and only passed back to
+// libyamlstar. An isolate thread is only used on the OS thread it belongs to:
+// `current_thread` hands out the creating thread's isolate thread on that OS
+// thread alone, and attaches a separate one anywhere else in `PerThread` mode,
+// and `Drop` tears down with an isolate thread of the OS thread it runs on.
+unsafe impl Send for Isolate {}
+unsafe impl Sync for Isolate {}
+
+impl Isolate {
+    /// Create a new isolate and its first isolate thread.
+    ///
+    /// Fails with [`Error::Unsupported`] if `mode` is
+    /// [`ThreadMode::PerThread`] and `thread_fns` is missing.
+    pub(crate) fn create(
+        mode: ThreadMode,
+        create_isolate_fn: CreateIsolateFn,
+        thread_fns: Option<(AttachThreadFn, DetachThreadFn)>,
+        tear_down_isolate_fn: TearDownIsolateFn,
+    ) -> Result<Arc<Self>, Error> {
+        let mode = match mode {
+            ThreadMode::Auto if thread_fns.is_some() => ThreadMode::PerThread,
+            ThreadMode::Auto => ThreadMode::Single,
+            ThreadMode::PerThread if thread_fns.is_none() => {
+                return Err(Error::Unsupported("per_thread"));
+            }
+            mode => mode,
+        };
+
+        let isolate = std::ptr::null_mut();
+        let thread = std::ptr::null_mut();
+
+        let x = unsafe {
+            (create_isolate_fn)(std::ptr::null_mut(), &raw const isolate, &raw const thread)
+        };
+        if x != 0 {
+            return Err(Error::GraalVM(x));
+        }
+
+        Ok(Arc::new(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            handle: isolate,
+            thread,
+            owner: std::thread::current().id(),
+            mode,
+            thread_fns,
+            tear_down_isolate_fn,
+        }))
+    }
+
+    /// Whether the library can attach isolate threads per OS thread.
+    pub(crate) fn supports_per_thread(&self) -> bool {
+        self.thread_fns.is_some()
+    }
+
+    /// Get the isolate thread the current OS thread must use, attaching one
+    /// if needed.
+    ///
+    /// Fails with [`Error::WrongThread`] off the creating OS thread in
+    /// [`ThreadMode::Single`] mode.
+    pub(crate) fn current_thread(self: &Arc<Self>) -> Result<*mut void, Error> {
+        if std::thread::current().id() == self.owner {
+            return Ok(self.thread);
+        }
+        let Some((attach_thread_fn, _)) =
+            self.thread_fns.filter(|_| self.mode == ThreadMode::PerThread)
+        else {
+            return Err(Error::WrongThread);
+        };
+
+        ATTACHED.with(|attached| {
+            let mut attached = attached.borrow_mut();
+            if let Some(entry) = attached.iter().find(|entry| entry.id == self.id) {
+                return Ok(entry.thread);
+            }
+
+            // Forget threads of isolates that have since been torn down.
+            attached.retain(|entry| entry.isolate.strong_count() > 0);
+
+            let mut thread = std::ptr::null_mut();
+            let x = unsafe { (attach_thread_fn)(self.handle, &raw mut thread) };
+            if x != 0 {
+                return Err(Error::GraalVM(x));
+            }
+            attached.push(AttachedThread {
+                id: self.id,
+                isolate: Arc::downgrade(self),
+                thread,
+            });
+            Ok(thread)
+        })
+    }
+}
+
+impl Drop for Isolate {
+    fn drop(&mut self) {
+        let Some(thread) = self.tear_down_thread() else {
+            eprintln!(
+                "Warning: Leaking yamlstar's GraalVM isolate, dropped on a thread \
+                that cannot attach to it"
+            );
+            return;
+        };
+        let res = unsafe { (self.tear_down_isolate_fn)(thread) };
+        if res != 0 {
+            eprintln!("Warning: Failed to tear down yamlstar's GraalVM isolate");
+        }
+    }
+}
+
+impl Isolate {
+    /// Get an isolate thread of the current OS thread to tear the isolate
+    /// down with, attaching one if needed, or `None` if there is none.
+    fn tear_down_thread(&self) -> Option<*mut void> {
+        if std::thread::current().id() == self.owner {
+            return Some(self.thread);
+        }
+
+        // Take the thread this OS thread attached, if any. Tearing down
+        // detaches it, and the isolate is already gone for its guard. The
+        // list may be borrowed or destroyed if the isolate is dropped by a
+        // guard or during thread exit.
+        let attached = ATTACHED
+            .try_with(|attached| {
+                let mut attached = attached.try_borrow_mut().ok()?;
+                let index = attached.iter().position(|entry| entry.id == self.id)?;
+                Some(attached.swap_remove(index).thread)
+            })
+            .ok()
+            .flatten();
+        if attached.is_some() {
+            return attached;
+        }
+
+        let (attach_thread_fn, _) = self.thread_fns?;
+        let mut thread = std::ptr::null_mut();
+        let x = unsafe { (attach_thread_fn)(self.handle, &raw mut thread) };
+        (x == 0).then_some(thread)
+    }
+}
+
+/// An isolate thread attached by the current OS thread.
+///
+/// Dropped along with the thread-local list when the OS thread exits.
+struct AttachedThread {
+    /// Identifier of the isolate the thread belongs to.
+    id: u64,
+    /// The isolate the thread belongs to.
+    isolate: Weak<Isolate>,
+    /// The attached isolate thread.
+    thread: *mut void,
+}
+
+impl Drop for AttachedThread {
+    fn drop(&mut self) {
+        // Tearing down an isolate detaches all of its threads, so there is
+        // nothing left to do once the isolate is gone.
+        if let Some((_, detach_thread_fn)) = self.isolate.upgrade().and_then(|x| x.thread_fns) {
+            let res = unsafe { (detach_thread_fn)(self.thread) };
+            if res != 0 {
+                eprintln!("Warning: Failed to detach yamlstar's GraalVM isolate thread");
+            }
+        }
+    }
+}
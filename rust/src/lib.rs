@@ -31,199 +31,337 @@
 //! assert_eq!(config.host, "localhost");
 //! assert_eq!(config.port, 8080);
 //! ```
+//!
+//! ## Sharing one instance
+//! [`YAMLStar`] is `Send + Sync`: by default each OS thread that uses an
+//! instance attaches its own isolate thread; see [`ThreadMode`].
+//! [`YAMLStar::global`] returns a lazily created instance shared by every
+//! thread, and [`from_str`] and [`to_string`] are shortcuts that use it.
+//! ```no_run
+//! let data: serde_json::Value = yamlstar::from_str("key: value").unwrap();
+//! let yaml = yamlstar::to_string(&data).unwrap();
+//! assert_eq!(yaml, "key: value\n");
+//! ```
 
 #![warn(clippy::pedantic)]
 
-use std::{mem::ManuallyDrop, path::Path};
-
-use dlopen::symbor::Library;
-use libc::{c_int, c_void as void};
-
+use std::{
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+
+#[cfg(all(feature = "static-link", feature = "bundled"))]
+compile_error!("The `static-link` and `bundled` features cannot be enabled together.");
+
+mod backend;
+mod builder;
+#[cfg(feature = "bundled")]
+mod bundled;
+mod cli;
+mod diagnostics;
+mod env;
 mod error;
-
+mod ffi;
+mod isolate;
+#[cfg(feature = "static-link")]
+mod linked;
+mod mock;
+mod options;
+mod tags;
+mod value;
+mod version;
+mod worker;
+
+pub use backend::{Backend, Capabilities};
+pub use builder::{BackendKind, Builder, LIBRARY_ENV_VAR};
+pub use cli::{CliBackend, CLI_ENV_VAR};
+pub use diagnostics::{Diagnostic, Diagnostics, DuplicateKey, DuplicateKeys};
+pub use env::{EnvProvider, StdEnv};
 pub use error::Error;
+pub use isolate::ThreadMode;
+pub use mock::{MockBackend, MockOp};
+pub use options::{Resolver, Schema};
+pub use value::{
+    from_value, to_value, Index, Mapping, MaybeTagged, Number, Tagged, TaggedValue, Value,
+};
+pub use version::{ParseVersionError, Version, VersionPolicy};
+pub use worker::{run_worker, run_worker_if_requested, WORKER_ENV_VAR};
 use serde::{Deserialize, Serialize};
 
-use crate::error::LibYSError;
+use crate::{
+    env::Env,
+    error::LibYSError,
+    options::Options,
+    tags::Tags,
+    value::{from_value_with_schema, to_value_with_tagged_enums},
+};
 
-/// The name of the YAMLStar library to load.
+/// The name of the `YAMLStar` library to load.
+#[cfg_attr(feature = "static-link", allow(dead_code))]
 const LIBYAMLSTAR_BASENAME: &str = "libyamlstar";
 
 /// The version of the yamlstar library this binding works with.
 pub const LIBYAMLSTAR_VERSION: &str = "0.1.18";
 
-/// The extension of the YAMLStar library. On Linux, it's a `.so` file.
+/// The extension of the `YAMLStar` library. On Linux, it's a `.so` file.
 #[cfg(target_os = "linux")]
+#[cfg_attr(feature = "static-link", allow(dead_code))]
 const LIBYAMLSTAR_EXTENSION: &str = "so";
-/// The extension of the YAMLStar library. On MacOS, it's a `.dylib` file.
+/// The extension of the `YAMLStar` library. On MacOS, it's a `.dylib` file.
 #[cfg(target_os = "macos")]
+#[cfg_attr(feature = "static-link", allow(dead_code))]
 const LIBYAMLSTAR_EXTENSION: &str = "dylib";
-/// The extension of the YAMLStar library. On Windows, it's a `.dll` file.
+/// The extension of the `YAMLStar` library. On Windows, it's a `.dll` file.
 #[cfg(target_os = "windows")]
+#[cfg_attr(feature = "static-link", allow(dead_code))]
 const LIBYAMLSTAR_EXTENSION: &str = "dll";
 #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
 compile_error!("Unsupported platform for yamlstar.");
 
-/// Prototype of the `graal_create_isolate` function.
-type CreateIsolateFn = unsafe extern "C" fn(*mut void, *const *mut void, *const *mut void) -> c_int;
-/// Prototype of the `graal_tear_down_isolate` function.
-type TearDownIsolateFn = unsafe extern "C" fn(*mut void) -> c_int;
-/// Prototype of the `yamlstar_load` function.
-type YamlstarLoadFn = unsafe extern "C" fn(*mut void, *const u8) -> *mut i8;
-/// Prototype of the `yamlstar_load_all` function.
-type YamlstarLoadAllFn = unsafe extern "C" fn(*mut void, *const u8) -> *mut i8;
-/// Prototype of the `yamlstar_dump` function.
-type YamlstarDumpFn = unsafe extern "C" fn(*mut void, *const u8) -> *mut i8;
-/// Prototype of the `yamlstar_dump_all` function.
-type YamlstarDumpAllFn = unsafe extern "C" fn(*mut void, *const u8) -> *mut i8;
-/// Prototype of the `yamlstar_version` function.
-type YamlstarVersionFn = unsafe extern "C" fn(*mut void) -> *mut i8;
-
 /// A wrapper around libyamlstar.
+#[allow(clippy::struct_excessive_bools)]
 pub struct YAMLStar {
-    /// A handle to the opened dynamic library.
-    // Go c-shared libraries cannot be safely unloaded on macOS. Keep the
-    // process-wide library loaded until process exit.
-    _handle: ManuallyDrop<Library>,
-    /// A GraalVM isolate.
-    _isolate: *mut void,
-    /// A GraalVM isolate thread.
-    isolate_thread: *mut void,
-    /// Pointer to the function in GraalVM to create the isolate and its thread.
-    _create_isolate_fn: CreateIsolateFn,
-    /// Pointer to the function in GraalVM to free an isolate thread.
-    tear_down_isolate_fn: TearDownIsolateFn,
-    /// Pointer to the `yamlstar_load` function.
-    load_fn: YamlstarLoadFn,
-    /// Pointer to the `yamlstar_load_all` function.
-    load_all_fn: YamlstarLoadAllFn,
-    /// Pointer to the `yamlstar_dump` function.
-    dump_fn: YamlstarDumpFn,
-    /// Pointer to the `yamlstar_dump_all` function.
-    dump_all_fn: YamlstarDumpAllFn,
-    /// Pointer to the `yamlstar_version` function.
-    version_fn: YamlstarVersionFn,
+    /// The engine that does the work.
+    backend: Box<dyn Backend>,
+    /// Whether the backend has the tagged operations, which every load and
+    /// dump then goes through.
+    tagged: bool,
+    /// Whether plain JSON goes through the operations with options, which
+    /// unlike the plain ones mark infinite and NaN floats and escape `!`
+    /// keys. It does whenever the backend has them but not the tagged ones.
+    marked: bool,
+    /// Whether integers that do not fit in an `i64` may be loaded.
+    arbitrary_precision: bool,
+    /// Whether enum variants that hold data are dumped as `!Variant` tags.
+    tagged_enums: bool,
+    /// The options libyamlstar applies; any but the defaults send every
+    /// load and dump through the operations with options.
+    options: Options,
+    /// Constructors for custom tags, applied to every loaded document.
+    tags: Tags,
+    /// Where `!env` and `${NAME}` are looked up, if they are expanded.
+    env: Option<Env>,
+    /// The warnings of every load.
+    diagnostics: Diagnostics,
 }
 
 impl YAMLStar {
-    /// Create a new instance of a YAMLStar loader.
+    /// Create a new instance of a `YAMLStar` loader.
     ///
     /// # Errors
     /// Returns an error if we fail to open the library.
     /// Returns [`Error::NotFound`] if the library cannot be found.
-    #[allow(clippy::crosspointer_transmute)]
+    /// Returns [`Error::VersionMismatch`] if the library is not version
+    /// [`LIBYAMLSTAR_VERSION`].
     pub fn new() -> Result<Self, Error> {
-        // Open library and create pointers the library needs.
-        let handle = Self::open_library()?;
-        let isolate = std::ptr::null_mut();
-        let isolate_thread = std::ptr::null_mut();
-
-        // Fetch symbols.
-        let create_isolate_fn =
-            unsafe { handle.ptr_or_null::<CreateIsolateFn>("graal_create_isolate")? };
-        let tear_down_isolate_fn =
-            unsafe { handle.ptr_or_null::<TearDownIsolateFn>("graal_tear_down_isolate")? };
-        let load_fn = unsafe { handle.ptr_or_null::<YamlstarLoadFn>("yamlstar_load")? };
-        let load_all_fn =
-            unsafe { handle.ptr_or_null::<YamlstarLoadAllFn>("yamlstar_load_all")? };
-        let dump_fn = unsafe { handle.ptr_or_null::<YamlstarDumpFn>("yamlstar_dump")? };
-        let dump_all_fn =
-            unsafe { handle.ptr_or_null::<YamlstarDumpAllFn>("yamlstar_dump_all")? };
-        let version_fn = unsafe { handle.ptr_or_null::<YamlstarVersionFn>("yamlstar_version")? };
-
-        // Check for null-ness.
-        if create_isolate_fn.is_null()
-            || tear_down_isolate_fn.is_null()
-            || load_fn.is_null()
-            || load_all_fn.is_null()
-            || dump_fn.is_null()
-            || dump_all_fn.is_null()
-            || version_fn.is_null()
-        {
-            return Err(Error::Load(dlopen::Error::NullSymbol));
-        }
+        Builder::new().build()
+    }
 
-        // Transmute to remove borrow and convert to correct Rust type.
-        let create_isolate_fn: CreateIsolateFn = unsafe { std::mem::transmute(*create_isolate_fn) };
-        let tear_down_isolate_fn: TearDownIsolateFn =
-            unsafe { std::mem::transmute(*tear_down_isolate_fn) };
-        let load_fn: YamlstarLoadFn = unsafe { std::mem::transmute(*load_fn) };
-        let load_all_fn: YamlstarLoadAllFn = unsafe { std::mem::transmute(*load_all_fn) };
-        let dump_fn: YamlstarDumpFn = unsafe { std::mem::transmute(*dump_fn) };
-        let dump_all_fn: YamlstarDumpAllFn = unsafe { std::mem::transmute(*dump_all_fn) };
-        let version_fn: YamlstarVersionFn = unsafe { std::mem::transmute(*version_fn) };
-
-        // Create GraalVM isolate.
-        let x = unsafe {
-            (create_isolate_fn)(
-                std::ptr::null_mut(),
-                &raw const isolate,
-                &raw const isolate_thread,
-            )
-        };
-        if x != 0 {
-            return Err(Error::GraalVM(x));
+    /// Create a [`Builder`] to configure where the library is found and how
+    /// the instance uses it.
+    #[must_use]
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    /// Create a new instance of a `YAMLStar` loader using the given
+    /// [`ThreadMode`].
+    ///
+    /// # Errors
+    /// Returns an error if we fail to open the library.
+    /// Returns [`Error::NotFound`] if the library cannot be found.
+    pub fn with_thread_mode(mode: ThreadMode) -> Result<Self, Error> {
+        Builder::new().thread_mode(mode).build()
+    }
+
+    /// Create an instance that uses `backend` instead of libyamlstar.
+    #[must_use]
+    pub fn with_backend(backend: impl Backend + 'static) -> Self {
+        let capabilities = backend.capabilities();
+        Self {
+            backend: Box::new(backend),
+            tagged: capabilities.tagged,
+            marked: !capabilities.tagged && capabilities.options,
+            arbitrary_precision: false,
+            tagged_enums: false,
+            options: Options::default(),
+            tags: Tags::default(),
+            env: None,
+            diagnostics: Diagnostics::default(),
         }
+    }
 
-        Ok(Self {
-            _handle: ManuallyDrop::new(handle),
-            _isolate: isolate,
-            isolate_thread,
-            _create_isolate_fn: create_isolate_fn,
-            tear_down_isolate_fn,
-            load_fn,
-            load_all_fn,
-            dump_fn,
-            dump_all_fn,
-            version_fn,
-        })
+    /// Report which operations the loaded library supports.
+    #[must_use]
+    pub fn capabilities(&self) -> Capabilities {
+        self.backend.capabilities()
+    }
+
+    /// The collector of the warnings from loads with this instance, such as
+    /// duplicate keys with [`DuplicateKeys::Warn`].
+    #[must_use]
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Get the process-wide shared instance, creating it on first use.
+    ///
+    /// If creating the instance fails, the error is returned and the next call
+    /// tries again.
+    ///
+    /// # Errors
+    /// Returns an error if the instance has not been created yet and
+    /// [`YAMLStar::new`] fails.
+    pub fn global() -> Result<&'static Self, Error> {
+        static GLOBAL: OnceLock<YAMLStar> = OnceLock::new();
+        static INIT: Mutex<()> = Mutex::new(());
+
+        if let Some(ys) = GLOBAL.get() {
+            return Ok(ys);
+        }
+        let _guard = INIT.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(ys) = GLOBAL.get() {
+            return Ok(ys);
+        }
+        let ys = Self::new()?;
+        Ok(GLOBAL.get_or_init(|| ys))
     }
 
     /// Load a YAML string and return the first document, deserialized.
     ///
+    /// Load into a [`Value`] to keep everything the document holds.
+    ///
     /// # Errors
     /// Returns an error if the input string is invalid or YAML parsing fails.
+    /// Returns [`Error::Unsupported`] if the library lacks `yamlstar_load`.
     pub fn load<T>(&self, yaml: &str) -> Result<T, Error>
     where
         T: serde::de::DeserializeOwned,
     {
-        let raw = unsafe { std::ffi::CStr::from_ptr(self.load_raw(yaml)?) }.to_str()?;
-        let response = serde_json::from_str::<YsResponse<T>>(raw)?;
+        from_value_with_schema(self.load_value(yaml, &self.options)?, self.options.schema)
+    }
 
-        match response {
-            YsResponse::Data(value) => Ok(value),
-            YsResponse::Error(err) => Err(Error::YAMLStar(err)),
+    /// Load the YAML file at `path` and return the first document,
+    /// deserialized.
+    ///
+    /// With [`Builder::include_root`], `!include` paths in the file are
+    /// relative to it, and the file itself must be under the root.
+    ///
+    /// # Errors
+    /// Returns [`Error::Io`] if the file cannot be read, or is outside the
+    /// include root.
+    /// Returns an error if YAML parsing fails, as for [`YAMLStar::load`].
+    pub fn load_file<T>(&self, path: impl AsRef<Path>) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let path = path.as_ref();
+        let yaml = std::fs::read_to_string(path)?;
+        let Some(root) = &self.options.include_root else {
+            return self.load(&yaml);
+        };
+        let file = path.canonicalize()?;
+        if !file.starts_with(root) {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!(
+                    "{} is outside the include root {}",
+                    path.display(),
+                    root.display()
+                ),
+            )));
         }
+        let options = Options {
+            file: Some(file),
+            ..self.options.clone()
+        };
+        from_value_with_schema(self.load_value(&yaml, &options)?, options.schema)
     }
 
     /// Load a YAML string and return all documents, deserialized.
     ///
     /// # Errors
     /// Returns an error if the input string is invalid or YAML parsing fails.
+    /// Returns [`Error::Unsupported`] if the library lacks `yamlstar_load_all`.
     pub fn load_all<T>(&self, yaml: &str) -> Result<Vec<T>, Error>
     where
         T: serde::de::DeserializeOwned,
     {
-        let raw = unsafe { std::ffi::CStr::from_ptr(self.load_all_raw(yaml)?) }.to_str()?;
-        let response = serde_json::from_str::<YsResponse<Vec<T>>>(raw)?;
+        let raw = match self.options_json(&self.options) {
+            Some(options) => self.backend.load_all_with_options(yaml, &options)?,
+            None if self.tagged => self.backend.load_all_tagged(yaml)?,
+            None => self.backend.load_all(yaml)?,
+        };
+        let value = match self.value_response(&raw, true)? {
+            Value::Sequence(docs) => Value::Sequence(
+                docs.into_iter()
+                    .enumerate()
+                    .map(|(i, doc)| self.finish_document(doc, i))
+                    .collect::<Result<_, _>>()?,
+            ),
+            value => value,
+        };
+        from_value_with_schema(value, self.options.schema)
+    }
 
-        match response {
-            YsResponse::Data(value) => Ok(value),
-            YsResponse::Error(err) => Err(Error::YAMLStar(err)),
+    /// Construct every node tagged `tag` with `constructor` when loading,
+    /// replacing any constructor `tag` had.
+    ///
+    /// The constructor gets the node as a [`TaggedValue`]: a scalar as its
+    /// text, and a collection with its children already constructed. Nodes
+    /// with a tag that has no constructor load as a [`Value::Tagged`]. Give
+    /// `!!` tags in short form, such as `"!!set"`.
+    ///
+    /// If the constructor fails, loading fails with [`Error::Tag`], which
+    /// holds the tag and where the node is.
+    ///
+    /// ```no_run
+    /// use yamlstar::{TaggedValue, Value, YAMLStar};
+    ///
+    /// let mut ys = YAMLStar::new().unwrap();
+    /// ys.register_tag("!upper", |node: TaggedValue| match node.value {
+    ///     Value::String(s) => Ok(Value::String(s.to_uppercase())),
+    ///     _ => Err("!upper needs a scalar"),
+    /// })
+    /// .unwrap();
+    /// assert_eq!(ys.load::<String>("!upper abc").unwrap(), "ABC");
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`Error::Unsupported`] if the library lacks the tagged
+    /// exports, without which custom tags fail to load; see
+    /// [`Capabilities::tagged`].
+    pub fn register_tag<F, E>(&mut self, tag: &str, constructor: F) -> Result<&mut Self, Error>
+    where
+        F: Fn(TaggedValue) -> Result<Value, E> + Send + Sync + 'static,
+        E: Into<tags::TagError>,
+    {
+        if !self.tagged {
+            return Err(Error::Unsupported("register_tag"));
         }
+        self.tags.insert(
+            tag,
+            std::sync::Arc::new(move |node| constructor(node).map_err(Into::into)),
+        );
+        Ok(self)
     }
 
     /// Dump a serializable value to a YAML string.
     ///
     /// # Errors
-    /// Returns an error if the value cannot be serialized to JSON or YAMLStar fails.
+    /// Returns an error if the value cannot be serialized to JSON or `YAMLStar` fails.
+    /// Returns [`Error::Unsupported`] if the library lacks `yamlstar_dump`.
     pub fn dump<T>(&self, value: &T) -> Result<String, Error>
     where
         T: Serialize,
     {
-        let data = serde_json::to_string(value)?;
-        let raw = unsafe { std::ffi::CStr::from_ptr(self.dump_raw(&data)?) }.to_str()?;
-        let response = serde_json::from_str::<YsResponse<String>>(raw)?;
+        let data = self.dump_data(to_value_with_tagged_enums(value, self.tagged_enums)?)?;
+        let raw = match self.options_json(&self.options) {
+            Some(options) => self.backend.dump_with_options(&data, &options)?,
+            None if self.tagged => self.backend.dump_tagged(&data)?,
+            None => self.backend.dump(&data)?,
+        };
+        let response = serde_json::from_str::<YsResponse<String>>(&raw)?;
 
         match response {
             YsResponse::Data(value) => Ok(value),
@@ -234,14 +372,19 @@ impl YAMLStar {
     /// Dump serializable values to a multi-document YAML stream.
     ///
     /// # Errors
-    /// Returns an error if the values cannot be serialized to JSON or YAMLStar fails.
+    /// Returns an error if the values cannot be serialized to JSON or `YAMLStar` fails.
+    /// Returns [`Error::Unsupported`] if the library lacks `yamlstar_dump_all`.
     pub fn dump_all<T>(&self, values: &[T]) -> Result<String, Error>
     where
         T: Serialize,
     {
-        let data = serde_json::to_string(values)?;
-        let raw = unsafe { std::ffi::CStr::from_ptr(self.dump_all_raw(&data)?) }.to_str()?;
-        let response = serde_json::from_str::<YsResponse<String>>(raw)?;
+        let data = self.dump_data(to_value_with_tagged_enums(values, self.tagged_enums)?)?;
+        let raw = match self.options_json(&self.options) {
+            Some(options) => self.backend.dump_all_with_options(&data, &options)?,
+            None if self.tagged => self.backend.dump_all_tagged(&data)?,
+            None => self.backend.dump_all(&data)?,
+        };
+        let response = serde_json::from_str::<YsResponse<String>>(&raw)?;
 
         match response {
             YsResponse::Data(value) => Ok(value),
@@ -249,134 +392,147 @@ impl YAMLStar {
         }
     }
 
-    /// Get the YAMLStar library version.
+    /// Get the `YAMLStar` library version.
     ///
     /// # Errors
     /// Returns an error if the version string cannot be retrieved.
+    /// Returns [`Error::Unsupported`] if the library cannot report its version.
     pub fn version(&self) -> Result<String, Error> {
-        let raw = unsafe { (self.version_fn)(self.isolate_thread) };
-        if raw.is_null() {
-            return Err(Error::Ffi("yamlstar_version: returned null".to_string()));
-        }
-        let version = unsafe { std::ffi::CStr::from_ptr(raw) }.to_str()?;
-        Ok(version.to_string())
+        self.backend.version()
     }
 
-    /// Load a YAML string, returning the raw buffer from the library.
-    fn load_raw(&self, yaml: &str) -> Result<*mut i8, Error> {
-        let input = std::ffi::CString::new(yaml)
-            .map_err(|_| Error::Ffi("load: input contains a nil-byte".to_string()))?;
-        let json = unsafe { (self.load_fn)(self.isolate_thread, input.as_bytes().as_ptr()) };
-        if json.is_null() {
-            Err(Error::Ffi("yamlstar_load: returned null".to_string()))
-        } else {
-            Ok(json)
-        }
+    /// Get the `YAMLStar` library version, parsed.
+    ///
+    /// # Errors
+    /// Returns an error if the version string cannot be retrieved.
+    /// Returns [`Error::VersionMismatch`] if it is not a valid version.
+    pub fn library_version(&self) -> Result<Version, Error> {
+        let found = self.version()?;
+        found.parse().map_err(|_| Error::VersionMismatch {
+            expected: LIBYAMLSTAR_VERSION.to_string(),
+            found,
+        })
     }
 
-    /// Load all YAML documents, returning the raw buffer from the library.
-    fn load_all_raw(&self, yaml: &str) -> Result<*mut i8, Error> {
-        let input = std::ffi::CString::new(yaml)
-            .map_err(|_| Error::Ffi("load_all: input contains a nil-byte".to_string()))?;
-        let json = unsafe { (self.load_all_fn)(self.isolate_thread, input.as_bytes().as_ptr()) };
-        if json.is_null() {
-            Err(Error::Ffi(
-                "yamlstar_load_all: returned null".to_string(),
-            ))
-        } else {
-            Ok(json)
-        }
+    /// Load the first document of `yaml` with `options` and apply the tag
+    /// constructors.
+    fn load_value(&self, yaml: &str, options: &Options) -> Result<Value, Error> {
+        let raw = match self.options_json(options) {
+            Some(options) => self.backend.load_with_options(yaml, &options)?,
+            None if self.tagged => self.backend.load_tagged(yaml)?,
+            None => self.backend.load(yaml)?,
+        };
+        let value = self.value_response(&raw, false)?;
+        self.finish_document(value, 0)
     }
 
-    /// Dump a JSON string, returning the raw buffer from the library.
-    fn dump_raw(&self, data_json: &str) -> Result<*mut i8, Error> {
-        let input = std::ffi::CString::new(data_json)
-            .map_err(|_| Error::Ffi("dump: input contains a nil-byte".to_string()))?;
-        let json = unsafe { (self.dump_fn)(self.isolate_thread, input.as_bytes().as_ptr()) };
-        if json.is_null() {
-            Err(Error::Ffi("yamlstar_dump: returned null".to_string()))
-        } else {
-            Ok(json)
-        }
+    /// Expand environment variables in the loaded document numbered
+    /// `document`, then apply the tag constructors.
+    fn finish_document(&self, value: Value, document: usize) -> Result<Value, Error> {
+        let value = match &self.env {
+            Some(env) => env.expand(value, document, self.options.schema)?,
+            None => value,
+        };
+        self.tags.construct(value, document)
     }
 
-    /// Dump JSON documents, returning the raw buffer from the library.
-    fn dump_all_raw(&self, data_json: &str) -> Result<*mut i8, Error> {
-        let input = std::ffi::CString::new(data_json)
-            .map_err(|_| Error::Ffi("dump_all: input contains a nil-byte".to_string()))?;
-        let json = unsafe { (self.dump_all_fn)(self.isolate_thread, input.as_bytes().as_ptr()) };
-        if json.is_null() {
-            Err(Error::Ffi(
-                "yamlstar_dump_all: returned null".to_string(),
-            ))
+    /// The options JSON to call the operations with options with, or `None`
+    /// to call the others.
+    fn options_json(&self, options: &Options) -> Option<String> {
+        (self.marked || !options.is_default()).then(|| options.to_json(self.tagged))
+    }
+
+    /// Encode a value to dump, in the tagged encoding or plain JSON.
+    fn dump_data(&self, value: Value) -> Result<String, Error> {
+        if self.tagged {
+            let value = if self.options.has_predicates() {
+                value::wire::quote_resolved(value, &self.options)
+            } else {
+                value
+            };
+            Ok(value::wire::encode(&value).to_string())
         } else {
-            Ok(json)
+            let plain = value::wire::Plain {
+                value: &value,
+                marked: self.marked,
+            };
+            Ok(serde_json::to_string(&plain)?)
         }
     }
 
-    /// Open the library found at the first matching path in LD_LIBRARY_PATH.
-    fn open_library() -> Result<Library, Error> {
-        let mut first_error = None;
-
-        // Build list of search paths
-        let mut search_paths: Vec<String> = Vec::new();
-
-        // Check relative to crate for development
-        if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
-            search_paths.push(format!("{manifest_dir}/../libyamlstar/lib"));
+    /// Decode a load response envelope, in the tagged encoding or plain JSON.
+    /// `stream` tells whether it holds a sequence of documents.
+    fn value_response(&self, raw: &str, stream: bool) -> Result<Value, Error> {
+        let mut data = match serde_json::from_str::<YsResponse<Value>>(raw)? {
+            YsResponse::Data(data) => data,
+            YsResponse::Error(err) => return Err(Error::YAMLStar(err)),
+        };
+        if self.options.has_predicates() {
+            data = value::wire::resolve_plain(data, &self.options)?;
         }
-
-        // Check LD_LIBRARY_PATH (Unix) or PATH (Windows)
-        let path_var = if cfg!(windows) { "PATH" } else { "LD_LIBRARY_PATH" };
-        let path_sep = if cfg!(windows) { ';' } else { ':' };
-        if let Ok(library_path) = std::env::var(path_var) {
-            for path in library_path.split(path_sep) {
-                search_paths.push(path.to_string());
-            }
+        let policy = self.options.duplicate_keys;
+        if policy != DuplicateKeys::Last {
+            let mut warnings = Vec::new();
+            data = match data {
+                Value::Sequence(docs) if stream => Value::Sequence(
+                    docs.into_iter()
+                        .enumerate()
+                        .map(|(i, doc)| value::wire::check_keys(doc, policy, i, &mut warnings))
+                        .collect::<Result<_, _>>()?,
+                ),
+                data => value::wire::check_keys(data, policy, 0, &mut warnings)?,
+            };
+            self.diagnostics.extend(warnings);
         }
-
-        // Standard locations
-        search_paths.push("/usr/local/lib".to_string());
-        if let Ok(home) = std::env::var("HOME") {
-            search_paths.push(format!("{home}/.local/lib"));
+        if self.tagged {
+            value::wire::decode(data, self.arbitrary_precision)
+        } else {
+            value::wire::decode_plain(data, self.marked, self.arbitrary_precision)
         }
+    }
 
-        let library_filename = format!(
-            "{}.{}.{}",
-            LIBYAMLSTAR_BASENAME, LIBYAMLSTAR_EXTENSION, LIBYAMLSTAR_VERSION
-        );
-
-        for path in &search_paths {
-            let full_path = Path::new(path).join(&library_filename);
-            if !full_path.is_file() {
-                continue;
-            }
-            let library = Library::open(&full_path);
-
-            match library {
-                Ok(x) => return Ok(x),
-                Err(x) => {
-                    if first_error.is_none() {
-                        first_error = Some(x);
-                    }
-                }
-            }
+    /// Check the library version against [`LIBYAMLSTAR_VERSION`].
+    fn check_version(&self, policy: VersionPolicy) -> Result<(), Error> {
+        if policy == VersionPolicy::Any {
+            return Ok(());
         }
-
-        match first_error {
-            Some(x) => Err(x.into()),
-            None => Err(Error::NotFound),
+        let expected = LIBYAMLSTAR_VERSION
+            .parse::<Version>()
+            .expect("LIBYAMLSTAR_VERSION is a valid version");
+        let found = self.library_version()?;
+        if policy.accepts(&expected, &found) {
+            Ok(())
+        } else {
+            Err(Error::VersionMismatch {
+                expected: expected.to_string(),
+                found: found.to_string(),
+            })
         }
     }
 }
 
-impl Drop for YAMLStar {
-    fn drop(&mut self) {
-        let res = unsafe { (self.tear_down_isolate_fn)(self.isolate_thread) };
-        if res != 0 {
-            eprintln!("Warning: Failed to tear down yamlstar's GraalVM isolate");
-        }
-    }
+/// Load a YAML string using the shared [`YAMLStar::global`] instance.
+///
+/// # Errors
+/// Returns an error if the shared instance cannot be created or
+/// [`YAMLStar::load`] fails.
+pub fn from_str<T>(yaml: &str) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    YAMLStar::global()?.load(yaml)
+}
+
+/// Dump a value to YAML using the shared [`YAMLStar::global`] instance.
+///
+/// # Errors
+/// Returns an error if the shared instance cannot be created or
+/// [`YAMLStar::dump`] fails.
+pub fn to_string<T>(value: &T) -> Result<String, Error>
+where
+    T: Serialize,
+{
+    YAMLStar::global()?.dump(value)
 }
 
 /// A response from the yamlstar library.
@@ -0,0 +1,50 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! libyamlstar functions linked at build time by the `static-link` feature.
+//!
+//! `build.rs` tells the linker where to find the library, so these
+//! declarations carry no `#[link]` attribute.
+
+use libc::{c_int, c_void as void};
+
+extern "C" {
+    pub(crate) fn graal_create_isolate(
+        params: *mut void,
+        isolate: *const *mut void,
+        thread: *const *mut void,
+    ) -> c_int;
+    pub(crate) fn graal_tear_down_isolate(thread: *mut void) -> c_int;
+    pub(crate) fn graal_attach_thread(isolate: *mut void, thread: *mut *mut void) -> c_int;
+    pub(crate) fn graal_detach_thread(thread: *mut void) -> c_int;
+    pub(crate) fn yamlstar_load(thread: *mut void, yaml: *const u8) -> *mut i8;
+    pub(crate) fn yamlstar_load_all(thread: *mut void, yaml: *const u8) -> *mut i8;
+    pub(crate) fn yamlstar_dump(thread: *mut void, data_json: *const u8) -> *mut i8;
+    pub(crate) fn yamlstar_dump_all(thread: *mut void, data_json: *const u8) -> *mut i8;
+    pub(crate) fn yamlstar_load_tagged(thread: *mut void, yaml: *const u8) -> *mut i8;
+    pub(crate) fn yamlstar_load_all_tagged(thread: *mut void, yaml: *const u8) -> *mut i8;
+    pub(crate) fn yamlstar_dump_tagged(thread: *mut void, data_json: *const u8) -> *mut i8;
+    pub(crate) fn yamlstar_dump_all_tagged(thread: *mut void, data_json: *const u8) -> *mut i8;
+    pub(crate) fn yamlstar_load_with_options(
+        thread: *mut void,
+        yaml: *const u8,
+        options_json: *const u8,
+    ) -> *mut i8;
+    pub(crate) fn yamlstar_load_all_with_options(
+        thread: *mut void,
+        yaml: *const u8,
+        options_json: *const u8,
+    ) -> *mut i8;
+    pub(crate) fn yamlstar_dump_with_options(
+        thread: *mut void,
+        data_json: *const u8,
+        options_json: *const u8,
+    ) -> *mut i8;
+    pub(crate) fn yamlstar_dump_all_with_options(
+        thread: *mut void,
+        data_json: *const u8,
+        options_json: *const u8,
+    ) -> *mut i8;
+    pub(crate) fn yamlstar_version(thread: *mut void) -> *mut i8;
+    pub(crate) fn yamlstar_free(thread: *mut void, ptr: *mut i8);
+}
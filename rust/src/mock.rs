@@ -0,0 +1,338 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! A scriptable [`Backend`] for testing without libyamlstar.
+
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use serde::Serialize;
+
+use crate::{Backend, Capabilities, Error};
+
+/// An operation of a [`Backend`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MockOp {
+    /// [`Backend::load`].
+    Load,
+    /// [`Backend::load_all`].
+    LoadAll,
+    /// [`Backend::dump`].
+    Dump,
+    /// [`Backend::dump_all`].
+    DumpAll,
+    /// [`Backend::load_tagged`].
+    LoadTagged,
+    /// [`Backend::load_all_tagged`].
+    LoadAllTagged,
+    /// [`Backend::dump_tagged`].
+    DumpTagged,
+    /// [`Backend::dump_all_tagged`].
+    DumpAllTagged,
+}
+
+impl MockOp {
+    /// The name of the operation, as [`Error::Unsupported`] gives it.
+    fn name(self) -> &'static str {
+        match self {
+            MockOp::Load => "load",
+            MockOp::LoadAll => "load_all",
+            MockOp::Dump => "dump",
+            MockOp::DumpAll => "dump_all",
+            MockOp::LoadTagged => "load_tagged",
+            MockOp::LoadAllTagged => "load_all_tagged",
+            MockOp::DumpTagged => "dump_tagged",
+            MockOp::DumpAllTagged => "dump_all_tagged",
+        }
+    }
+
+    /// The tagged counterpart of a plain operation.
+    fn tagged(self) -> Self {
+        match self {
+            MockOp::Load => MockOp::LoadTagged,
+            MockOp::LoadAll => MockOp::LoadAllTagged,
+            MockOp::Dump => MockOp::DumpTagged,
+            MockOp::DumpAll => MockOp::DumpAllTagged,
+            op => op,
+        }
+    }
+}
+
+/// A [`Backend`] that returns canned responses.
+///
+/// Responses are queued per operation and returned in order; calling an
+/// operation with nothing queued panics. Every call is recorded and can be
+/// inspected with [`MockBackend::calls`].
+///
+/// Like a library without the tagged exports and those with options, a
+/// mock reports [`Capabilities::PLAIN`] unless given other capabilities, and
+/// [`crate::YAMLStar`] speaks plain JSON to it. With
+/// [`Capabilities::tagged`], the tagged operations answer from their own
+/// queues, such as [`MockOp::LoadTagged`], in the tagged encoding. The
+/// operations with options answer from the queue of the encoding their
+/// options ask for, so a [`Backend::load_with_options`] call with
+/// `"tagged": true` takes a [`MockOp::LoadTagged`] response. The options of
+/// each call can be inspected with [`MockBackend::options`].
+///
+/// ```
+/// use yamlstar::{MockBackend, MockOp, YAMLStar};
+///
+/// let mock = MockBackend::new()
+///     .respond_data(MockOp::Load, &42)
+///     .respond_error(MockOp::Load, "bad indentation", "ParseError");
+/// let ys = YAMLStar::with_backend(mock);
+///
+/// assert_eq!(ys.load::<i64>("42").unwrap(), 42);
+/// match ys.load::<i64>(" - x").unwrap_err() {
+///     yamlstar::Error::YAMLStar(err) => assert_eq!(err.cause, "bad indentation"),
+///     err => panic!("unexpected error: {err}"),
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    /// Queued responses and recorded calls.
+    state: Mutex<MockState>,
+    /// The version string to report.
+    version: Option<String>,
+    /// The capabilities to report.
+    capabilities: Option<Capabilities>,
+}
+
+/// The mutable part of a [`MockBackend`].
+#[derive(Debug, Default)]
+struct MockState {
+    /// Queued `load` responses.
+    load: VecDeque<String>,
+    /// Queued `load_all` responses.
+    load_all: VecDeque<String>,
+    /// Queued `dump` responses.
+    dump: VecDeque<String>,
+    /// Queued `dump_all` responses.
+    dump_all: VecDeque<String>,
+    /// Queued `load_tagged` responses.
+    load_tagged: VecDeque<String>,
+    /// Queued `load_all_tagged` responses.
+    load_all_tagged: VecDeque<String>,
+    /// Queued `dump_tagged` responses.
+    dump_tagged: VecDeque<String>,
+    /// Queued `dump_all_tagged` responses.
+    dump_all_tagged: VecDeque<String>,
+    /// Every call made, with its input.
+    calls: Vec<(MockOp, String)>,
+    /// The options JSON of every call made, if it had any.
+    options: Vec<Option<String>>,
+}
+
+impl MockState {
+    /// The response queue for `op`.
+    fn queue(&mut self, op: MockOp) -> &mut VecDeque<String> {
+        match op {
+            MockOp::Load => &mut self.load,
+            MockOp::LoadAll => &mut self.load_all,
+            MockOp::Dump => &mut self.dump,
+            MockOp::DumpAll => &mut self.dump_all,
+            MockOp::LoadTagged => &mut self.load_tagged,
+            MockOp::LoadAllTagged => &mut self.load_all_tagged,
+            MockOp::DumpTagged => &mut self.dump_tagged,
+            MockOp::DumpAllTagged => &mut self.dump_all_tagged,
+        }
+    }
+}
+
+impl MockBackend {
+    /// Create a mock with no queued responses.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a raw response envelope for `op`.
+    #[must_use]
+    pub fn respond(self, op: MockOp, envelope: impl Into<String>) -> Self {
+        self.lock().queue(op).push_back(envelope.into());
+        self
+    }
+
+    /// Queue a successful response carrying `data` for `op`.
+    ///
+    /// # Panics
+    /// Panics if `data` cannot be serialized to JSON.
+    #[must_use]
+    pub fn respond_data<T: Serialize + ?Sized>(self, op: MockOp, data: &T) -> Self {
+        let envelope = serde_json::json!({ "data": data });
+        self.respond(op, envelope.to_string())
+    }
+
+    /// Queue an error response for `op`, as libyamlstar reports it.
+    #[must_use]
+    pub fn respond_error(self, op: MockOp, cause: &str, type_: &str) -> Self {
+        let envelope = serde_json::json!({ "error": { "cause": cause, "type": type_ } });
+        self.respond(op, envelope.to_string())
+    }
+
+    /// Set the version string to report. Without it,
+    /// [`crate::LIBYAMLSTAR_VERSION`] is reported.
+    #[must_use]
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Set the capabilities to report. Operations marked unavailable return
+    /// [`Error::Unsupported`].
+    #[must_use]
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// List every call made so far, with its input.
+    #[must_use]
+    pub fn calls(&self) -> Vec<(MockOp, String)> {
+        self.lock().calls.clone()
+    }
+
+    /// List the options JSON of every call made so far, in the order of
+    /// [`MockBackend::calls`], or `None` for a call without options.
+    #[must_use]
+    pub fn options(&self) -> Vec<Option<String>> {
+        self.lock().options.clone()
+    }
+
+    /// Lock the mutable state, ignoring poisoning.
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record a call and return the next queued response for it.
+    fn next(&self, op: MockOp, input: &str) -> Result<String, Error> {
+        self.next_with(op, input, None)
+    }
+
+    /// Record a call with options and return the next queued response for
+    /// it, from the queue of the tagged counterpart of `op` if the options
+    /// have `"tagged": true`.
+    fn next_with_options(&self, op: MockOp, input: &str, options: &str) -> Result<String, Error> {
+        if !Backend::capabilities(self).options {
+            return Err(Error::Unsupported(match op {
+                MockOp::Load => "load_with_options",
+                MockOp::LoadAll => "load_all_with_options",
+                MockOp::Dump => "dump_with_options",
+                _ => "dump_all_with_options",
+            }));
+        }
+        let tagged = serde_json::from_str::<serde_json::Value>(options)
+            .is_ok_and(|options| options["tagged"] == true);
+        let op = if tagged { op.tagged() } else { op };
+        self.next_with(op, input, Some(options))
+    }
+
+    /// Record a call, with its options if any, and return the next queued
+    /// response for it.
+    fn next_with(&self, op: MockOp, input: &str, options: Option<&str>) -> Result<String, Error> {
+        let caps = Backend::capabilities(self);
+        let supported = match op {
+            MockOp::Load => caps.load,
+            MockOp::LoadAll => caps.load_all,
+            MockOp::Dump => caps.dump,
+            MockOp::DumpAll => caps.dump_all,
+            MockOp::LoadTagged
+            | MockOp::LoadAllTagged
+            | MockOp::DumpTagged
+            | MockOp::DumpAllTagged => caps.tagged,
+        };
+        let name = op.name();
+        if !supported {
+            return Err(Error::Unsupported(name));
+        }
+
+        let mut state = self.lock();
+        state.calls.push((op, input.to_string()));
+        state.options.push(options.map(str::to_string));
+        let response = state.queue(op).pop_front();
+        drop(state);
+        Ok(response.unwrap_or_else(|| panic!("MockBackend: no response queued for {name}")))
+    }
+}
+
+impl Backend for MockBackend {
+    /// # Panics
+    /// Panics if no response is queued for [`MockOp::Load`].
+    fn load(&self, yaml: &str) -> Result<String, Error> {
+        self.next(MockOp::Load, yaml)
+    }
+
+    /// # Panics
+    /// Panics if no response is queued for [`MockOp::LoadAll`].
+    fn load_all(&self, yaml: &str) -> Result<String, Error> {
+        self.next(MockOp::LoadAll, yaml)
+    }
+
+    /// # Panics
+    /// Panics if no response is queued for [`MockOp::Dump`].
+    fn dump(&self, data_json: &str) -> Result<String, Error> {
+        self.next(MockOp::Dump, data_json)
+    }
+
+    /// # Panics
+    /// Panics if no response is queued for [`MockOp::DumpAll`].
+    fn dump_all(&self, data_json: &str) -> Result<String, Error> {
+        self.next(MockOp::DumpAll, data_json)
+    }
+
+    /// # Panics
+    /// Panics if no response is queued for [`MockOp::LoadTagged`].
+    fn load_tagged(&self, yaml: &str) -> Result<String, Error> {
+        self.next(MockOp::LoadTagged, yaml)
+    }
+
+    /// # Panics
+    /// Panics if no response is queued for [`MockOp::LoadAllTagged`].
+    fn load_all_tagged(&self, yaml: &str) -> Result<String, Error> {
+        self.next(MockOp::LoadAllTagged, yaml)
+    }
+
+    /// # Panics
+    /// Panics if no response is queued for [`MockOp::DumpTagged`].
+    fn dump_tagged(&self, data_json: &str) -> Result<String, Error> {
+        self.next(MockOp::DumpTagged, data_json)
+    }
+
+    /// # Panics
+    /// Panics if no response is queued for [`MockOp::DumpAllTagged`].
+    fn dump_all_tagged(&self, data_json: &str) -> Result<String, Error> {
+        self.next(MockOp::DumpAllTagged, data_json)
+    }
+
+    fn load_with_options(&self, yaml: &str, options_json: &str) -> Result<String, Error> {
+        self.next_with_options(MockOp::Load, yaml, options_json)
+    }
+
+    fn load_all_with_options(&self, yaml: &str, options_json: &str) -> Result<String, Error> {
+        self.next_with_options(MockOp::LoadAll, yaml, options_json)
+    }
+
+    fn dump_with_options(&self, data_json: &str, options_json: &str) -> Result<String, Error> {
+        self.next_with_options(MockOp::Dump, data_json, options_json)
+    }
+
+    fn dump_all_with_options(&self, data_json: &str, options_json: &str) -> Result<String, Error> {
+        self.next_with_options(MockOp::DumpAll, data_json, options_json)
+    }
+
+    fn version(&self) -> Result<String, Error> {
+        if !Backend::capabilities(self).version {
+            return Err(Error::Unsupported("version"));
+        }
+        Ok(self
+            .version
+            .clone()
+            .unwrap_or_else(|| crate::LIBYAMLSTAR_VERSION.to_string()))
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities.unwrap_or(Capabilities::PLAIN)
+    }
+}
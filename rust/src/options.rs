@@ -0,0 +1,429 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Load and dump options that libyamlstar applies.
+
+use std::{fmt, path::PathBuf, sync::Arc};
+
+use serde_json::json;
+
+use crate::{DuplicateKeys, Value};
+
+/// How plain (unquoted, untagged) scalars are typed when loading, and so
+/// which strings need quotes when dumping.
+///
+/// ```no_run
+/// use yamlstar::{Schema, YAMLStar};
+///
+/// let ys = YAMLStar::builder().schema(Schema::Yaml11).build().unwrap();
+/// assert_eq!(ys.load::<bool>("yes").unwrap(), true);
+/// assert_eq!(ys.load::<i64>("0755").unwrap(), 0o755);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Schema {
+    /// Every plain scalar is a string.
+    Failsafe,
+    /// Only JSON's `null`, `true`, `false` and numbers are plain scalars.
+    /// Any other plain scalar is an error, so strings must be quoted.
+    Json,
+    /// The YAML 1.2 core schema.
+    #[default]
+    Core,
+    /// The YAML 1.1 types, for files written by older tools: `yes`, `no`,
+    /// `on` and `off` are booleans, `0755` is octal, `1:30:00` is base 60,
+    /// numbers may have `_` separators, and `<<` merges mappings. `y` and
+    /// `n` stay strings, as in most YAML 1.1 loaders.
+    Yaml11,
+}
+
+impl Schema {
+    /// The name libyamlstar knows the schema by.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Schema::Failsafe => "failsafe",
+            Schema::Json => "json",
+            Schema::Core => "core",
+            Schema::Yaml11 => "yaml11",
+        }
+    }
+
+    /// Type `text` as a plain scalar of this schema would be. Text that is
+    /// not a JSON schema value stays a string rather than an error, as if it
+    /// had been quoted.
+    pub(crate) fn plain_scalar(self, text: &str) -> Value {
+        match self {
+            Schema::Failsafe => Value::String(text.to_string()),
+            Schema::Json => json_scalar(text),
+            Schema::Core => core_scalar(text),
+            Schema::Yaml11 => yaml11_scalar(text),
+        }
+    }
+}
+
+/// A user-defined implicit resolver: it gives its tag to the plain scalars
+/// it matches, before the schema types them.
+///
+/// Resolvers are added with [`crate::Builder::resolver`] and belong to the
+/// instance built. A scalar given a tag loads as a [`crate::Value::Tagged`]
+/// holding its text.
+///
+/// ```no_run
+/// use yamlstar::{Resolver, Value, YAMLStar};
+///
+/// let ys = YAMLStar::builder()
+///     .resolver(Resolver::pattern("!duration", r"\d+(ms|s|m|h)"))
+///     .build()
+///     .unwrap();
+/// let value: Value = ys.load("timeout: 10s").unwrap();
+/// assert_eq!(value["timeout"].tag(), Some("!duration"));
+/// ```
+#[derive(Clone)]
+pub struct Resolver {
+    /// The tag given to matching scalars.
+    tag: String,
+    /// What the scalars must match.
+    rule: Rule,
+}
+
+/// How a [`Resolver`] matches a scalar.
+#[derive(Clone)]
+enum Rule {
+    /// A regular expression libyamlstar matches against the whole scalar.
+    Pattern(String),
+    /// A function of the scalar's text.
+    Predicate(Arc<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+impl Resolver {
+    /// A resolver for the plain scalars that `pattern` matches in full.
+    ///
+    /// libyamlstar applies the pattern, so it is in the regular expression
+    /// syntax of the platform it was built for, Java or Go. Classes,
+    /// repetition, alternation and groups work the same in both.
+    #[must_use]
+    pub fn pattern(tag: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            tag: tag.into(),
+            rule: Rule::Pattern(pattern.into()),
+        }
+    }
+
+    /// A resolver for the plain scalars `predicate` returns true for.
+    ///
+    /// Predicates run in Rust after libyamlstar has applied the pattern
+    /// resolvers, and only see the scalars none of them matched. A string
+    /// a predicate matches is dumped quoted so that it loads back as a
+    /// string.
+    #[must_use]
+    pub fn predicate(
+        tag: impl Into<String>,
+        predicate: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            tag: tag.into(),
+            rule: Rule::Predicate(Arc::new(predicate)),
+        }
+    }
+
+    /// The tag given to matching scalars.
+    #[must_use]
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+}
+
+impl fmt::Debug for Resolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Resolver");
+        debug.field("tag", &self.tag);
+        match &self.rule {
+            Rule::Pattern(pattern) => debug.field("pattern", pattern),
+            Rule::Predicate(_) => debug.field("predicate", &format_args!("..")),
+        };
+        debug.finish()
+    }
+}
+
+/// The options of a [`crate::YAMLStar`] instance that libyamlstar applies.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Options {
+    /// How plain scalars are typed.
+    pub(crate) schema: Schema,
+    /// User resolvers, tried in order before the schema.
+    pub(crate) resolvers: Vec<Resolver>,
+    /// Whether a plain `<<` key merges mappings in any schema.
+    pub(crate) merge: bool,
+    /// What to do with duplicate mapping keys. Applied in Rust, to the
+    /// pairs of the tagged encoding.
+    pub(crate) duplicate_keys: DuplicateKeys,
+    /// The canonical directory `!include` files must be in, if includes are
+    /// followed.
+    pub(crate) include_root: Option<PathBuf>,
+    /// The canonical path of the file being loaded, which includes are
+    /// relative to.
+    pub(crate) file: Option<PathBuf>,
+}
+
+impl Options {
+    /// Whether every option libyamlstar applies has its default, so that
+    /// libraries without the `*_with_options` operations can be used.
+    pub(crate) fn is_default(&self) -> bool {
+        self.schema == Schema::Core
+            && self.resolvers.is_empty()
+            && !self.merge
+            && self.include_root.is_none()
+    }
+
+    /// The options as the JSON object the `*_with_options` operations take.
+    ///
+    /// Predicate resolvers ask for `plain`, which marks every scalar the
+    /// schema typed as `{"!plain": [text, value]}` for [`Options::resolve`].
+    pub(crate) fn to_json(&self, tagged: bool) -> String {
+        let mut options = json!({
+            "tagged": tagged,
+            "schema": self.schema.as_str(),
+        });
+        let patterns = self
+            .resolvers
+            .iter()
+            .filter_map(|resolver| match &resolver.rule {
+                Rule::Pattern(pattern) => Some(json!({"tag": resolver.tag, "pattern": pattern})),
+                Rule::Predicate(_) => None,
+            })
+            .collect::<Vec<_>>();
+        if !patterns.is_empty() {
+            options["resolvers"] = patterns.into();
+        }
+        if self.has_predicates() {
+            options["plain"] = true.into();
+        }
+        if self.merge {
+            options["merge"] = true.into();
+        }
+        if let Some(root) = &self.include_root {
+            options["include"] = json!({ "root": root.to_string_lossy() });
+            if let Some(file) = &self.file {
+                options["include"]["file"] = file.to_string_lossy().into();
+            }
+        }
+        options.to_string()
+    }
+
+    /// Whether any resolver is a predicate, run in Rust.
+    pub(crate) fn has_predicates(&self) -> bool {
+        self.resolvers
+            .iter()
+            .any(|resolver| matches!(resolver.rule, Rule::Predicate(_)))
+    }
+
+    /// The tag of the first predicate resolver that matches `text`.
+    pub(crate) fn resolve(&self, text: &str) -> Option<&str> {
+        self.resolvers.iter().find_map(|resolver| match &resolver.rule {
+            Rule::Predicate(predicate) if predicate(text) => Some(resolver.tag.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// Type `text` as a plain scalar of the JSON schema: `null`, `true`,
+/// `false`, an integer that fits in an `i64`, a float, or else a string.
+fn json_scalar(text: &str) -> Value {
+    match text {
+        "null" => return Value::Null,
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        _ => {}
+    }
+    let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (unsigned, None),
+    };
+    let (int, fraction) = match mantissa.split_once('.') {
+        Some((int, fraction)) => (int, Some(fraction)),
+        None => (mantissa, None),
+    };
+    let valid = digits(int)
+        && (int == "0" || !int.starts_with('0'))
+        && fraction.is_none_or(|fraction| fraction.is_empty() || digits(fraction))
+        && exponent.is_none_or(|exponent| {
+            digits(exponent.strip_prefix(['-', '+']).unwrap_or(exponent))
+        });
+    if !valid {
+        return Value::String(text.to_string());
+    }
+    if fraction.is_none() && exponent.is_none() {
+        if let Ok(n) = text.parse::<i64>() {
+            return Value::Int(n);
+        }
+    }
+    text.parse::<f64>()
+        .map_or_else(|_| Value::String(text.to_string()), Value::Float)
+}
+
+/// Type `text` as a plain scalar of the YAML 1.2 core schema: null, a
+/// boolean, an integer that fits in an `i64`, a float, or else a string.
+fn core_scalar(text: &str) -> Value {
+    match text {
+        "true" | "True" | "TRUE" => return Value::Bool(true),
+        "false" | "False" | "FALSE" => return Value::Bool(false),
+        _ => {}
+    }
+    if let Some(value) = null_or_special_float(text) {
+        return value;
+    }
+    let radix = |prefix: &str, radix: u32| {
+        text.strip_prefix(prefix)
+            .filter(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_digit(radix)))
+            .and_then(|digits| i64::from_str_radix(digits, radix).ok())
+    };
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(text);
+    if !unsigned.is_empty() && unsigned.bytes().all(|b| b.is_ascii_digit()) {
+        if let Ok(n) = text.parse::<i64>() {
+            return Value::Int(n);
+        }
+    } else if let Some(n) = radix("0o", 8).or_else(|| radix("0x", 16)) {
+        return Value::Int(n);
+    }
+    let is_float = unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && unsigned
+            .bytes()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'-' | b'+'));
+    match text.parse::<f64>() {
+        Ok(f) if is_float => Value::Float(f),
+        _ => Value::String(text.to_string()),
+    }
+}
+
+/// Type `text` as a plain scalar of the YAML 1.1 types: the core schema's
+/// null, `yes`, `no`, `on` and `off` booleans, binary, octal, hex and base
+/// 60 integers and floats with `_` separators, or else a string.
+fn yaml11_scalar(text: &str) -> Value {
+    match text {
+        "yes" | "Yes" | "YES" | "on" | "On" | "ON" | "true" | "True" | "TRUE" => {
+            return Value::Bool(true)
+        }
+        "no" | "No" | "NO" | "off" | "Off" | "OFF" | "false" | "False" | "FALSE" => {
+            return Value::Bool(false)
+        }
+        _ => {}
+    }
+    if let Some(value) = null_or_special_float(text) {
+        return value;
+    }
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let signed = |n: i64| if negative { n.checked_neg() } else { Some(n) };
+    if let Some(n) = yaml11_int(unsigned).and_then(signed) {
+        return Value::Int(n);
+    }
+    match yaml11_float(unsigned) {
+        Some(f) => Value::Float(if negative { -f } else { f }),
+        None => Value::String(text.to_string()),
+    }
+}
+
+/// The value of an unsigned YAML 1.1 integer, if `text` is one that fits in
+/// an `i64`.
+fn yaml11_int(text: &str) -> Option<i64> {
+    let radix = |digits: &str, radix: u32| {
+        if digits.is_empty() || !digits.chars().all(|c| c == '_' || c.is_digit(radix)) {
+            return None;
+        }
+        let digits = digits.replace('_', "");
+        if digits.is_empty() {
+            Some(0)
+        } else {
+            i64::from_str_radix(&digits, radix).ok()
+        }
+    };
+    if let Some(digits) = text.strip_prefix("0b") {
+        radix(digits, 2)
+    } else if let Some(digits) = text.strip_prefix("0x") {
+        radix(digits, 16)
+    } else if text == "0" {
+        Some(0)
+    } else if let Some(digits) = text.strip_prefix('0') {
+        radix(digits, 8)
+    } else if text.starts_with(|c: char| c.is_ascii_digit()) {
+        match text.split_once(':') {
+            Some((first, rest)) => sexagesimal(first, rest),
+            None => radix(text, 10),
+        }
+    } else {
+        None
+    }
+}
+
+/// The value of an unsigned YAML 1.1 float, if `text` is one.
+fn yaml11_float(text: &str) -> Option<f64> {
+    let (whole, fraction) = text.split_once('.')?;
+    let (fraction, exponent) = match fraction.find(['e', 'E']) {
+        Some(at) => (&fraction[..at], &fraction[at + 1..]),
+        None => (fraction, ""),
+    };
+    let fraction_ok = fraction.chars().all(|c| c == '_' || c.is_ascii_digit());
+    let exponent_digits = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+    let exponent_ok = exponent_digits.bytes().all(|b| b.is_ascii_digit())
+        && (exponent.is_empty() || !exponent_digits.is_empty());
+    if !fraction_ok || !exponent_ok {
+        return None;
+    }
+    let fraction = fraction.replace('_', "");
+    if let Some((first, rest)) = whole.split_once(':') {
+        if !exponent.is_empty() {
+            return None;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let whole = sexagesimal(first, rest)? as f64;
+        return Some(whole + format!("0.{fraction}0").parse::<f64>().ok()?);
+    }
+    let whole_ok = if whole.is_empty() {
+        !fraction.is_empty()
+    } else {
+        whole.starts_with(|c: char| c.is_ascii_digit())
+            && whole.chars().all(|c| c == '_' || c.is_ascii_digit())
+    };
+    if !whole_ok {
+        return None;
+    }
+    let mut number = format!("0{}.{fraction}0", whole.replace('_', ""));
+    if !exponent.is_empty() {
+        number.push('e');
+        number.push_str(exponent);
+    }
+    number.parse().ok()
+}
+
+/// The value of the base 60 number `first:rest`, such as `1:30:00`.
+fn sexagesimal(first: &str, rest: &str) -> Option<i64> {
+    if !first.starts_with(|c: char| c.is_ascii_digit())
+        || !first.chars().all(|c| c == '_' || c.is_ascii_digit())
+    {
+        return None;
+    }
+    let mut total = first.replace('_', "").parse::<i64>().ok()?;
+    for part in rest.split(':') {
+        let digit = match part.as_bytes() {
+            [b'0'..=b'9'] | [b'0'..=b'5', b'0'..=b'9'] => part.parse::<i64>().ok()?,
+            _ => return None,
+        };
+        total = total.checked_mul(60)?.checked_add(digit)?;
+    }
+    Some(total)
+}
+
+/// The null, infinity or NaN that the core and YAML 1.1 schemas share.
+fn null_or_special_float(text: &str) -> Option<Value> {
+    Some(match text {
+        "" | "~" | "null" | "Null" | "NULL" => Value::Null,
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => Value::Float(f64::INFINITY),
+        "-.inf" | "-.Inf" | "-.INF" => Value::Float(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => Value::Float(f64::NAN),
+        _ => return None,
+    })
+}
@@ -0,0 +1,104 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Constructors for custom tags, registered with [`crate::YAMLStar::register_tag`].
+//!
+//! libyamlstar leaves a node with a tag it does not know as a placeholder in
+//! the tagged encoding, `{"!tag": [tag, value]}`. Once a response is decoded,
+//! every [`Value::Tagged`] whose tag has a constructor is replaced by what
+//! the constructor returns, innermost nodes first.
+
+use std::{collections::HashMap, error::Error as StdError, sync::Arc};
+
+use crate::{
+    value::{key_token, push_pointer_token},
+    Error, TaggedValue, Value,
+};
+
+/// The error a tag constructor returns.
+pub(crate) type TagError = Box<dyn StdError + Send + Sync>;
+
+/// A tag constructor.
+type Constructor = Arc<dyn Fn(TaggedValue) -> Result<Value, TagError> + Send + Sync>;
+
+/// The tag constructors of a [`crate::YAMLStar`] instance.
+#[derive(Clone, Default)]
+pub(crate) struct Tags {
+    /// The constructors by tag, with `!!` tags in full.
+    constructors: HashMap<String, Constructor>,
+}
+
+impl Tags {
+    /// Use `constructor` for nodes tagged `tag`, replacing any constructor
+    /// it had.
+    pub(crate) fn insert(&mut self, tag: &str, constructor: Constructor) {
+        let tag = match tag.strip_prefix("!!") {
+            Some(suffix) => format!("tag:yaml.org,2002:{suffix}"),
+            None => tag.to_string(),
+        };
+        self.constructors.insert(tag, constructor);
+    }
+
+    /// Apply the constructors to the document numbered `document`.
+    pub(crate) fn construct(&self, value: Value, document: usize) -> Result<Value, Error> {
+        if self.constructors.is_empty() {
+            return Ok(value);
+        }
+        self.construct_at(value, &mut String::new(), document)
+    }
+
+    /// Apply the constructors to the node at `path` and its children.
+    fn construct_at(&self, value: Value, path: &mut String, document: usize) -> Result<Value, Error> {
+        Ok(match value {
+            Value::Sequence(seq) => Value::Sequence(
+                seq.into_iter()
+                    .enumerate()
+                    .map(|(i, item)| self.construct_child(item, path, &i.to_string(), document))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Mapping(map) => Value::Mapping(
+                map.into_iter()
+                    .map(|(k, v)| {
+                        let token = key_token(&k);
+                        let key = self.construct_child(k, path, &token, document)?;
+                        let value = self.construct_child(v, path, &token, document)?;
+                        Ok((key, value))
+                    })
+                    .collect::<Result<_, Error>>()?,
+            ),
+            Value::Tagged(tagged) => {
+                let TaggedValue { tag, value } = *tagged;
+                let value = self.construct_at(value, path, document)?;
+                match self.constructors.get(&tag) {
+                    Some(constructor) => {
+                        let node = TaggedValue::new(tag.clone(), value);
+                        constructor(node).map_err(|source| Error::Tag {
+                            tag,
+                            path: path.clone(),
+                            document,
+                            source,
+                        })?
+                    }
+                    None => TaggedValue { tag, value }.into(),
+                }
+            }
+            other => other,
+        })
+    }
+
+    /// Apply the constructors to the child of the node at `path` found by
+    /// the JSON Pointer reference `token`.
+    fn construct_child(
+        &self,
+        value: Value,
+        path: &mut String,
+        token: &str,
+        document: usize,
+    ) -> Result<Value, Error> {
+        let len = path.len();
+        push_pointer_token(path, token);
+        let result = self.construct_at(value, path, document);
+        path.truncate(len);
+        result
+    }
+}
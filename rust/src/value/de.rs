@@ -0,0 +1,550 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::fmt;
+
+use serde::{
+    de::{self, DeserializeOwned, IntoDeserializer, Visitor},
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+
+use super::{
+    number::{number_value, JSON_NUMBER_TOKEN},
+    tagged::TAGGED_TOKEN,
+    Mapping, TaggedValue, Value,
+};
+use crate::{Error, Schema};
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// Builds a [`Value`] from any self-describing format.
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any YAML value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Int(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut access: A) -> Result<Value, A::Error> {
+        let mut seq = Vec::with_capacity(access.size_hint().unwrap_or(0));
+        while let Some(item) = access.next_element()? {
+            seq.push(item);
+        }
+        Ok(Value::Sequence(seq))
+    }
+
+    /// A `serde_json` arbitrary precision number is seen as a map.
+    fn visit_map<A: de::MapAccess<'de>>(self, mut access: A) -> Result<Value, A::Error> {
+        let mut map = Mapping::with_capacity(access.size_hint().unwrap_or(0));
+        let Some(first) = access.next_key::<Value>()? else {
+            return Ok(Value::Mapping(map));
+        };
+        if first == JSON_NUMBER_TOKEN {
+            return number_value(&access.next_value::<String>()?).map_err(de::Error::custom);
+        }
+        map.insert(first, access.next_value::<Value>()?);
+        while let Some((key, value)) = access.next_entry::<Value, Value>()? {
+            map.insert(key, value);
+        }
+        Ok(Value::Mapping(map))
+    }
+
+    /// A [`Value`] deserializer presents a tagged value as an enum variant.
+    fn visit_enum<A: de::EnumAccess<'de>>(self, access: A) -> Result<Value, A::Error> {
+        let (tag, variant) = access.variant::<String>()?;
+        let value = de::VariantAccess::newtype_variant::<Value>(variant)?;
+        Ok(TaggedValue { tag, value }.into())
+    }
+}
+
+/// Convert a [`Value`] to any deserializable type.
+///
+/// A tagged value is seen as an enum variant named after its tag, with the
+/// untagged value as its content; a Rust enum takes `!Circle` for its
+/// variant `Circle`. Scalar mapping keys can be read as strings, and string
+/// keys as numbers or booleans, as with JSON object keys. The text of a
+/// tagged scalar read as another scalar type is typed with the core schema.
+///
+/// # Errors
+/// Returns [`Error::Value`] if `value` does not fit `T`.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, Error> {
+    from_value_with_schema(value, Schema::Core)
+}
+
+/// [`from_value`], typing the text of tagged scalars with `schema`, as
+/// [`crate::YAMLStar`] loads do with the schema of the instance.
+pub(crate) fn from_value_with_schema<T: DeserializeOwned>(
+    value: Value,
+    schema: Schema,
+) -> Result<T, Error> {
+    T::deserialize(ValueDeserializer { value, schema })
+}
+
+impl IntoDeserializer<'_, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Deserialize a [`Value`] by way of a [`ValueDeserializer`] with the core
+/// schema.
+macro_rules! with_core_schema {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Error> {
+                ValueDeserializer {
+                    value: self,
+                    schema: Schema::Core,
+                }
+                .$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = Error;
+
+    with_core_schema! {
+        deserialize_any();
+        deserialize_option();
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier
+    }
+}
+
+/// Deserializes a [`Value`], typing the text of the tagged scalars in it
+/// with `schema`; see [`TaggedContent`].
+struct ValueDeserializer {
+    /// The value.
+    value: Value,
+    /// The schema of the instance that loaded it.
+    schema: Schema,
+}
+
+impl ValueDeserializer {
+    /// Deserialize another value with the same schema.
+    fn with(&self, value: Value) -> Self {
+        Self {
+            value,
+            schema: self.schema,
+        }
+    }
+}
+
+impl IntoDeserializer<'_, Error> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let schema = self.schema;
+        match self.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Int(n) => visitor.visit_i64(n),
+            Value::BigInt(n) => n.visit(visitor),
+            Value::Float(f) => visitor.visit_f64(f),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Sequence(seq) => {
+                let mut access = de::value::SeqDeserializer::new(
+                    seq.into_iter().map(|value| Self { value, schema }),
+                );
+                let value = visitor.visit_seq(&mut access)?;
+                access.end()?;
+                Ok(value)
+            }
+            Value::Mapping(map) => visit_mapping(map, schema, visitor),
+            Value::Tagged(tagged) => visit_tagged(*tagged, schema, visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    /// A [`super::Tagged`] sees a tagged value as an enum variant, and any
+    /// other value as a newtype struct.
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Tagged(tagged) if name == TAGGED_TOKEN => {
+                visit_tagged(*tagged, self.schema, visitor)
+            }
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    /// An enum is a string for a unit variant, a tagged value such as
+    /// `!Circle {r: 1.0}`, or a single-entry mapping such as
+    /// `{Circle: {r: 1.0}}`. A tag `!Name` names the variant `Name`, unless
+    /// the enum has a variant named `!Name`. The text of a tagged scalar is
+    /// typed for a variant that holds a number, so `!Square 2.0` is read.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let schema = self.schema;
+        match self.value {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Mapping(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().expect("the map has one entry");
+                let variant = match variant {
+                    Value::String(tag) => Value::String(variant_name(tag, variants)),
+                    other => other,
+                };
+                visitor.visit_enum(de::value::MapAccessDeserializer::new(
+                    de::value::MapDeserializer::new(std::iter::once((
+                        KeyDeserializer(Self {
+                            value: variant,
+                            schema,
+                        }),
+                        Self { value, schema },
+                    ))),
+                ))
+            }
+            Value::Tagged(tagged) => {
+                let TaggedValue { tag, value } = *tagged;
+                let tag = variant_name(tag, variants);
+                visit_tagged(TaggedValue { tag, value }, schema, visitor)
+            }
+            other => Err(de::Error::invalid_type(unexpected(&other), &"an enum")),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier
+    }
+}
+
+/// Visit the entries of a mapping, with lenient keys.
+fn visit_mapping<'de, V: Visitor<'de>>(
+    map: Mapping,
+    schema: Schema,
+    visitor: V,
+) -> Result<V::Value, Error> {
+    let mut access = de::value::MapDeserializer::new(map.into_iter().map(|(key, value)| {
+        (
+            KeyDeserializer(ValueDeserializer { value: key, schema }),
+            ValueDeserializer { value, schema },
+        )
+    }));
+    let value = visitor.visit_map(&mut access)?;
+    access.end()?;
+    Ok(value)
+}
+
+/// Visit a tagged value as an enum variant.
+fn visit_tagged<'de, V: Visitor<'de>>(
+    tagged: TaggedValue,
+    schema: Schema,
+    visitor: V,
+) -> Result<V::Value, Error> {
+    visitor.visit_enum(de::value::MapAccessDeserializer::new(
+        de::value::MapDeserializer::new(std::iter::once((
+            tagged.tag,
+            TaggedContent(ValueDeserializer {
+                value: tagged.value,
+                schema,
+            }),
+        ))),
+    ))
+}
+
+/// The variant a tag names: the tag itself if it is one of `variants`, or
+/// else the tag without its leading `!` if that is.
+fn variant_name(tag: String, variants: &[&str]) -> String {
+    match tag.strip_prefix('!') {
+        Some(name) if !variants.contains(&tag.as_str()) && variants.contains(&name) => {
+            name.to_string()
+        }
+        _ => tag,
+    }
+}
+
+/// Describe a value for a type error.
+fn unexpected(value: &Value) -> de::Unexpected<'_> {
+    match value {
+        Value::Null => de::Unexpected::Unit,
+        Value::Bool(b) => de::Unexpected::Bool(*b),
+        Value::Int(n) => de::Unexpected::Signed(*n),
+        Value::BigInt(_) => de::Unexpected::Other("integer"),
+        Value::Float(f) => de::Unexpected::Float(*f),
+        Value::String(s) => de::Unexpected::Str(s),
+        Value::Sequence(_) => de::Unexpected::Seq,
+        Value::Mapping(_) => de::Unexpected::Map,
+        Value::Tagged(_) => de::Unexpected::Enum,
+    }
+}
+
+/// Deserializes a mapping key, converting between scalars and strings as
+/// needed.
+struct KeyDeserializer(ValueDeserializer);
+
+impl IntoDeserializer<'_, Error> for KeyDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Read a string key as another scalar type if it parses, else pass the key
+/// on as it is.
+macro_rules! parse_key {
+    ($($method:ident => $ty:ty, $visit:ident;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                if let Value::String(s) = &self.0.value {
+                    if let Ok(parsed) = s.parse::<$ty>() {
+                        return visitor.$visit(parsed);
+                    }
+                }
+                self.0.deserialize_any(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for KeyDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0.deserialize_any(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0.value.scalar_text() {
+            Some(text) if !matches!(self.0.value, Value::Tagged(_)) => visitor.visit_string(text),
+            _ => self.0.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0.deserialize_option(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.0.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    parse_key! {
+        deserialize_bool => bool, visit_bool;
+        deserialize_i8 => i8, visit_i8;
+        deserialize_i16 => i16, visit_i16;
+        deserialize_i32 => i32, visit_i32;
+        deserialize_i64 => i64, visit_i64;
+        deserialize_u8 => u8, visit_u8;
+        deserialize_u16 => u16, visit_u16;
+        deserialize_u32 => u32, visit_u32;
+        deserialize_u64 => u64, visit_u64;
+        deserialize_i128 => i128, visit_i128;
+        deserialize_u128 => u128, visit_u128;
+        deserialize_f32 => f32, visit_f32;
+        deserialize_f64 => f64, visit_f64;
+    }
+
+    forward_to_deserialize_any! {
+        char bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        ignored_any
+    }
+}
+
+/// Deserializes the value of a tagged node. libyamlstar sends a scalar with
+/// a tag it does not know as its text, so `!internal 8080` is the string
+/// `"8080"`; read as a boolean, a number, a unit or an option, the text is
+/// typed as a plain scalar of the instance's schema first.
+struct TaggedContent(ValueDeserializer);
+
+impl IntoDeserializer<'_, Error> for TaggedContent {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl TaggedContent {
+    /// The value, with its text typed if it is a string.
+    fn typed(self) -> ValueDeserializer {
+        match &self.0.value {
+            Value::String(text) => self.0.with(self.0.schema.plain_scalar(text)),
+            _ => self.0,
+        }
+    }
+}
+
+/// Type the text of a tagged scalar before reading it as another scalar
+/// type.
+macro_rules! typed_scalar {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.typed().$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for TaggedContent {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.0.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match &self.0.value {
+            Value::String(text) if self.0.schema.plain_scalar(text) == Value::Null => {
+                visitor.visit_none()
+            }
+            Value::String(_) => visitor.visit_some(self),
+            _ => self.0.deserialize_option(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.0.value {
+            Value::String(_) => visitor.visit_newtype_struct(self),
+            _ => self.0.deserialize_newtype_struct(name, visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    typed_scalar! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64 deserialize_unit
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit_struct seq tuple tuple_struct map struct
+        identifier
+    }
+}
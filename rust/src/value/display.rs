@@ -0,0 +1,228 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Format a [`Value`] as block-style YAML.
+//!
+//! This is for showing values; it needs no library and so does not go through
+//! libyamlstar's emitter. Use [`crate::YAMLStar::dump`] to write documents.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use super::{Mapping, Value};
+
+impl Display for Value {
+    /// Write the value as a YAML document, without a trailing newline.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_block(f, self, 0)
+    }
+}
+
+/// Write a value on one line, in flow style. The `yaml` CLI reads this where
+/// it would read JSON.
+pub(crate) fn flow_text(value: &Value) -> String {
+    /// Displays a value in flow style.
+    struct Flow<'a>(&'a Value);
+
+    impl Display for Flow<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            write_flow(f, self.0)
+        }
+    }
+
+    Flow(value).to_string()
+}
+
+/// The YAML text of a float.
+pub(crate) fn float_text(f: f64) -> String {
+    if f.is_nan() {
+        ".nan".to_string()
+    } else if f.is_infinite() {
+        if f > 0.0 { ".inf" } else { "-.inf" }.to_string()
+    } else {
+        // Debug keeps the fraction of integral values, such as "1.0".
+        format!("{f:?}")
+    }
+}
+
+/// How a tag is written before a node.
+pub(crate) fn tag_text(tag: &str) -> String {
+    if tag.starts_with('!') {
+        tag.to_string()
+    } else if let Some(suffix) = tag.strip_prefix("tag:yaml.org,2002:") {
+        format!("!!{suffix}")
+    } else if tag.contains(':') {
+        format!("!<{tag}>")
+    } else {
+        format!("!{tag}")
+    }
+}
+
+/// Whether a value is written over several lines in block style.
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Sequence(seq) => !seq.is_empty(),
+        Value::Mapping(map) => !map.is_empty(),
+        Value::Tagged(tagged) => is_block(&tagged.value),
+        _ => false,
+    }
+}
+
+/// Write a node that starts at the cursor, with following lines indented by
+/// `indent`.
+fn write_block(f: &mut Formatter<'_>, value: &Value, indent: usize) -> fmt::Result {
+    match value {
+        Value::Sequence(seq) if !seq.is_empty() => {
+            for (i, item) in seq.iter().enumerate() {
+                if i > 0 {
+                    newline(f, indent)?;
+                }
+                f.write_str("- ")?;
+                write_block(f, item, indent + 2)?;
+            }
+            Ok(())
+        }
+        Value::Mapping(map) if !map.is_empty() => write_mapping(f, map, indent),
+        Value::Tagged(tagged) if is_block(&tagged.value) => {
+            f.write_str(&tag_text(&tagged.tag))?;
+            newline(f, indent)?;
+            write_block(f, &tagged.value, indent)
+        }
+        _ => write_flow(f, value),
+    }
+}
+
+/// Write the entries of a non-empty mapping.
+fn write_mapping(f: &mut Formatter<'_>, map: &Mapping, indent: usize) -> fmt::Result {
+    for (i, (key, value)) in map.iter().enumerate() {
+        if i > 0 {
+            newline(f, indent)?;
+        }
+        write_flow(f, key)?;
+        f.write_char(':')?;
+
+        let (tag, inner) = match value {
+            Value::Tagged(tagged) if is_block(&tagged.value) => {
+                (Some(tag_text(&tagged.tag)), &tagged.value)
+            }
+            value => (None, value),
+        };
+        if let Some(tag) = tag {
+            write!(f, " {tag}")?;
+        }
+        match inner {
+            // A sequence in a mapping may start at the key's indentation.
+            Value::Sequence(seq) if !seq.is_empty() => {
+                newline(f, indent)?;
+                write_block(f, inner, indent)?;
+            }
+            Value::Mapping(map) if !map.is_empty() => {
+                newline(f, indent + 2)?;
+                write_mapping(f, map, indent + 2)?;
+            }
+            _ => {
+                f.write_char(' ')?;
+                write_flow(f, inner)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write a value on one line, in flow style for collections.
+fn write_flow(f: &mut Formatter<'_>, value: &Value) -> fmt::Result {
+    match value {
+        Value::Null => f.write_str("null"),
+        Value::Bool(b) => write!(f, "{b}"),
+        Value::Int(n) => write!(f, "{n}"),
+        Value::BigInt(n) => write!(f, "{n}"),
+        Value::Float(x) => f.write_str(&float_text(*x)),
+        Value::String(s) => write_string(f, s),
+        Value::Sequence(seq) => {
+            f.write_char('[')?;
+            for (i, item) in seq.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write_flow(f, item)?;
+            }
+            f.write_char(']')
+        }
+        Value::Mapping(map) => {
+            f.write_char('{')?;
+            for (i, (key, value)) in map.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write_flow(f, key)?;
+                f.write_str(": ")?;
+                write_flow(f, value)?;
+            }
+            f.write_char('}')
+        }
+        Value::Tagged(tagged) => {
+            write!(f, "{} ", tag_text(&tagged.tag))?;
+            write_flow(f, &tagged.value)
+        }
+    }
+}
+
+/// Write a string plain if it would load back as the same string, else
+/// quoted.
+fn write_string(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+    if is_plain_safe(s) {
+        f.write_str(s)
+    } else if s.chars().all(|c| c == ' ' || !c.is_control()) {
+        write!(f, "'{}'", s.replace('\'', "''"))
+    } else {
+        f.write_char('"')?;
+        for c in s.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\t' => f.write_str("\\t")?,
+                '\r' => f.write_str("\\r")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", u32::from(c))?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+/// Whether a string can be written without quotes in any context.
+fn is_plain_safe(s: &str) -> bool {
+    !s.is_empty()
+        && !looks_implicit(s)
+        && !s.starts_with([' ', '-', '?', '!'])
+        && !s.ends_with(' ')
+        && !s.chars().any(|c| {
+            c.is_control() || "#[]{},&*?:|>'\"%@`".contains(c)
+        })
+}
+
+/// Whether a plain scalar would load as something other than a string.
+///
+/// This errs on the side of quoting: anything that starts like a number is
+/// treated as one.
+fn looks_implicit(s: &str) -> bool {
+    if matches!(
+        s,
+        "~" | "null" | "Null" | "NULL" | "true" | "True" | "TRUE" | "false" | "False" | "FALSE"
+    ) {
+        return true;
+    }
+    let unsigned = s.trim_start_matches(['+', '-']);
+    matches!(
+        unsigned,
+        ".inf" | ".Inf" | ".INF" | ".nan" | ".NaN" | ".NAN"
+    ) || unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && unsigned
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || "._+-xXoO".contains(c))
+}
+
+/// Start a new line indented by `indent` spaces.
+fn newline(f: &mut Formatter<'_>, indent: usize) -> fmt::Result {
+    write!(f, "\n{:indent$}", "")
+}
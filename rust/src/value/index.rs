@@ -0,0 +1,177 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use super::{Mapping, Value};
+
+/// A type that can index into a [`Value`]: a `usize` for a sequence item
+/// or an integer key, a string for a string key, or a [`Value`] for any key.
+///
+/// This trait is sealed.
+pub trait Index: private::Sealed {
+    /// Look up the item, or `None` if there is none.
+    #[doc(hidden)]
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value>;
+
+    /// Mutable version of [`Index::index_into`].
+    #[doc(hidden)]
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value>;
+
+    /// Look up the item, adding a mapping entry if it is missing.
+    #[doc(hidden)]
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl Sealed for super::Value {}
+    impl<T: Sealed + ?Sized> Sealed for &T {}
+}
+
+/// Find or add `key` in a mapping, turning `null` into an empty mapping.
+fn entry(value: &mut Value, key: Value) -> &mut Value {
+    if let Value::Null = value {
+        *value = Value::Mapping(Mapping::new());
+    }
+    match value.untagged_mut() {
+        Value::Mapping(map) => map.entry(key),
+        other => panic!("cannot index into a YAML {} with {key:?}", kind(other)),
+    }
+}
+
+/// Describe the kind of a value for a panic message.
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Int(_) | Value::BigInt(_) => "integer",
+        Value::Float(_) => "float",
+        Value::String(_) => "string",
+        Value::Sequence(_) => "sequence",
+        Value::Mapping(_) => "mapping",
+        Value::Tagged(_) => "tagged value",
+    }
+}
+
+impl Index for usize {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        match value.untagged() {
+            Value::Sequence(seq) => seq.get(*self),
+            Value::Mapping(map) => map.get(&Value::Int(i64::try_from(*self).ok()?)),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        match value.untagged_mut() {
+            Value::Sequence(seq) => seq.get_mut(*self),
+            Value::Mapping(map) => map.get_mut(&Value::Int(i64::try_from(*self).ok()?)),
+            _ => None,
+        }
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value {
+        if let Value::Sequence(seq) = value.untagged() {
+            let len = seq.len();
+            let Value::Sequence(seq) = value.untagged_mut() else {
+                unreachable!("the value is a sequence")
+            };
+            return seq.get_mut(*self).unwrap_or_else(|| {
+                panic!("cannot access index {self} of a YAML sequence of length {len}")
+            });
+        }
+        let key = i64::try_from(*self).expect("index fits in an i64");
+        entry(value, Value::Int(key))
+    }
+}
+
+impl Index for str {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        match value.untagged() {
+            Value::Mapping(map) => map.get(&Value::String(self.to_string())),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        match value.untagged_mut() {
+            Value::Mapping(map) => map.get_mut(&Value::String(self.to_string())),
+            _ => None,
+        }
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value {
+        entry(value, Value::String(self.to_string()))
+    }
+}
+
+impl Index for String {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_str().index_into_mut(value)
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value {
+        self.as_str().index_or_insert(value)
+    }
+}
+
+impl Index for Value {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        match value.untagged() {
+            Value::Mapping(map) => map.get(self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        match value.untagged_mut() {
+            Value::Mapping(map) => map.get_mut(self),
+            _ => None,
+        }
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value {
+        entry(value, self.clone())
+    }
+}
+
+impl<T: Index + ?Sized> Index for &T {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        (**self).index_into_mut(value)
+    }
+
+    fn index_or_insert<'v>(&self, value: &'v mut Value) -> &'v mut Value {
+        (**self).index_or_insert(value)
+    }
+}
+
+impl<I: Index> std::ops::Index<I> for Value {
+    type Output = Value;
+
+    /// Look up an item, or `null` if there is none.
+    fn index(&self, index: I) -> &Value {
+        static NULL: Value = Value::Null;
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+impl<I: Index> std::ops::IndexMut<I> for Value {
+    /// Look up an item, adding a `null` mapping entry if it is missing.
+    ///
+    /// # Panics
+    /// Panics if the value is neither a mapping nor `null`, or a sequence
+    /// index is out of bounds.
+    fn index_mut(&mut self, index: I) -> &mut Value {
+        index.index_or_insert(self)
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{BuildHasher, Hash, Hasher},
+};
+
+use super::Value;
+
+/// A YAML mapping: entries in document order, keyed by any [`Value`].
+///
+/// Lookups hash the key and compare it with `==`, so they take constant
+/// time on average. Removing an entry is linear in the number of entries.
+#[derive(Clone, Default)]
+pub struct Mapping {
+    /// The entries, in order.
+    entries: Vec<(Value, Value)>,
+    /// The positions in `entries` of the keys with each hash.
+    index: HashMap<u64, Vec<usize>>,
+}
+
+impl Mapping {
+    /// Create an empty mapping.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty mapping with room for `capacity` entries.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// The number of entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Look up the value of `key`.
+    #[must_use]
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        self.position(key).map(|i| &self.entries[i].1)
+    }
+
+    /// Mutable version of [`Mapping::get`].
+    pub fn get_mut(&mut self, key: &Value) -> Option<&mut Value> {
+        self.position(key).map(|i| &mut self.entries[i].1)
+    }
+
+    /// Whether the mapping has `key`.
+    #[must_use]
+    pub fn contains_key(&self, key: &Value) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Set the value of `key` and return the old one. A new key is added at
+    /// the end; an existing key keeps its place.
+    pub fn insert(&mut self, key: impl Into<Value>, value: impl Into<Value>) -> Option<Value> {
+        let key = key.into();
+        let value = value.into();
+        if let Some(i) = self.position(&key) {
+            return Some(std::mem::replace(&mut self.entries[i].1, value));
+        }
+        self.push(key, value);
+        None
+    }
+
+    /// Remove `key` and return its value, keeping the order of the others.
+    pub fn remove(&mut self, key: &Value) -> Option<Value> {
+        let i = self.position(key)?;
+        let hash = self.hash(key);
+        if let Some(positions) = self.index.get_mut(&hash) {
+            positions.retain(|&j| j != i);
+            if positions.is_empty() {
+                self.index.remove(&hash);
+            }
+        }
+        for j in self.index.values_mut().flatten() {
+            if *j > i {
+                *j -= 1;
+            }
+        }
+        Some(self.entries.remove(i).1)
+    }
+
+    /// Iterate over the entries in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, (Value, Value)> {
+        self.entries.iter()
+    }
+
+    /// Iterate over the entries in order, with mutable values.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Value, &mut Value)> {
+        self.entries.iter_mut().map(|(k, v)| (&*k, v))
+    }
+
+    /// Iterate over the keys in order.
+    pub fn keys(&self) -> impl Iterator<Item = &Value> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    /// Iterate over the values in order.
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    /// The value of `key`, added as `null` if it is missing.
+    pub(crate) fn entry(&mut self, key: Value) -> &mut Value {
+        let i = match self.position(&key) {
+            Some(i) => i,
+            None => self.push(key, Value::Null),
+        };
+        &mut self.entries[i].1
+    }
+
+    /// Add an entry for a key that is not in the mapping and return its
+    /// index.
+    fn push(&mut self, key: Value, value: Value) -> usize {
+        let i = self.entries.len();
+        let hash = self.hash(&key);
+        self.index.entry(hash).or_default().push(i);
+        self.entries.push((key, value));
+        i
+    }
+
+    /// The hash of `key` in the index.
+    fn hash(&self, key: &Value) -> u64 {
+        self.index.hasher().hash_one(Key(key))
+    }
+
+    /// The index of `key`.
+    pub(crate) fn position(&self, key: &Value) -> Option<usize> {
+        self.index
+            .get(&self.hash(key))?
+            .iter()
+            .copied()
+            .find(|&i| self.entries[i].0 == *key)
+    }
+
+    /// Look up a key by its text; see [`Value::pointer`].
+    pub(crate) fn get_by_text(&self, text: &str) -> Option<&Value> {
+        self.position_by_text(text).map(|i| &self.entries[i].1)
+    }
+
+    /// Mutable version of [`Mapping::get_by_text`].
+    pub(crate) fn get_by_text_mut(&mut self, text: &str) -> Option<&mut Value> {
+        self.position_by_text(text).map(|i| &mut self.entries[i].1)
+    }
+
+    /// The index of a string key equal to `text`, or else of a scalar key
+    /// written as `text`.
+    fn position_by_text(&self, text: &str) -> Option<usize> {
+        self.position(&Value::String(text.to_string())).or_else(|| {
+            self.entries
+                .iter()
+                .position(|(k, _)| k.scalar_text().as_deref() == Some(text))
+        })
+    }
+}
+
+/// A key borrowed for hashing, consistently with `==` on [`Value`].
+struct Key<'a>(&'a Value);
+
+impl Hash for Key<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self.0).hash(state);
+        match self.0 {
+            Value::Null => {}
+            Value::Bool(b) => b.hash(state),
+            Value::Int(n) => n.hash(state),
+            Value::BigInt(n) => n.hash(state),
+            // Adding zero turns -0.0, which equals 0.0, into 0.0. NaN equals
+            // nothing, so its bits do not matter.
+            Value::Float(f) => (f + 0.0).to_bits().hash(state),
+            Value::String(s) => s.hash(state),
+            Value::Sequence(items) => {
+                items.len().hash(state);
+                for item in items {
+                    Key(item).hash(state);
+                }
+            }
+            Value::Mapping(map) => {
+                map.len().hash(state);
+                for (k, v) in map {
+                    Key(k).hash(state);
+                    Key(v).hash(state);
+                }
+            }
+            Value::Tagged(tagged) => {
+                tagged.tag.hash(state);
+                Key(&tagged.value).hash(state);
+            }
+        }
+    }
+}
+
+impl PartialEq for Mapping {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl fmt::Debug for Mapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mapping")
+            .field("entries", &self.entries)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<K: Into<Value>, V: Into<Value>> FromIterator<(K, V)> for Mapping {
+    /// Collect entries; a repeated key keeps its first place and last value.
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Into<Value>, V: Into<Value>> Extend<(K, V)> for Mapping {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl IntoIterator for Mapping {
+    type Item = (Value, Value);
+    type IntoIter = std::vec::IntoIter<(Value, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Mapping {
+    type Item = &'a (Value, Value);
+    type IntoIter = std::slice::Iter<'a, (Value, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
@@ -0,0 +1,497 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! A native representation of any YAML value.
+//!
+//! Unlike `serde_json::Value`, a [`Value`] keeps everything YAML can express:
+//! mapping keys of any type, key order, 64-bit integers (or any integer in
+//! arbitrary precision mode), `.inf` and `.nan`, and tags the core schema does
+//! not know about.
+
+mod de;
+pub(crate) mod display;
+mod index;
+mod mapping;
+mod number;
+mod ser;
+mod tagged;
+pub(crate) mod wire;
+
+pub use de::from_value;
+pub(crate) use de::from_value_with_schema;
+pub use index::Index;
+pub use mapping::Mapping;
+pub use number::Number;
+pub use ser::to_value;
+pub(crate) use ser::to_value_with_tagged_enums;
+pub use tagged::{MaybeTagged, Tagged};
+
+use crate::Error;
+
+/// Any YAML value.
+///
+/// Load one with [`crate::YAMLStar::load`] and dump it with
+/// [`crate::YAMLStar::dump`]. With a library that supports it (see
+/// [`crate::Capabilities::tagged`]) a value survives the round trip
+/// unchanged, except that a scalar under a custom tag always loads as its
+/// text. Aliases are expanded as they load: each one becomes a copy of its
+/// anchored node, and a dump writes the copies out in full without anchors.
+///
+/// ```no_run
+/// use yamlstar::{Value, YAMLStar};
+///
+/// let ys = YAMLStar::new().unwrap();
+/// let value: Value = ys.load("1: one\nratio: .inf\nsecret: !vault abc").unwrap();
+/// assert_eq!(value[1], "one");
+/// assert_eq!(value["ratio"], f64::INFINITY);
+/// assert_eq!(value["secret"].tag(), Some("!vault"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Value {
+    /// `null`, `~` or an empty node.
+    #[default]
+    Null,
+    /// `true` or `false`.
+    Bool(bool),
+    /// An integer.
+    Int(i64),
+    /// An integer that does not fit in an `i64`, only loaded in arbitrary
+    /// precision mode.
+    BigInt(Number),
+    /// A floating-point number, including the non-finite ones.
+    Float(f64),
+    /// A string.
+    String(String),
+    /// A sequence.
+    Sequence(Vec<Value>),
+    /// A mapping, in document order.
+    Mapping(Mapping),
+    /// A value with a tag outside the core schema.
+    Tagged(Box<TaggedValue>),
+}
+
+/// A [`Value`] with a tag outside the core schema, such as `!vault abc`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaggedValue {
+    /// The tag as written, such as `"!vault"`, or in full for `!!` tags,
+    /// such as `"tag:yaml.org,2002:set"`.
+    pub tag: String,
+    /// The tagged value. A tagged scalar loads as its text.
+    pub value: Value,
+}
+
+impl Value {
+    /// Look up a mapping entry or sequence item, or `None` if there is none.
+    ///
+    /// Tagged values are looked through.
+    pub fn get<I: Index>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    /// Mutable version of [`Value::get`].
+    pub fn get_mut<I: Index>(&mut self, index: I) -> Option<&mut Value> {
+        index.index_into_mut(self)
+    }
+
+    /// Look up a value by a JSON Pointer (RFC 6901), such as `/servers/0/host`.
+    ///
+    /// A mapping key matches a reference token if it is a string equal to it,
+    /// or a scalar whose YAML text equals it, so `/1` finds the key `1`.
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        pointer_tokens(pointer)?
+            .into_iter()
+            .try_fold(self, |target, token| match target.untagged() {
+                Value::Mapping(map) => map.get_by_text(&token),
+                Value::Sequence(seq) => parse_seq_index(&token).and_then(|i| seq.get(i)),
+                _ => None,
+            })
+    }
+
+    /// Mutable version of [`Value::pointer`].
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        pointer_tokens(pointer)?
+            .into_iter()
+            .try_fold(self, |target, token| match target.untagged_mut() {
+                Value::Mapping(map) => map.get_by_text_mut(&token),
+                Value::Sequence(seq) => parse_seq_index(&token).and_then(|i| seq.get_mut(i)),
+                _ => None,
+            })
+    }
+
+    /// The tag of a [`Value::Tagged`], or `None` for any other value.
+    #[must_use]
+    pub fn tag(&self) -> Option<&str> {
+        match self {
+            Value::Tagged(tagged) => Some(&tagged.tag),
+            _ => None,
+        }
+    }
+
+    /// The value without its tag, if it has one.
+    #[must_use]
+    pub fn untagged(&self) -> &Value {
+        match self {
+            Value::Tagged(tagged) => tagged.value.untagged(),
+            value => value,
+        }
+    }
+
+    /// Mutable version of [`Value::untagged`].
+    pub fn untagged_mut(&mut self) -> &mut Value {
+        match self {
+            Value::Tagged(tagged) => tagged.value.untagged_mut(),
+            value => value,
+        }
+    }
+
+    /// Whether the value is [`Value::Null`].
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// The boolean, if the value is one.
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// The integer, if the value is one.
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// The integer as a [`Number`], if the value is one.
+    #[must_use]
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            Value::Int(n) => Some((*n).into()),
+            Value::BigInt(n) => Some(n.clone()),
+            _ => None,
+        }
+    }
+
+    /// The number as a float, if the value is an integer or a float.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(n) => Some(*n as f64),
+            Value::BigInt(n) => n.as_str().parse().ok(),
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// The string, if the value is one.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The items, if the value is a sequence.
+    #[must_use]
+    pub fn as_sequence(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Sequence(seq) => Some(seq),
+            _ => None,
+        }
+    }
+
+    /// Mutable version of [`Value::as_sequence`].
+    pub fn as_sequence_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::Sequence(seq) => Some(seq),
+            _ => None,
+        }
+    }
+
+    /// The entries, if the value is a mapping.
+    #[must_use]
+    pub fn as_mapping(&self) -> Option<&Mapping> {
+        match self {
+            Value::Mapping(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Mutable version of [`Value::as_mapping`].
+    pub fn as_mapping_mut(&mut self) -> Option<&mut Mapping> {
+        match self {
+            Value::Mapping(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// The YAML text of a scalar, or `None` for a collection.
+    ///
+    /// This is how a scalar key is matched by [`Value::pointer`] and read as
+    /// a string map key by [`from_value`].
+    pub(crate) fn scalar_text(&self) -> Option<String> {
+        match self {
+            Value::Null => Some("null".to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            Value::Int(n) => Some(n.to_string()),
+            Value::BigInt(n) => Some(n.to_string()),
+            Value::Float(f) => Some(display::float_text(*f)),
+            Value::String(s) => Some(s.clone()),
+            Value::Sequence(_) | Value::Mapping(_) => None,
+            Value::Tagged(tagged) => tagged.value.scalar_text(),
+        }
+    }
+}
+
+impl TaggedValue {
+    /// Create a tagged value.
+    pub fn new(tag: impl Into<String>, value: impl Into<Value>) -> Self {
+        Self {
+            tag: tag.into(),
+            value: value.into(),
+        }
+    }
+}
+
+/// Append the reference token for the mapping key or sequence index `token`
+/// to the JSON Pointer `pointer`.
+pub(crate) fn push_pointer_token(pointer: &mut String, token: &str) {
+    pointer.push('/');
+    pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
+
+/// The text of a mapping key as a JSON Pointer reference token, before
+/// escaping. A collection key is written in flow style.
+pub(crate) fn key_token(key: &Value) -> String {
+    key.scalar_text()
+        .unwrap_or_else(|| display::flow_text(key))
+}
+
+/// Split a JSON Pointer into unescaped reference tokens.
+fn pointer_tokens(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/')?;
+    Some(
+        rest.split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Parse a JSON Pointer array index, which may not have leading zeros.
+fn parse_seq_index(token: &str) -> Option<usize> {
+    if token.starts_with('+') || (token.starts_with('0') && token.len() > 1) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+macro_rules! from_integer {
+    ($($ty:ty)*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Value::Int(value.into())
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i8 i16 i32 i64 u8 u16 u32);
+
+macro_rules! from_wide_integer {
+    ($($ty:ty)*) => {
+        $(
+            impl From<$ty> for Value {
+                /// Convert to a [`Value::Int`], or a [`Value::BigInt`] if the
+                /// integer does not fit in an `i64`.
+                fn from(value: $ty) -> Self {
+                    Number::from(value).into()
+                }
+            }
+        )*
+    };
+}
+
+from_wide_integer!(i128 u64 u128);
+
+impl From<Number> for Value {
+    /// Convert to a [`Value::Int`], or a [`Value::BigInt`] if the integer
+    /// does not fit in an `i64`.
+    fn from(value: Number) -> Self {
+        match value.as_i64() {
+            Some(n) => Value::Int(n),
+            None => Value::BigInt(value),
+        }
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(value: Vec<T>) -> Self {
+        Value::Sequence(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<Mapping> for Value {
+    fn from(value: Mapping) -> Self {
+        Value::Mapping(value)
+    }
+}
+
+impl From<TaggedValue> for Value {
+    fn from(value: TaggedValue) -> Self {
+        Value::Tagged(Box::new(value))
+    }
+}
+
+impl<T: Into<Value>> FromIterator<T> for Value {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Value::Sequence(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Convert JSON to a [`Value`]. Integers that do not fit in an `i64` become
+/// a [`Value::BigInt`].
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Number(n) => match n.to_string().parse::<Number>() {
+                Ok(int) => int.into(),
+                Err(_) => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(s) => Value::String(s),
+            serde_json::Value::Array(seq) => seq.into_iter().collect(),
+            serde_json::Value::Object(map) => Value::Mapping(
+                map.into_iter()
+                    .map(|(k, v)| (Value::String(k), Value::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Convert a [`Value`] to JSON.
+///
+/// Tags are dropped and scalar mapping keys become their YAML text. Fails
+/// with [`Error::Value`] on a non-finite float, an integer `serde_json`
+/// cannot hold or a collection used as a mapping key.
+impl TryFrom<Value> for serde_json::Value {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        Ok(match value {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => serde_json::Value::Bool(b),
+            Value::Int(n) => n.into(),
+            Value::BigInt(n) => match serde_json::to_value(&n) {
+                Ok(json @ serde_json::Value::Number(_)) => json,
+                _ => return Err(Error::Value(format!("{n} cannot be represented in JSON"))),
+            },
+            Value::Float(f) => serde_json::Number::from_f64(f)
+                .map(serde_json::Value::Number)
+                .ok_or_else(|| Error::Value(format!("{f} cannot be represented in JSON")))?,
+            Value::String(s) => serde_json::Value::String(s),
+            Value::Sequence(seq) => seq
+                .into_iter()
+                .map(serde_json::Value::try_from)
+                .collect::<Result<_, _>>()?,
+            Value::Mapping(map) => map
+                .into_iter()
+                .map(|(k, v)| {
+                    let key = k.scalar_text().ok_or_else(|| {
+                        Error::Value("a collection cannot be a JSON object key".to_string())
+                    })?;
+                    Ok((key, serde_json::Value::try_from(v)?))
+                })
+                .collect::<Result<serde_json::Map<_, _>, Error>>()?
+                .into(),
+            Value::Tagged(tagged) => serde_json::Value::try_from(tagged.value)?,
+        })
+    }
+}
+
+impl PartialEq<bool> for Value {
+    fn eq(&self, other: &bool) -> bool {
+        self.as_bool() == Some(*other)
+    }
+}
+
+impl PartialEq<i64> for Value {
+    fn eq(&self, other: &i64) -> bool {
+        self.as_i64() == Some(*other)
+    }
+}
+
+impl PartialEq<i32> for Value {
+    fn eq(&self, other: &i32) -> bool {
+        self.as_i64() == Some(i64::from(*other))
+    }
+}
+
+impl PartialEq<f64> for Value {
+    fn eq(&self, other: &f64) -> bool {
+        matches!(self, Value::Float(f) if f == other)
+    }
+}
+
+impl PartialEq<str> for Value {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == Some(other)
+    }
+}
+
+impl PartialEq<&str> for Value {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == Some(*other)
+    }
+}
+
+impl PartialEq<String> for Value {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == Some(other.as_str())
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::{fmt, str::FromStr};
+
+use serde::{
+    de::{self, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::Value;
+use crate::Error;
+
+/// The struct name `serde_json` gives a number with its
+/// `arbitrary_precision` feature. A [`Number`] passes through serde the same
+/// way when it does not fit in 128 bits.
+pub(crate) const JSON_NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+/// An integer of any size, kept as its exact decimal digits.
+///
+/// In arbitrary precision mode (see `Builder::arbitrary_precision`) an
+/// integer that does not fit in an `i64` loads as a [`Value::BigInt`]. Load
+/// it into a `u64`, an `i128` or a `Number` to read it.
+///
+/// ```
+/// let n: yamlstar::Number = "18446744073709551615".parse().unwrap();
+/// assert_eq!(n.as_u64(), Some(u64::MAX));
+/// assert_eq!(n.as_i64(), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Number {
+    /// The digits, with a leading `-` if negative and no leading zeros.
+    digits: String,
+}
+
+impl Number {
+    /// The exact decimal digits, with a leading `-` if negative.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.digits
+    }
+
+    /// The integer as an `i64`, if it fits.
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        self.digits.parse().ok()
+    }
+
+    /// The integer as a `u64`, if it fits.
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        self.digits.parse().ok()
+    }
+
+    /// The integer as an `i128`, if it fits.
+    #[must_use]
+    pub fn as_i128(&self) -> Option<i128> {
+        self.digits.parse().ok()
+    }
+
+    /// The integer as a `u128`, if it fits.
+    #[must_use]
+    pub fn as_u128(&self) -> Option<u128> {
+        self.digits.parse().ok()
+    }
+
+    /// Visit the integer as the smallest Rust type that holds it, or as a
+    /// `serde_json` arbitrary precision number if none does.
+    pub(crate) fn visit<'de, V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if let Some(n) = self.as_i64() {
+            visitor.visit_i64(n)
+        } else if let Some(n) = self.as_u64() {
+            visitor.visit_u64(n)
+        } else if let Some(n) = self.as_i128() {
+            visitor.visit_i128(n)
+        } else if let Some(n) = self.as_u128() {
+            visitor.visit_u128(n)
+        } else {
+            visitor.visit_map(de::value::MapDeserializer::new(std::iter::once((
+                JSON_NUMBER_TOKEN,
+                self.digits,
+            ))))
+        }
+    }
+}
+
+impl FromStr for Number {
+    type Err = Error;
+
+    /// Parse a decimal integer with an optional sign.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let (negative, unsigned) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        if unsigned.is_empty() || !unsigned.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::Value(format!("invalid integer {s:?}")));
+        }
+        let unsigned = unsigned.trim_start_matches('0');
+        let digits = match unsigned {
+            "" => "0".to_string(),
+            _ if negative => format!("-{unsigned}"),
+            _ => unsigned.to_string(),
+        };
+        Ok(Self { digits })
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.digits)
+    }
+}
+
+macro_rules! from_integer {
+    ($($ty:ty)*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(value: $ty) -> Self {
+                    Self {
+                        digits: value.to_string(),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i8 i16 i32 i64 i128 u8 u16 u32 u64 u128);
+
+/// Parse the text of a `serde_json` arbitrary precision number.
+pub(crate) fn number_value(text: &str) -> Result<Value, Error> {
+    match text.parse::<Number>() {
+        Ok(n) => Ok(n.into()),
+        Err(_) => text
+            .parse()
+            .map(Value::Float)
+            .map_err(|_| Error::Value(format!("invalid number {text:?}"))),
+    }
+}
+
+impl Serialize for Number {
+    /// Serialize as the smallest Rust integer type that holds the number,
+    /// or as a `serde_json` arbitrary precision number if none does.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Some(n) = self.as_i64() {
+            serializer.serialize_i64(n)
+        } else if let Some(n) = self.as_u64() {
+            serializer.serialize_u64(n)
+        } else if let Some(n) = self.as_i128() {
+            serializer.serialize_i128(n)
+        } else if let Some(n) = self.as_u128() {
+            serializer.serialize_u128(n)
+        } else {
+            let mut state = serializer.serialize_struct(JSON_NUMBER_TOKEN, 1)?;
+            state.serialize_field(JSON_NUMBER_TOKEN, &self.digits)?;
+            state.end()
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Number, D::Error> {
+        deserializer.deserialize_any(NumberVisitor)
+    }
+}
+
+/// Builds a [`Number`] from any integer.
+struct NumberVisitor;
+
+impl<'de> Visitor<'de> for NumberVisitor {
+    type Value = Number;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an integer")
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Number, E> {
+        Ok(v.into())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Number, E> {
+        Ok(v.into())
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Number, E> {
+        Ok(v.into())
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Number, E> {
+        Ok(v.into())
+    }
+
+    /// A `serde_json` arbitrary precision number.
+    fn visit_map<A: de::MapAccess<'de>>(self, mut access: A) -> Result<Number, A::Error> {
+        match access.next_key::<String>()? {
+            Some(key) if key == JSON_NUMBER_TOKEN => {
+                let text = access.next_value::<String>()?;
+                text.parse()
+                    .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&text), &self))
+            }
+            _ => Err(de::Error::invalid_type(de::Unexpected::Map, &self)),
+        }
+    }
+}
@@ -0,0 +1,439 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use serde::{
+    ser::{self, SerializeMap, SerializeSeq},
+    Serialize,
+};
+
+use super::{
+    number::{number_value, JSON_NUMBER_TOKEN},
+    tagged::{TagEntry, TAGGED_TOKEN},
+    Mapping, TaggedValue, Value,
+};
+use crate::Error;
+
+impl Serialize for Value {
+    /// A tagged value is serialized like a [`super::Tagged`]: other formats
+    /// see a single-entry map from its tag to its value, and [`to_value`]
+    /// turns it back into a tagged value.
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Int(n) => serializer.serialize_i64(*n),
+            Value::BigInt(n) => n.serialize(serializer),
+            Value::Float(f) => serializer.serialize_f64(*f),
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Sequence(seq) => {
+                let mut state = serializer.serialize_seq(Some(seq.len()))?;
+                for item in seq {
+                    state.serialize_element(item)?;
+                }
+                state.end()
+            }
+            Value::Mapping(map) => {
+                let mut state = serializer.serialize_map(Some(map.len()))?;
+                for (k, v) in map {
+                    state.serialize_entry(k, v)?;
+                }
+                state.end()
+            }
+            Value::Tagged(tagged) => serializer
+                .serialize_newtype_struct(TAGGED_TOKEN, &TagEntry(&tagged.tag, &tagged.value)),
+        }
+    }
+}
+
+/// Convert any serializable value to a [`Value`].
+///
+/// Struct fields and map entries keep their order. A [`super::Tagged`] or a
+/// tagged [`Value`] stays a [`Value::Tagged`], while a map whose keys start
+/// with `!` stays a map. An integer that does not fit in an `i64` becomes a
+/// [`Value::BigInt`]. A unit enum variant becomes its name, and any other
+/// variant a single-entry map from its name to its data.
+///
+/// # Errors
+/// Returns [`Error::Value`] if `value` fails to serialize.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, Error> {
+    to_value_with_tagged_enums(value, false)
+}
+
+/// [`to_value`], with enum variants that hold data tagged `!Variant` if
+/// `tagged_enums`, as [`crate::Builder::tagged_enums`] asks.
+pub(crate) fn to_value_with_tagged_enums<T: Serialize + ?Sized>(
+    value: &T,
+    tagged_enums: bool,
+) -> Result<Value, Error> {
+    value.serialize(Serializer { tagged_enums })
+}
+
+/// A serializer whose output is a [`Value`].
+#[derive(Clone, Copy)]
+struct Serializer {
+    /// Whether enum variants that hold data are tagged `!Variant` rather
+    /// than keyed by their name.
+    tagged_enums: bool,
+}
+
+/// A sequence being serialized.
+struct SeqBuilder {
+    /// The serializer of the items.
+    serializer: Serializer,
+    /// The items so far.
+    items: Vec<Value>,
+    /// The variant name, for a tuple variant.
+    variant: Option<&'static str>,
+}
+
+/// A map being serialized.
+struct MapBuilder {
+    /// The serializer of the keys and values.
+    serializer: Serializer,
+    /// The entries so far.
+    map: Mapping,
+    /// A key waiting for its value.
+    key: Option<Value>,
+    /// The variant name, for a struct variant.
+    variant: Option<&'static str>,
+}
+
+impl Serializer {
+    /// Wrap `value` in a single-entry mapping keyed by a variant name, or
+    /// with `tagged_enums`, tag it `!` and the variant name.
+    fn variant_value(self, variant: &'static str, value: Value) -> Value {
+        if self.tagged_enums {
+            return TaggedValue::new(format!("!{variant}"), value).into();
+        }
+        let mut map = Mapping::with_capacity(1);
+        map.insert(variant, value);
+        Value::Mapping(map)
+    }
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SeqBuilder;
+    type SerializeTuple = SeqBuilder;
+    type SerializeTupleStruct = SeqBuilder;
+    type SerializeTupleVariant = SeqBuilder;
+    type SerializeMap = MapBuilder;
+    type SerializeStruct = MapBuilder;
+    type SerializeStructVariant = MapBuilder;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        Ok(v.iter().copied().collect())
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(variant.into())
+    }
+
+    /// A [`super::Tagged`] is a single-entry map from its tag to its value,
+    /// which becomes a tagged value whatever the tag.
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        match value.serialize(self)? {
+            Value::Mapping(map) if name == TAGGED_TOKEN && map.len() == 1 => {
+                match map.into_iter().next().expect("the map has one entry") {
+                    (Value::String(tag), value) => Ok(TaggedValue { tag, value }.into()),
+                    (tag, _) => Err(Error::Value(format!("a tag must be a string, not {tag}"))),
+                }
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        Ok(self.variant_value(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqBuilder, Error> {
+        Ok(SeqBuilder {
+            serializer: self,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqBuilder, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqBuilder, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqBuilder, Error> {
+        Ok(SeqBuilder {
+            serializer: self,
+            items: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapBuilder, Error> {
+        Ok(MapBuilder {
+            serializer: self,
+            map: Mapping::with_capacity(len.unwrap_or(0)),
+            key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapBuilder, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<MapBuilder, Error> {
+        Ok(MapBuilder {
+            serializer: self,
+            map: Mapping::with_capacity(len),
+            key: None,
+            variant: Some(variant),
+        })
+    }
+}
+
+impl SeqBuilder {
+    /// Finish the sequence.
+    fn finish(self) -> Value {
+        let seq = Value::Sequence(self.items);
+        match self.variant {
+            Some(variant) => self.serializer.variant_value(variant, seq),
+            None => seq,
+        }
+    }
+}
+
+impl ser::SerializeSeq for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(value.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl MapBuilder {
+    /// Finish the mapping.
+    fn finish(self) -> Value {
+        match self.variant {
+            Some(variant) => self.serializer.variant_value(variant, Value::Mapping(self.map)),
+            None => Value::Mapping(self.map),
+        }
+    }
+}
+
+impl ser::SerializeMap for MapBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Value("map value serialized before its key".to_string()))?;
+        self.map.insert(key, value.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for MapBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.map.insert(key, value.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    /// A `serde_json` arbitrary precision number, or a [`super::Number`]
+    /// too large for 128 bits, is a struct with one field named
+    /// [`JSON_NUMBER_TOKEN`].
+    fn end(self) -> Result<Value, Error> {
+        if self.map.len() == 1 {
+            if let Some(Value::String(text)) = self.map.get(&JSON_NUMBER_TOKEN.into()) {
+                return number_value(text);
+            }
+        }
+        Ok(Value::Mapping(self.map))
+    }
+}
+
+impl ser::SerializeStructVariant for MapBuilder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
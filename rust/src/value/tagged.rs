@@ -0,0 +1,205 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// The newtype struct name a [`Tagged`] passes through serde with. The
+/// [`crate::Value`] serializer and deserializer know it; other formats see a
+/// single-entry map from the tag to the value.
+pub(crate) const TAGGED_TOKEN: &str = "$yamlstar::private::Tagged";
+
+/// A value with the tag of the node it was loaded from.
+///
+/// Loading a `Tagged<T>` keeps the tag that a plain `T` would not see, and
+/// fails on a node without a tag outside the core schema; load a
+/// [`MaybeTagged`] to accept both. Dumping a `Tagged<T>` writes the tag.
+///
+/// ```no_run
+/// use yamlstar::{Tagged, YAMLStar};
+///
+/// let ys = YAMLStar::new().unwrap();
+/// let secret: Tagged<String> = ys.load("!vault s3cr3t").unwrap();
+/// assert_eq!(secret.tag, "!vault");
+/// assert_eq!(secret.value, "s3cr3t");
+/// assert_eq!(ys.dump(&secret).unwrap(), "!vault s3cr3t\n");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Tagged<T> {
+    /// The tag as written, such as `"!vault"`, or in full for `!!` tags,
+    /// such as `"tag:yaml.org,2002:set"`.
+    pub tag: String,
+    /// The value. A tagged scalar loads as its text, typed as a plain scalar
+    /// of the instance's [`crate::Schema`] when `T` is a boolean, a number or
+    /// an option.
+    pub value: T,
+}
+
+impl<T> Tagged<T> {
+    /// Create a tagged value.
+    pub fn new(tag: impl Into<String>, value: T) -> Self {
+        Self {
+            tag: tag.into(),
+            value,
+        }
+    }
+}
+
+/// A value that may have a tag: a [`Tagged`] for a node with a tag outside
+/// the core schema, or the bare value for any other node.
+///
+/// ```no_run
+/// use yamlstar::{MaybeTagged, YAMLStar};
+///
+/// let ys = YAMLStar::new().unwrap();
+/// let ports: Vec<MaybeTagged<u16>> = ys.load("[80, !internal 8080]").unwrap();
+/// assert_eq!(ports[0].tag(), None);
+/// assert_eq!(ports[1].tag(), Some("!internal"));
+/// assert_eq!(*ports[1].value(), 8080);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MaybeTagged<T> {
+    /// A node with a tag.
+    Tagged(Tagged<T>),
+    /// A node without one.
+    Untagged(T),
+}
+
+impl<T> MaybeTagged<T> {
+    /// The tag, or `None` for an untagged node.
+    #[must_use]
+    pub fn tag(&self) -> Option<&str> {
+        match self {
+            MaybeTagged::Tagged(tagged) => Some(&tagged.tag),
+            MaybeTagged::Untagged(_) => None,
+        }
+    }
+
+    /// The value, with or without its tag.
+    #[must_use]
+    pub fn value(&self) -> &T {
+        match self {
+            MaybeTagged::Tagged(tagged) => &tagged.value,
+            MaybeTagged::Untagged(value) => value,
+        }
+    }
+
+    /// Drop the tag, if any, and return the value.
+    #[must_use]
+    pub fn into_value(self) -> T {
+        match self {
+            MaybeTagged::Tagged(tagged) => tagged.value,
+            MaybeTagged::Untagged(value) => value,
+        }
+    }
+
+    /// The [`Tagged`] value, or `None` for an untagged node.
+    #[must_use]
+    pub fn into_tagged(self) -> Option<Tagged<T>> {
+        match self {
+            MaybeTagged::Tagged(tagged) => Some(tagged),
+            MaybeTagged::Untagged(_) => None,
+        }
+    }
+}
+
+impl<T> From<Tagged<T>> for MaybeTagged<T> {
+    fn from(tagged: Tagged<T>) -> Self {
+        MaybeTagged::Tagged(tagged)
+    }
+}
+
+impl<T: Serialize> Serialize for Tagged<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(TAGGED_TOKEN, &TagEntry(&self.tag, &self.value))
+    }
+}
+
+impl<T: Serialize> Serialize for MaybeTagged<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            MaybeTagged::Tagged(tagged) => tagged.serialize(serializer),
+            MaybeTagged::Untagged(value) => value.serialize(serializer),
+        }
+    }
+}
+
+/// A tag and its value, serialized as a single-entry map.
+pub(crate) struct TagEntry<'a, T>(pub(crate) &'a str, pub(crate) &'a T);
+
+impl<T: Serialize> Serialize for TagEntry<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_map(Some(1))?;
+        state.serialize_entry(self.0, self.1)?;
+        state.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tagged<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let visitor = NodeVisitor {
+            require_tag: true,
+            marker: PhantomData,
+        };
+        match deserializer.deserialize_newtype_struct(TAGGED_TOKEN, visitor)? {
+            MaybeTagged::Tagged(tagged) => Ok(tagged),
+            MaybeTagged::Untagged(_) => Err(de::Error::custom("expected a tagged node")),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for MaybeTagged<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let visitor = NodeVisitor {
+            require_tag: false,
+            marker: PhantomData,
+        };
+        deserializer.deserialize_newtype_struct(TAGGED_TOKEN, visitor)
+    }
+}
+
+/// Reads a node as [`MaybeTagged`]. The [`crate::Value`] deserializer
+/// presents a tagged node as an enum variant named after its tag, and any
+/// other node as a newtype struct.
+struct NodeVisitor<T> {
+    /// Whether an untagged node is an error.
+    require_tag: bool,
+    /// The type of the value.
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for NodeVisitor<T> {
+    type Value = MaybeTagged<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.require_tag {
+            f.write_str("a tagged node")
+        } else {
+            f.write_str("any node")
+        }
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, access: A) -> Result<Self::Value, A::Error> {
+        let (tag, variant) = access.variant::<String>()?;
+        let value = de::VariantAccess::newtype_variant::<T>(variant)?;
+        Ok(MaybeTagged::Tagged(Tagged { tag, value }))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        if self.require_tag {
+            return Err(de::Error::invalid_type(
+                de::Unexpected::Other("an untagged node"),
+                &self,
+            ));
+        }
+        T::deserialize(deserializer).map(MaybeTagged::Untagged)
+    }
+}
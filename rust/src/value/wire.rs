@@ -0,0 +1,444 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! The tagged JSON encoding spoken by the `yamlstar_*_tagged` exports.
+//!
+//! Null, booleans, integers, strings and sequences are plain JSON. The rest
+//! is marked with a single-entry object:
+//!
+//! - a mapping is `{"!map": [[key, value], ...]}`, so keys keep their type
+//!   and order;
+//! - a float is `{"!float": "<YAML text>"}`, so `.inf` and `.nan` survive;
+//! - an integer that does not fit in an `i64` is `{"!int": "<digits>"}`;
+//! - a node with a tag outside the core schema is `{"!tag": [tag, value]}`;
+//! - with the `plain` option, a scalar the schema typed is
+//!   `{"!plain": [text, value]}`, for the predicate resolvers; see
+//!   [`resolve_plain`].
+//!
+//! The pairs of a `!map` keep any duplicate keys, which [`check_keys`]
+//! handles by the instance's [`DuplicateKeys`] policy before decoding.
+//!
+//! Any other object is read as a mapping with string keys.
+//!
+//! Without the tagged encoding the exports speak plain JSON. The ones with
+//! options mark the infinite and NaN floats that JSON cannot express with
+//! `!float` alone, and so that no mapping reads as the marker, a string key
+//! starting with `!` gets another `!` in front. The plain exports, which
+//! other bindings use too, do neither; see [`Plain`] and [`decode_plain`].
+
+use serde::{
+    ser::{Error as _, SerializeMap},
+    Serialize, Serializer,
+};
+use serde_json::json;
+
+use super::{key_token, push_pointer_token, Mapping, Number, TaggedValue, Value};
+use crate::{options::Options, Diagnostic, DuplicateKey, DuplicateKeys, Error};
+
+/// Encode a value for the tagged exports.
+pub(crate) fn encode(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Bool(b) => json!(b),
+        Value::Int(n) => json!(n),
+        Value::BigInt(n) => json!({ "!int": n.as_str() }),
+        Value::Float(f) => json!({ "!float": super::display::float_text(*f) }),
+        Value::String(s) => json!(s),
+        Value::Sequence(seq) => seq.iter().map(encode).collect(),
+        Value::Mapping(map) => {
+            let pairs = map
+                .iter()
+                .map(|(k, v)| json!([encode(k), encode(v)]))
+                .collect::<Vec<_>>();
+            json!({ "!map": pairs })
+        }
+        Value::Tagged(tagged) => json!({ "!tag": [tagged.tag, encode(&tagged.value)] }),
+    }
+}
+
+/// Serializes a value as plain JSON for libraries without the tagged
+/// exports. A tagged value is a single-entry map from its tag to its value.
+///
+/// If `marked`, for the exports with options, infinite and NaN floats become
+/// `{"!float": "<YAML text>"}` and string keys starting with `!` get another
+/// `!` in front. Otherwise infinite and NaN floats fail to serialize.
+pub(crate) struct Plain<'a> {
+    /// The value.
+    pub(crate) value: &'a Value,
+    /// Whether to mark floats and escape keys.
+    pub(crate) marked: bool,
+}
+
+impl<'a> Plain<'a> {
+    /// The same encoding of another value.
+    fn of(&self, value: &'a Value) -> Self {
+        Plain {
+            value,
+            marked: self.marked,
+        }
+    }
+}
+
+/// A mapping key of a [`Plain`] value.
+struct PlainKey<'a>(Plain<'a>);
+
+impl Serialize for PlainKey<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0.value {
+            Value::String(s) if self.0.marked => serializer.serialize_str(&escape_key(s)),
+            _ => self.0.serialize(serializer),
+        }
+    }
+}
+
+/// Put another `!` in front of a plain JSON key starting with `!`.
+fn escape_key(key: &str) -> std::borrow::Cow<'_, str> {
+    if key.starts_with('!') {
+        format!("!{key}").into()
+    } else {
+        key.into()
+    }
+}
+
+impl Serialize for Plain<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value {
+            Value::Float(f) if !f.is_finite() && self.marked => {
+                let mut state = serializer.serialize_map(Some(1))?;
+                state.serialize_entry("!float", &super::display::float_text(*f))?;
+                state.end()
+            }
+            Value::Float(f) if !f.is_finite() => Err(S::Error::custom(format!(
+                "{} needs a library with the tagged exports or those with options",
+                super::display::float_text(*f)
+            ))),
+            Value::Sequence(seq) => serializer.collect_seq(seq.iter().map(|v| self.of(v))),
+            Value::Mapping(map) => serializer
+                .collect_map(map.iter().map(|(k, v)| (PlainKey(self.of(k)), self.of(v)))),
+            Value::Tagged(tagged) => {
+                let mut state = serializer.serialize_map(Some(1))?;
+                let tag = if self.marked {
+                    escape_key(&tagged.tag)
+                } else {
+                    tagged.tag.as_str().into()
+                };
+                state.serialize_entry(&tag, &self.of(&tagged.value))?;
+                state.end()
+            }
+            other => other.serialize(serializer),
+        }
+    }
+}
+
+/// Decode a value returned by the tagged exports.
+///
+/// The response is parsed into a [`Value`] first, so plain objects keep
+/// their key order. Integers that do not fit in an `i64` are an error unless
+/// `arbitrary_precision` is set.
+pub(crate) fn decode(value: Value, arbitrary_precision: bool) -> Result<Value, Error> {
+    Ok(match value {
+        Value::Sequence(seq) => Value::Sequence(
+            seq.into_iter()
+                .map(|item| decode(item, arbitrary_precision))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Mapping(map) => {
+            let marker = match map.iter().next() {
+                Some((Value::String(key), _)) if map.len() == 1 => key.clone(),
+                _ => return decode_object(map, arbitrary_precision),
+            };
+            let payload = map.into_iter().next().map(|(_, v)| v).unwrap_or_default();
+            match (marker.as_str(), payload) {
+                ("!map", Value::Sequence(pairs)) => decode_pairs(pairs, arbitrary_precision)?,
+                ("!float", Value::String(text)) => Value::Float(parse_float(&text)?),
+                ("!int", Value::String(text)) => {
+                    let n = text
+                        .parse::<Number>()
+                        .map_err(|_| malformed(&format!("invalid integer {text:?}")))?;
+                    decode(n.into(), arbitrary_precision)?
+                }
+                ("!tag", Value::Sequence(parts)) => decode_tagged(parts, arbitrary_precision)?,
+                (_, payload) => decode_object(
+                    Mapping::from_iter([(marker, payload)]),
+                    arbitrary_precision,
+                )?,
+            }
+        }
+        Value::BigInt(n) => big_int(n, arbitrary_precision)?,
+        other => other,
+    })
+}
+
+/// Decode a plain JSON response. If `marked`, as written by [`Plain`] for the
+/// exports with options, turn `{"!float": "<YAML text>"}` into a float and
+/// take the extra `!` off keys.
+pub(crate) fn decode_plain(
+    value: Value,
+    marked: bool,
+    arbitrary_precision: bool,
+) -> Result<Value, Error> {
+    Ok(match value {
+        Value::Sequence(seq) => Value::Sequence(
+            seq.into_iter()
+                .map(|item| decode_plain(item, marked, arbitrary_precision))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Mapping(map) => {
+            if let (true, 1, Some(Value::String(text))) =
+                (marked, map.len(), map.get(&"!float".into()))
+            {
+                return Ok(Value::Float(parse_float(text)?));
+            }
+            let mut object = Mapping::with_capacity(map.len());
+            for (key, value) in map {
+                let key = match key {
+                    Value::String(s) if marked && s.starts_with('!') => {
+                        Value::String(s[1..].to_string())
+                    }
+                    key => key,
+                };
+                object.insert(key, decode_plain(value, marked, arbitrary_precision)?);
+            }
+            Value::Mapping(object)
+        }
+        Value::BigInt(n) => big_int(n, arbitrary_precision)?,
+        other => other,
+    })
+}
+
+/// Check that an integer beyond `i64` may be loaded.
+fn big_int(n: Number, arbitrary_precision: bool) -> Result<Value, Error> {
+    if arbitrary_precision {
+        Ok(Value::BigInt(n))
+    } else {
+        Err(Error::Value(format!(
+            "integer {n} does not fit in an i64; enable arbitrary precision to load it"
+        )))
+    }
+}
+
+/// Replace each `{"!plain": [text, value]}` marker in a response with a
+/// `!tag` marker for the text if a predicate resolver of `options` matches
+/// it, or else with its value.
+pub(crate) fn resolve_plain(value: Value, options: &Options) -> Result<Value, Error> {
+    Ok(match value {
+        Value::Sequence(seq) => Value::Sequence(
+            seq.into_iter()
+                .map(|item| resolve_plain(item, options))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Mapping(map) if map.len() == 1 && map.contains_key(&"!plain".into()) => {
+            let payload = map.into_iter().next().map(|(_, v)| v).unwrap_or_default();
+            let Value::Sequence(parts) = payload else {
+                return Err(malformed("!plain is not a [text, value] pair"));
+            };
+            let Ok([Value::String(text), value]) = <[_; 2]>::try_from(parts) else {
+                return Err(malformed("!plain is not a [text, value] pair"));
+            };
+            match options.resolve(&text) {
+                Some(tag) => json!({ "!tag": [tag, text] }).into(),
+                None => value,
+            }
+        }
+        Value::Mapping(map) => Value::Mapping(
+            map.into_iter()
+                .map(|(k, v)| Ok((resolve_plain(k, options)?, resolve_plain(v, options)?)))
+                .collect::<Result<_, Error>>()?,
+        ),
+        other => other,
+    })
+}
+
+/// Give each string that a predicate resolver of `options` matches the
+/// non-specific tag `!`, so that libyamlstar quotes it when dumping.
+pub(crate) fn quote_resolved(value: Value, options: &Options) -> Value {
+    match value {
+        Value::String(s) if options.resolve(&s).is_some() => TaggedValue::new("!", s).into(),
+        Value::Sequence(seq) => Value::Sequence(
+            seq.into_iter()
+                .map(|item| quote_resolved(item, options))
+                .collect(),
+        ),
+        Value::Mapping(map) => Value::Mapping(
+            map.into_iter()
+                .map(|(k, v)| (quote_resolved(k, options), quote_resolved(v, options)))
+                .collect(),
+        ),
+        Value::Tagged(tagged) if matches!(tagged.value, Value::Sequence(_) | Value::Mapping(_)) => {
+            TaggedValue::new(tagged.tag, quote_resolved(tagged.value, options)).into()
+        }
+        other => other,
+    }
+}
+
+/// Apply `policy` to the duplicate keys of each `!map` marker in the
+/// response for the document numbered `document`: fail, or drop the pairs
+/// whose values are not kept, adding a warning to `warnings` for
+/// [`DuplicateKeys::Warn`].
+pub(crate) fn check_keys(
+    value: Value,
+    policy: DuplicateKeys,
+    document: usize,
+    warnings: &mut Vec<Diagnostic>,
+) -> Result<Value, Error> {
+    KeyCheck {
+        policy,
+        document,
+        path: String::new(),
+        warnings,
+    }
+    .check(value)
+}
+
+/// The state of [`check_keys`].
+struct KeyCheck<'a> {
+    /// What to do with a duplicate key.
+    policy: DuplicateKeys,
+    /// The index of the document in the stream.
+    document: usize,
+    /// The JSON Pointer of the node being checked.
+    path: String,
+    /// Where [`DuplicateKeys::Warn`] records duplicates.
+    warnings: &'a mut Vec<Diagnostic>,
+}
+
+impl KeyCheck<'_> {
+    /// Check the mappings in a node of a response.
+    fn check(&mut self, value: Value) -> Result<Value, Error> {
+        Ok(match value {
+            Value::Sequence(seq) => Value::Sequence(
+                seq.into_iter()
+                    .enumerate()
+                    .map(|(i, item)| self.check_child(item, &i.to_string()))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Mapping(map) if map.len() == 1 => {
+                let (marker, payload) = map.into_iter().next().unwrap_or_default();
+                let payload = match (marker.as_str(), payload) {
+                    (Some("!map"), Value::Sequence(pairs)) => {
+                        Value::Sequence(self.check_pairs(pairs)?)
+                    }
+                    (Some("!tag"), Value::Sequence(mut parts)) if parts.len() == 2 => {
+                        let value = parts.pop().unwrap_or_default();
+                        parts.push(self.check(value)?);
+                        Value::Sequence(parts)
+                    }
+                    (_, payload) => payload,
+                };
+                Value::Mapping(Mapping::from_iter([(marker, payload)]))
+            }
+            other => other,
+        })
+    }
+
+    /// Check the child of the node being checked found by the JSON Pointer
+    /// reference `token`.
+    fn check_child(&mut self, value: Value, token: &str) -> Result<Value, Error> {
+        let len = self.path.len();
+        push_pointer_token(&mut self.path, token);
+        let result = self.check(value);
+        self.path.truncate(len);
+        result
+    }
+
+    /// Check the pairs of a `!map` marker, returning those to decode.
+    /// Malformed pairs are kept for [`decode_pairs`] to report.
+    fn check_pairs(&mut self, pairs: Vec<Value>) -> Result<Vec<Value>, Error> {
+        let mut kept = Vec::with_capacity(pairs.len());
+        // Each key so far, decoded, and at the same index in `places` its
+        // entry and its index in `kept`.
+        let mut seen = Mapping::with_capacity(pairs.len());
+        let mut places = Vec::with_capacity(pairs.len());
+        for (entry, pair) in pairs.into_iter().enumerate() {
+            let Value::Sequence(pair) = pair else {
+                kept.push(pair);
+                continue;
+            };
+            let Ok([key, value]) = <[_; 2]>::try_from(pair) else {
+                return Err(malformed("!map entry is not a pair"));
+            };
+            let decoded = decode(key.clone(), true)?;
+            let token = key_token(&decoded);
+            let Some((first, slot)) = seen.position(&decoded).map(|i| places[i]) else {
+                let key = self.check_child(key, &token)?;
+                let value = self.check_child(value, &token)?;
+                seen.insert(decoded, Value::Null);
+                places.push((entry, kept.len()));
+                kept.push(Value::Sequence(vec![key, value]));
+                continue;
+            };
+            let duplicate = DuplicateKey {
+                key: token.clone(),
+                path: self.path.clone(),
+                document: self.document,
+                first,
+                second: entry,
+            };
+            match self.policy {
+                DuplicateKeys::Error => return Err(Error::DuplicateKey(duplicate)),
+                DuplicateKeys::First => continue,
+                DuplicateKeys::Last => {}
+                DuplicateKeys::Warn => self.warnings.push(Diagnostic::DuplicateKey(duplicate)),
+            }
+            let value = self.check_child(value, &token)?;
+            if let Value::Sequence(pair) = &mut kept[slot] {
+                pair[1] = value;
+            }
+        }
+        Ok(kept)
+    }
+}
+
+/// Decode the payload of a `!map` marker.
+fn decode_pairs(pairs: Vec<Value>, arbitrary_precision: bool) -> Result<Value, Error> {
+    let mut map = Mapping::with_capacity(pairs.len());
+    for pair in pairs {
+        let Value::Sequence(pair) = pair else {
+            return Err(malformed("!map entry is not a pair"));
+        };
+        let [key, value]: [Value; 2] = pair
+            .try_into()
+            .map_err(|_| malformed("!map entry is not a pair"))?;
+        map.insert(
+            decode(key, arbitrary_precision)?,
+            decode(value, arbitrary_precision)?,
+        );
+    }
+    Ok(Value::Mapping(map))
+}
+
+/// Decode the payload of a `!tag` marker.
+fn decode_tagged(parts: Vec<Value>, arbitrary_precision: bool) -> Result<Value, Error> {
+    let Ok([Value::String(tag), value]) = <[_; 2]>::try_from(parts) else {
+        return Err(malformed("!tag is not a [tag, value] pair"));
+    };
+    Ok(TaggedValue::new(tag, decode(value, arbitrary_precision)?).into())
+}
+
+/// Decode a plain JSON object.
+fn decode_object(object: Mapping, arbitrary_precision: bool) -> Result<Value, Error> {
+    let mut map = Mapping::with_capacity(object.len());
+    for (key, value) in object {
+        map.insert(key, decode(value, arbitrary_precision)?);
+    }
+    Ok(Value::Mapping(map))
+}
+
+/// Parse the YAML text of a float.
+fn parse_float(text: &str) -> Result<f64, Error> {
+    let unsigned = text.trim_start_matches(['+', '-']);
+    let negative = text.starts_with('-');
+    match unsigned {
+        ".inf" | ".Inf" | ".INF" if negative => Ok(f64::NEG_INFINITY),
+        ".inf" | ".Inf" | ".INF" => Ok(f64::INFINITY),
+        ".nan" | ".NaN" | ".NAN" if unsigned.len() == text.len() => Ok(f64::NAN),
+        _ => text
+            .parse()
+            .map_err(|_| malformed(&format!("invalid float {text:?}"))),
+    }
+}
+
+/// An error for a response that does not follow the tagged encoding.
+fn malformed(what: &str) -> Error {
+    Error::Ffi(format!("malformed tagged response: {what}"))
+}
@@ -0,0 +1,105 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Library version parsing and compatibility checks.
+
+use std::{
+    error::Error as StdError,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+/// A `major.minor.patch[-pre]` version, as reported by `yamlstar_version`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Version {
+    /// The major version.
+    pub major: u64,
+    /// The minor version.
+    pub minor: u64,
+    /// The patch version.
+    pub patch: u64,
+    /// The pre-release label, such as `SNAPSHOT`.
+    pub pre: Option<String>,
+}
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseVersionError(s.to_string());
+
+        let s = s.trim();
+        let (release, pre) = match s.split_once('-') {
+            Some((release, pre)) if !pre.is_empty() => (release, Some(pre.to_string())),
+            Some(_) => return Err(err()),
+            None => (s, None),
+        };
+
+        let mut parts = release.split('.').map(str::parse::<u64>);
+        let (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch)), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(err());
+        };
+
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{pre}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An error while parsing a [`Version`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseVersionError(String);
+
+impl Display for ParseVersionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid version string: {:?}", self.0)
+    }
+}
+
+impl StdError for ParseVersionError {}
+
+/// Which library versions a [`crate::YAMLStar`] instance accepts.
+///
+/// Pre-release labels are ignored, so a `0.1.18-SNAPSHOT` library matches
+/// version `0.1.18` under every policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VersionPolicy {
+    /// Require the same major, minor and patch version.
+    #[default]
+    Exact,
+    /// Require the same major and minor version.
+    SameMinor,
+    /// Accept any version, without asking the library for it.
+    Any,
+}
+
+impl VersionPolicy {
+    /// Check whether a library reporting `found` satisfies this policy for a
+    /// binding that expects `expected`.
+    #[must_use]
+    pub fn accepts(self, expected: &Version, found: &Version) -> bool {
+        match self {
+            Self::Exact => {
+                (expected.major, expected.minor, expected.patch)
+                    == (found.major, found.minor, found.patch)
+            }
+            Self::SameMinor => (expected.major, expected.minor) == (found.major, found.minor),
+            Self::Any => true,
+        }
+    }
+}
@@ -0,0 +1,653 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Sandboxed worker processes.
+//!
+//! In [`crate::BackendKind::Worker`] mode every call is sent to one of a pool
+//! of helper processes, each of which loads libyamlstar itself. A runtime
+//! panic, an out-of-memory abort or a resource limit only kills the worker;
+//! the call returns [`Error::WorkerCrashed`] and the next call starts a new
+//! worker.
+//!
+//! Requests and responses are framed on the worker's stdin and stdout as a
+//! one-byte tag, a little-endian `u32` length and the payload. The payload
+//! of a request with options is the options JSON, a newline and the input.
+//! The worker first sends a response frame saying whether the library could
+//! be loaded and, if so, which operations it supports. The pool starts one
+//! worker when it is created and keeps those capabilities for its lifetime.
+//!
+//! Each worker's responses are read on a thread of their own, so that a
+//! worker that does not answer in time, even one blocked without using CPU,
+//! can be killed: the call returns [`Error::WorkerTimedOut`] and the next
+//! call starts a new worker.
+
+use std::{
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Condvar, Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use crate::{Backend, BackendKind, Capabilities, Error, VersionPolicy, YAMLStar};
+
+/// The environment variable that makes [`run_worker_if_requested`] serve.
+pub const WORKER_ENV_VAR: &str = "YAMLSTAR_WORKER";
+/// The environment variable holding the worker's CPU-time limit in seconds.
+const CPU_TIME_ENV_VAR: &str = "YAMLSTAR_WORKER_CPU_TIME";
+/// The environment variable holding the worker's address-space limit in
+/// bytes.
+const ADDRESS_SPACE_ENV_VAR: &str = "YAMLSTAR_WORKER_ADDRESS_SPACE";
+
+/// How long a worker may take to start or answer a request, unless
+/// [`crate::Builder::worker_timeout`] says otherwise.
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_mins(1);
+
+/// A response frame: its tag and payload.
+type Frame = (u8, Vec<u8>);
+
+/// Request tags.
+const OP_LOAD: u8 = 0;
+const OP_LOAD_ALL: u8 = 1;
+const OP_DUMP: u8 = 2;
+const OP_DUMP_ALL: u8 = 3;
+const OP_VERSION: u8 = 4;
+const OP_LOAD_TAGGED: u8 = 6;
+const OP_LOAD_ALL_TAGGED: u8 = 7;
+const OP_DUMP_TAGGED: u8 = 8;
+const OP_DUMP_ALL_TAGGED: u8 = 9;
+const OP_LOAD_WITH_OPTIONS: u8 = 10;
+const OP_LOAD_ALL_WITH_OPTIONS: u8 = 11;
+const OP_DUMP_WITH_OPTIONS: u8 = 12;
+const OP_DUMP_ALL_WITH_OPTIONS: u8 = 13;
+
+/// Response tags.
+const STATUS_OK: u8 = 0;
+const STATUS_ERROR: u8 = 1;
+const STATUS_UNSUPPORTED: u8 = 2;
+
+/// How to start and limit worker processes.
+#[derive(Clone, Debug)]
+pub(crate) struct WorkerConfig {
+    /// The executable to run, or the `yamlstar-worker` binary if `None`;
+    /// see [`run_worker`].
+    pub(crate) program: Option<PathBuf>,
+    /// The most workers to run at once.
+    pub(crate) workers: usize,
+    /// CPU time allowed for each request.
+    pub(crate) cpu_time: Option<Duration>,
+    /// Address space allowed for each worker, in bytes.
+    pub(crate) address_space: Option<u64>,
+    /// Wall-clock time allowed for starting and for each request.
+    pub(crate) timeout: Duration,
+    /// The library file the worker should open.
+    pub(crate) library: Option<PathBuf>,
+}
+
+/// A backend that forwards every call to a pool of worker processes.
+pub(crate) struct WorkerBackend {
+    /// How to start workers.
+    config: WorkerConfig,
+    /// The capabilities the first worker reported.
+    capabilities: Capabilities,
+    /// Idle workers and the number of live ones.
+    pool: Mutex<Pool>,
+    /// Signalled when a worker becomes idle or exits.
+    available: Condvar,
+}
+
+/// The workers of a [`WorkerBackend`].
+#[derive(Default)]
+struct Pool {
+    /// Workers waiting for a request.
+    idle: Vec<Worker>,
+    /// Workers that are idle or handling a request.
+    live: usize,
+}
+
+/// A running worker process.
+struct Worker {
+    /// The process.
+    child: Child,
+    /// Its request pipe.
+    stdin: BufWriter<ChildStdin>,
+    /// The frames read from its response pipe, ending with the error that
+    /// stopped the reading.
+    responses: Receiver<io::Result<Frame>>,
+}
+
+impl WorkerBackend {
+    /// Create a pool and start its first worker, to learn what the library
+    /// supports. Further workers are started as needed.
+    pub(crate) fn new(config: WorkerConfig) -> Result<Self, Error> {
+        let (worker, capabilities) = Worker::spawn(&config)?;
+        Ok(Self {
+            config,
+            capabilities,
+            pool: Mutex::new(Pool {
+                idle: vec![worker],
+                live: 1,
+            }),
+            available: Condvar::new(),
+        })
+    }
+
+    /// Send one request to an idle worker, starting one if needed.
+    fn call(&self, op: u8, input: &str) -> Result<String, Error> {
+        if u32::try_from(input.len()).is_err() {
+            let e = io::Error::new(io::ErrorKind::InvalidInput, "worker request too large");
+            return Err(e.into());
+        }
+        let mut worker = self.checkout()?;
+        let error = match worker.call(op, input.as_bytes(), self.config.timeout) {
+            Ok(response) => {
+                self.checkin(worker);
+                return response;
+            }
+            Err(error) => error,
+        };
+        let status = worker.kill();
+        self.retire();
+        if error.kind() == io::ErrorKind::TimedOut {
+            return Err(Error::WorkerTimedOut(self.config.timeout));
+        }
+        Err(status.map_or_else(Error::Io, Error::WorkerCrashed))
+    }
+
+    /// Send one request with options. Options JSON holds no raw newline, so
+    /// the first one ends it.
+    fn call_with_options(&self, op: u8, input: &str, options_json: &str) -> Result<String, Error> {
+        self.call(op, &format!("{options_json}\n{input}"))
+    }
+
+    /// Take an idle worker, start a new one, or wait for one to be free.
+    fn checkout(&self) -> Result<Worker, Error> {
+        let mut pool = self.pool.lock().unwrap_or_else(PoisonError::into_inner);
+        loop {
+            if let Some(worker) = pool.idle.pop() {
+                return Ok(worker);
+            }
+            if pool.live < self.config.workers.max(1) {
+                pool.live += 1;
+                drop(pool);
+                return Worker::spawn(&self.config)
+                    .map(|(worker, _)| worker)
+                    .inspect_err(|_| self.retire());
+            }
+            pool = self
+                .available
+                .wait(pool)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Return a worker to the pool.
+    fn checkin(&self, worker: Worker) {
+        let mut pool = self.pool.lock().unwrap_or_else(PoisonError::into_inner);
+        pool.idle.push(worker);
+        self.available.notify_one();
+    }
+
+    /// Forget a worker that has exited, so a new one may be started.
+    fn retire(&self) {
+        let mut pool = self.pool.lock().unwrap_or_else(PoisonError::into_inner);
+        pool.live -= 1;
+        self.available.notify_one();
+    }
+}
+
+impl Backend for WorkerBackend {
+    fn load(&self, yaml: &str) -> Result<String, Error> {
+        self.call(OP_LOAD, yaml)
+    }
+
+    fn load_all(&self, yaml: &str) -> Result<String, Error> {
+        self.call(OP_LOAD_ALL, yaml)
+    }
+
+    fn dump(&self, data_json: &str) -> Result<String, Error> {
+        self.call(OP_DUMP, data_json)
+    }
+
+    fn dump_all(&self, data_json: &str) -> Result<String, Error> {
+        self.call(OP_DUMP_ALL, data_json)
+    }
+
+    fn version(&self) -> Result<String, Error> {
+        self.call(OP_VERSION, "")
+    }
+
+    fn load_tagged(&self, yaml: &str) -> Result<String, Error> {
+        self.call(OP_LOAD_TAGGED, yaml)
+    }
+
+    fn load_all_tagged(&self, yaml: &str) -> Result<String, Error> {
+        self.call(OP_LOAD_ALL_TAGGED, yaml)
+    }
+
+    fn dump_tagged(&self, data_json: &str) -> Result<String, Error> {
+        self.call(OP_DUMP_TAGGED, data_json)
+    }
+
+    fn dump_all_tagged(&self, data_json: &str) -> Result<String, Error> {
+        self.call(OP_DUMP_ALL_TAGGED, data_json)
+    }
+
+    fn load_with_options(&self, yaml: &str, options_json: &str) -> Result<String, Error> {
+        self.call_with_options(OP_LOAD_WITH_OPTIONS, yaml, options_json)
+    }
+
+    fn load_all_with_options(&self, yaml: &str, options_json: &str) -> Result<String, Error> {
+        self.call_with_options(OP_LOAD_ALL_WITH_OPTIONS, yaml, options_json)
+    }
+
+    fn dump_with_options(&self, data_json: &str, options_json: &str) -> Result<String, Error> {
+        self.call_with_options(OP_DUMP_WITH_OPTIONS, data_json, options_json)
+    }
+
+    fn dump_all_with_options(&self, data_json: &str, options_json: &str) -> Result<String, Error> {
+        self.call_with_options(OP_DUMP_ALL_WITH_OPTIONS, data_json, options_json)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+}
+
+impl Worker {
+    /// Start a worker and wait until it has loaded the library. Returns the
+    /// worker and the capabilities it reported.
+    fn spawn(config: &WorkerConfig) -> Result<(Self, Capabilities), Error> {
+        let program = config.program.clone().unwrap_or_else(default_program);
+        let mut command = Command::new(program);
+        command
+            .env(WORKER_ENV_VAR, "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+        if let Some(cpu_time) = config.cpu_time {
+            // RLIMIT_CPU counts whole seconds; round up so short limits
+            // still allow some work.
+            let secs = cpu_time.as_secs() + u64::from(cpu_time.subsec_nanos() > 0);
+            command.env(CPU_TIME_ENV_VAR, secs.to_string());
+        }
+        if let Some(bytes) = config.address_space {
+            command.env(ADDRESS_SPACE_ENV_VAR, bytes.to_string());
+        }
+        if let Some(library) = &config.library {
+            command.env(crate::LIBRARY_ENV_VAR, library);
+        }
+
+        let mut child = command.spawn()?;
+        let stdin = BufWriter::new(child.stdin.take().expect("stdin is piped"));
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let mut worker = Self {
+            child,
+            stdin,
+            responses: read_frames(stdout)?,
+        };
+
+        match worker.receive(config.timeout) {
+            Ok((STATUS_OK, bits)) => {
+                let bits = std::str::from_utf8(&bits).ok().and_then(|bits| bits.parse().ok());
+                if let Some(bits) = bits {
+                    return Ok((worker, capabilities_from_bits(bits)));
+                }
+                worker.kill().ok();
+                Err(Error::Ffi(
+                    "worker failed to start: it did not report its capabilities".to_string(),
+                ))
+            }
+            Ok((_, message)) => {
+                worker.kill().ok();
+                Err(Error::Ffi(format!(
+                    "worker failed to start: {}",
+                    String::from_utf8_lossy(&message)
+                )))
+            }
+            Err(error) if error.kind() == io::ErrorKind::TimedOut => {
+                worker.kill().ok();
+                Err(Error::WorkerTimedOut(config.timeout))
+            }
+            Err(_) => Err(worker.kill().map_or_else(Error::Io, Error::WorkerCrashed)),
+        }
+    }
+
+    /// Send a request and wait up to `timeout` for the response. The outer
+    /// error means the worker is gone or did not answer in time.
+    fn call(
+        &mut self,
+        op: u8,
+        input: &[u8],
+        timeout: Duration,
+    ) -> io::Result<Result<String, Error>> {
+        write_frame(&mut self.stdin, op, input)?;
+        self.stdin.flush()?;
+        let (status, payload) = self.receive(timeout)?;
+        let payload = String::from_utf8(payload).map_err(|e| e.utf8_error());
+        Ok(match (status, payload) {
+            (STATUS_OK, Ok(payload)) => Ok(payload),
+            (STATUS_UNSUPPORTED, Ok(what)) => Err(Error::Unsupported(unsupported_name(&what))),
+            (_, Ok(message)) => Err(Error::Ffi(message)),
+            (_, Err(e)) => Err(Error::Utf8(e)),
+        })
+    }
+
+    /// Wait up to `timeout` for the next frame from the worker.
+    fn receive(&self, timeout: Duration) -> io::Result<Frame> {
+        match self.responses.recv_timeout(timeout) {
+            Ok(frame) => frame,
+            Err(RecvTimeoutError::Timeout) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "the worker did not answer in time",
+            )),
+            Err(RecvTimeoutError::Disconnected) => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+
+    /// Stop the worker and return how it exited.
+    fn kill(&mut self) -> io::Result<std::process::ExitStatus> {
+        if let Some(status) = self.child.try_wait()? {
+            return Ok(status);
+        }
+        // It may still be flushing a crash report; give it a moment.
+        std::thread::sleep(Duration::from_millis(10));
+        if let Some(status) = self.child.try_wait()? {
+            return Ok(status);
+        }
+        let _ = self.child.kill();
+        self.child.wait()
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Serve requests on stdin and stdout as a worker process, then exit.
+///
+/// This is the `main` of a dedicated worker executable, such as the
+/// `yamlstar-worker` binary of this crate. The library is found as
+/// [`YAMLStar::new`] would find it.
+pub fn run_worker() -> ! {
+    // Keep the protocol pipe to ourselves: anything the library prints goes
+    // to stderr instead.
+    #[cfg(unix)]
+    let output = unsafe {
+        use std::os::fd::FromRawFd;
+
+        let fd = libc::dup(libc::STDOUT_FILENO);
+        if fd < 0 {
+            std::process::exit(70);
+        }
+        libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO);
+        std::fs::File::from_raw_fd(fd)
+    };
+    #[cfg(not(unix))]
+    let output = io::stdout();
+    let mut output = BufWriter::new(output);
+    let mut input = BufReader::new(io::stdin());
+
+    let cpu_time = env_limit(CPU_TIME_ENV_VAR);
+    if let Some(bytes) = env_limit(ADDRESS_SPACE_ENV_VAR) {
+        set_limit(Limit::AddressSpace, bytes);
+    }
+
+    let ys = YAMLStar::builder()
+        .backend(BackendKind::Library)
+        .version_policy(VersionPolicy::Any)
+        .build();
+    let ys = match ys {
+        Ok(ys) => ys,
+        Err(e) => {
+            let _ = write_frame(&mut output, STATUS_ERROR, e.to_string().as_bytes());
+            let _ = output.flush();
+            std::process::exit(1);
+        }
+    };
+    let capabilities = capabilities_to_bits(ys.capabilities()).to_string();
+    if write_frame(&mut output, STATUS_OK, capabilities.as_bytes()).is_err()
+        || output.flush().is_err()
+    {
+        std::process::exit(1);
+    }
+
+    loop {
+        let Ok((op, payload)) = read_frame(&mut input) else {
+            // The parent closed the pipe.
+            std::process::exit(0);
+        };
+        if let Some(secs) = cpu_time {
+            set_limit(Limit::CpuTime, cpu_seconds_used() + secs);
+        }
+
+        let response = match std::str::from_utf8(&payload) {
+            Ok(payload) => serve(&*ys.backend, op, payload),
+            Err(e) => Err(Error::Utf8(e)),
+        };
+        let written = match response {
+            Ok(response) => write_frame(&mut output, STATUS_OK, response.as_bytes()),
+            Err(Error::Unsupported(what)) => {
+                write_frame(&mut output, STATUS_UNSUPPORTED, what.as_bytes())
+            }
+            Err(e) => write_frame(&mut output, STATUS_ERROR, e.to_string().as_bytes()),
+        };
+        if written.is_err() || output.flush().is_err() {
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Serve as a worker if this process was started as one; otherwise return.
+///
+/// Call this at the top of `main` to let the current executable serve as
+/// the worker program of a [`crate::BackendKind::Worker`] pool, given to
+/// [`crate::Builder::worker_program`].
+pub fn run_worker_if_requested() {
+    if std::env::var_os(WORKER_ENV_VAR).is_some() {
+        run_worker();
+    }
+}
+
+/// Handle one request in a worker.
+fn serve(backend: &dyn Backend, op: u8, payload: &str) -> Result<String, Error> {
+    match op {
+        OP_LOAD => backend.load(payload),
+        OP_LOAD_ALL => backend.load_all(payload),
+        OP_DUMP => backend.dump(payload),
+        OP_DUMP_ALL => backend.dump_all(payload),
+        OP_VERSION => backend.version(),
+        OP_LOAD_TAGGED => backend.load_tagged(payload),
+        OP_LOAD_ALL_TAGGED => backend.load_all_tagged(payload),
+        OP_DUMP_TAGGED => backend.dump_tagged(payload),
+        OP_DUMP_ALL_TAGGED => backend.dump_all_tagged(payload),
+        OP_LOAD_WITH_OPTIONS..=OP_DUMP_ALL_WITH_OPTIONS => {
+            let (options, input) = payload
+                .split_once('\n')
+                .ok_or_else(|| Error::Ffi("worker request without options".to_string()))?;
+            match op {
+                OP_LOAD_WITH_OPTIONS => backend.load_with_options(input, options),
+                OP_LOAD_ALL_WITH_OPTIONS => backend.load_all_with_options(input, options),
+                OP_DUMP_WITH_OPTIONS => backend.dump_with_options(input, options),
+                _ => backend.dump_all_with_options(input, options),
+            }
+        }
+        _ => Err(Error::Ffi(format!("unknown worker request {op}"))),
+    }
+}
+
+/// The `yamlstar-worker` binary installed next to the current executable,
+/// or in the directory above it as for Cargo's test binaries, or else the
+/// one found on `PATH`.
+fn default_program() -> PathBuf {
+    let name = format!("yamlstar-worker{}", std::env::consts::EXE_SUFFIX);
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.ancestors()
+                .skip(1)
+                .take(2)
+                .map(|dir| dir.join(&name))
+                .find(|path| path.is_file())
+        })
+        .unwrap_or_else(|| PathBuf::from(&name))
+}
+
+/// Write a tagged, length-prefixed frame.
+fn write_frame(writer: &mut impl Write, tag: u8, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "worker request too large"))?;
+    writer.write_all(&[tag])?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(payload)
+}
+
+/// Read frames from a worker's response pipe on a thread of its own, until
+/// the pipe fails or closes or the worker is dropped.
+fn read_frames(stdout: ChildStdout) -> io::Result<Receiver<io::Result<Frame>>> {
+    let (sender, responses) = mpsc::channel();
+    std::thread::Builder::new()
+        .name("yamlstar-worker-reader".to_string())
+        .spawn(move || {
+            let mut stdout = BufReader::new(stdout);
+            loop {
+                let frame = read_frame(&mut stdout);
+                let failed = frame.is_err();
+                if sender.send(frame).is_err() || failed {
+                    break;
+                }
+            }
+        })?;
+    Ok(responses)
+}
+
+/// Read a tagged, length-prefixed frame.
+fn read_frame(reader: &mut impl Read) -> io::Result<Frame> {
+    let mut header = [0; 5];
+    reader.read_exact(&mut header)?;
+    let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]);
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload)?;
+    Ok((header[0], payload))
+}
+
+/// Map an operation name sent by a worker back to its static name.
+fn unsupported_name(what: &str) -> &'static str {
+    [
+        "load",
+        "load_all",
+        "dump",
+        "dump_all",
+        "version",
+        "per_thread",
+        "load_tagged",
+        "load_all_tagged",
+        "dump_tagged",
+        "dump_all_tagged",
+        "load_with_options",
+        "load_all_with_options",
+        "dump_with_options",
+        "dump_all_with_options",
+    ]
+        .into_iter()
+        .find(|name| *name == what)
+        .unwrap_or("unknown")
+}
+
+/// Pack [`Capabilities`] into bits, in field order.
+fn capabilities_to_bits(caps: Capabilities) -> u16 {
+    [
+        caps.load,
+        caps.load_all,
+        caps.dump,
+        caps.dump_all,
+        caps.version,
+        caps.free,
+        caps.per_thread,
+        caps.tagged,
+        caps.options,
+    ]
+    .iter()
+    .enumerate()
+    .fold(0, |bits, (i, &set)| bits | (u16::from(set) << i))
+}
+
+/// Unpack [`Capabilities`] packed by [`capabilities_to_bits`].
+fn capabilities_from_bits(bits: u16) -> Capabilities {
+    let bit = |i: u8| bits & (1 << i) != 0;
+    Capabilities {
+        load: bit(0),
+        load_all: bit(1),
+        dump: bit(2),
+        dump_all: bit(3),
+        version: bit(4),
+        free: bit(5),
+        per_thread: bit(6),
+        tagged: bit(7),
+        options: bit(8),
+    }
+}
+
+/// Read a numeric limit from the environment.
+fn env_limit(name: &str) -> Option<u64> {
+    std::env::var(name).ok()?.parse().ok()
+}
+
+/// A resource limit applied inside a worker.
+#[derive(Clone, Copy)]
+enum Limit {
+    /// `RLIMIT_CPU`, in seconds.
+    CpuTime,
+    /// `RLIMIT_AS`, in bytes.
+    AddressSpace,
+}
+
+/// Set the soft value of a resource limit, where supported.
+#[cfg(unix)]
+fn set_limit(limit: Limit, value: u64) {
+    let resource = match limit {
+        Limit::CpuTime => libc::RLIMIT_CPU,
+        Limit::AddressSpace => libc::RLIMIT_AS,
+    };
+    let mut rlim = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    unsafe {
+        if libc::getrlimit(resource, &raw mut rlim) != 0 {
+            return;
+        }
+        rlim.rlim_cur = libc::rlim_t::try_from(value)
+            .unwrap_or(libc::RLIM_INFINITY)
+            .min(rlim.rlim_max);
+        libc::setrlimit(resource, &raw const rlim);
+    }
+}
+
+/// Set the soft value of a resource limit, where supported.
+#[cfg(not(unix))]
+fn set_limit(_limit: Limit, _value: u64) {}
+
+/// CPU time used by this process so far, in whole seconds.
+#[cfg(unix)]
+fn cpu_seconds_used() -> u64 {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+        return 0;
+    }
+    let usage = unsafe { usage.assume_init() };
+    let secs = usage.ru_utime.tv_sec + usage.ru_stime.tv_sec;
+    u64::try_from(secs).unwrap_or(0)
+}
+
+/// CPU time used by this process so far, in whole seconds.
+#[cfg(not(unix))]
+fn cpu_seconds_used() -> u64 {
+    0
+}
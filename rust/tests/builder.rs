@@ -0,0 +1,68 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::path::{Path, PathBuf};
+
+/// The development build of the library.
+fn dev_library() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../libyamlstar/lib")
+        .join(format!("libyamlstar.so.{}", yamlstar::LIBYAMLSTAR_VERSION))
+}
+
+#[test]
+fn builder_defaults() {
+    let ys = yamlstar::YAMLStar::builder().build().unwrap();
+    assert_eq!(ys.load::<String>("hello").unwrap(), "hello");
+}
+
+#[test]
+fn builder_explicit_library_path() {
+    let ys = yamlstar::YAMLStar::builder()
+        .library_path("/nonexistent/libyamlstar.so")
+        .library_path(dev_library())
+        .build()
+        .unwrap();
+    assert_eq!(ys.load::<i64>("42").unwrap(), 42);
+}
+
+#[test]
+fn builder_search_dirs() {
+    let ys = yamlstar::YAMLStar::builder()
+        .search_dirs(["/nonexistent", dev_library().parent().unwrap().to_str().unwrap()])
+        .build()
+        .unwrap();
+    assert!(ys.load::<bool>("true").unwrap());
+}
+
+#[test]
+fn not_found_lists_tried_paths() {
+    let err = yamlstar::Error::NotFound(vec![
+        PathBuf::from("/opt/a/libyamlstar.so"),
+        PathBuf::from("/opt/b/libyamlstar.so"),
+    ]);
+    let message = err.to_string();
+    assert!(message.contains("/opt/a/libyamlstar.so"));
+    assert!(message.contains("/opt/b/libyamlstar.so"));
+    let debug = format!("{err:?}");
+    assert!(debug.contains("YAMLSTAR_LIBRARY"));
+    assert!(debug.contains("\n  /opt/b/libyamlstar.so"));
+}
+
+#[test]
+fn current_library_has_every_capability() {
+    let ys = yamlstar::YAMLStar::new().unwrap();
+    let caps = ys.capabilities();
+    assert!(caps.load && caps.load_all && caps.dump && caps.dump_all);
+    assert!(caps.version && caps.free && caps.per_thread);
+    assert!(caps.tagged && caps.options);
+}
+
+#[test]
+fn unsupported_message_names_capability() {
+    let err = yamlstar::Error::Unsupported("dump");
+    assert_eq!(
+        err.to_string(),
+        "The loaded libyamlstar does not support 'dump'"
+    );
+}
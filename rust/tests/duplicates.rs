@@ -0,0 +1,206 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use yamlstar::{
+    Capabilities, Diagnostic, Diagnostics, DuplicateKey, DuplicateKeys, Error, MockBackend,
+    MockOp, Value, YAMLStar,
+};
+
+/// The tagged response for a stream whose second document has `timeout`
+/// twice in a nested mapping, and `max` twice in a mapping inside that.
+const RESPONSE: &str = r#"{"data":[
+    {"!map":[["name","ok"]]},
+    {"!map":[["server",{"!map":[
+        ["timeout",5],
+        ["retries",{"!map":[["max",3],["max",4]]}],
+        ["timeout",50]
+    ]}]]}
+]}"#;
+
+/// An instance with `policy` that answers every load with [`RESPONSE`].
+fn mock(policy: DuplicateKeys) -> YAMLStar {
+    YAMLStar::builder()
+        .duplicate_keys(policy)
+        .build_with_backend(
+            MockBackend::new()
+                .with_capabilities(Capabilities::ALL)
+                .respond(MockOp::LoadTagged, r#"{"data":{"!map":[["a",1],["b",2],["a",3]]}}"#)
+                .respond(MockOp::LoadAllTagged, RESPONSE),
+        )
+        .unwrap()
+}
+
+#[test]
+fn duplicate_keys_error_gives_both_entries() {
+    let err = mock(DuplicateKeys::Error)
+        .load_all::<Value>("...")
+        .unwrap_err();
+    let Error::DuplicateKey(duplicate) = &err else {
+        panic!("expected Error::DuplicateKey, got {err:?}");
+    };
+    assert_eq!(
+        *duplicate,
+        DuplicateKey {
+            key: "max".into(),
+            path: "/server/retries".into(),
+            document: 1,
+            first: 0,
+            second: 1,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Duplicate key max in the mapping at /server/retries in document 1 (entries 0 and 1)"
+    );
+
+    let err = mock(DuplicateKeys::Error).load::<Value>("...").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Duplicate key a in the mapping at / in document 0 (entries 0 and 2)"
+    );
+}
+
+#[test]
+fn duplicate_keys_first_and_last_wins() {
+    let docs: Vec<Value> = mock(DuplicateKeys::First).load_all("...").unwrap();
+    assert_eq!(docs[1]["server"]["timeout"], 5);
+    assert_eq!(docs[1]["server"]["retries"]["max"], 3);
+
+    for policy in [DuplicateKeys::Last, DuplicateKeys::Warn] {
+        let docs: Vec<Value> = mock(policy).load_all("...").unwrap();
+        assert_eq!(docs[1]["server"]["timeout"], 50);
+        assert_eq!(docs[1]["server"]["retries"]["max"], 4);
+        let keys: Vec<_> = docs[1]["server"]
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(keys, [Value::from("timeout"), Value::from("retries")]);
+    }
+}
+
+#[test]
+fn duplicate_keys_warn_collects_diagnostics() {
+    let ys = mock(DuplicateKeys::Warn);
+    let _: Vec<Value> = ys.load_all("...").unwrap();
+    let _: Value = ys.load("...").unwrap();
+    assert_eq!(ys.diagnostics().len(), 3);
+    let warnings = ys.diagnostics().take();
+    assert!(ys.diagnostics().is_empty());
+    let found: Vec<_> = warnings
+        .iter()
+        .map(|warning| {
+            let Diagnostic::DuplicateKey(d) = warning else {
+                panic!("expected a duplicate key, got {warning:?}");
+            };
+            (d.key.as_str(), d.path.as_str(), d.document, d.first, d.second)
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("max", "/server/retries", 1, 0, 1),
+            ("timeout", "/server", 1, 0, 2),
+            ("a", "", 0, 0, 2),
+        ]
+    );
+    assert_eq!(
+        warnings[1].to_string(),
+        "Duplicate key timeout in the mapping at /server in document 1 (entries 0 and 2)"
+    );
+}
+
+#[test]
+fn duplicate_keys_shared_collector() {
+    let diagnostics = Diagnostics::new();
+    let ys = YAMLStar::builder()
+        .duplicate_keys(DuplicateKeys::Warn)
+        .diagnostics(diagnostics.clone())
+        .build_with_backend(MockBackend::new().with_capabilities(Capabilities::ALL).respond(
+            MockOp::LoadTagged,
+            r#"{"data":{"!tag":["!config",{"!map":[[1,"a"],[{"!float":"1.0"},"b"],[true,"c"]]}]}}"#,
+        ))
+        .unwrap();
+    // Keys of different types are different keys.
+    let value: Value = ys.load("...").unwrap();
+    assert_eq!(value.untagged().as_mapping().unwrap().len(), 3);
+    assert!(diagnostics.is_empty());
+
+    let ys = YAMLStar::builder()
+        .duplicate_keys(DuplicateKeys::Warn)
+        .diagnostics(diagnostics.clone())
+        .build_with_backend(MockBackend::new().with_capabilities(Capabilities::ALL).respond(
+            MockOp::LoadTagged,
+            r#"{"data":[{"!tag":["!config",{"!map":[[[1,2],"a"],[[1,2],"b"]]}]}]}"#,
+        ))
+        .unwrap();
+    let _: Value = ys.load("...").unwrap();
+    assert_eq!(
+        diagnostics.take(),
+        [Diagnostic::DuplicateKey(DuplicateKey {
+            key: "[1, 2]".into(),
+            path: "/0".into(),
+            document: 0,
+            first: 0,
+            second: 1,
+        })]
+    );
+}
+
+#[test]
+fn duplicate_keys_in_large_mappings() {
+    let pairs = (0..20_000)
+        .chain([123])
+        .map(|i| format!(r#"["key{i}",{i}]"#))
+        .collect::<Vec<_>>();
+    let response = format!(r#"{{"data":{{"!map":[{}]}}}}"#, pairs.join(","));
+    let ys = YAMLStar::builder()
+        .duplicate_keys(DuplicateKeys::Error)
+        .build_with_backend(
+            MockBackend::new()
+                .with_capabilities(Capabilities::ALL)
+                .respond(MockOp::LoadTagged, &response),
+        )
+        .unwrap();
+    let start = std::time::Instant::now();
+    let err = ys.load::<Value>("...").unwrap_err();
+    assert!(start.elapsed() < std::time::Duration::from_secs(2));
+    let Error::DuplicateKey(duplicate) = &err else {
+        panic!("expected Error::DuplicateKey, got {err:?}");
+    };
+    assert_eq!((duplicate.first, duplicate.second), (123, 20_000));
+}
+
+#[test]
+fn duplicate_keys_need_tagged_exports() {
+    let result = YAMLStar::builder()
+        .duplicate_keys(DuplicateKeys::Error)
+        .build_with_backend(MockBackend::new().with_capabilities(Capabilities {
+            tagged: false,
+            ..Capabilities::ALL
+        }));
+    assert!(matches!(result, Err(Error::Unsupported("duplicate_keys"))));
+}
+
+#[test]
+fn duplicate_keys_through_library() {
+    #[derive(Debug, serde::Deserialize)]
+    struct Config {
+        timeout: u32,
+    }
+
+    let yaml = "timeout: 5\nretries: 3\ntimeout: 50\n";
+    let ys = YAMLStar::builder()
+        .duplicate_keys(DuplicateKeys::Error)
+        .build()
+        .unwrap();
+    let err = ys.load::<Config>(yaml).unwrap_err();
+    assert!(matches!(err, Error::DuplicateKey(DuplicateKey { first: 0, second: 2, .. })));
+
+    let ys = YAMLStar::builder()
+        .duplicate_keys(DuplicateKeys::First)
+        .build()
+        .unwrap();
+    assert_eq!(ys.load::<Config>(yaml).unwrap().timeout, 5);
+}
@@ -0,0 +1,131 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use yamlstar::{
+    from_value, to_value, Capabilities, Error, MockBackend, MockOp, TaggedValue, Value, YAMLStar,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle { r: f64 },
+    Square(f64),
+    Line(i64, i64),
+    #[serde(rename = "!vault")]
+    Secret(String),
+}
+
+#[test]
+fn enums_load_from_tags() {
+    let ys = YAMLStar::with_backend(MockBackend::new().with_capabilities(Capabilities::ALL).respond(
+        MockOp::LoadTagged,
+        r#"{"data":[
+            "Empty",
+            {"!tag":["!Circle",{"!map":[["r",{"!float":"1.0"}]]}]},
+            {"!tag":["!Square","2.5"]},
+            {"!tag":["!Line",[1,2]]},
+            {"!tag":["!vault","abc"]},
+            {"!map":[["Square",{"!float":"3.0"}]]},
+            {"!map":[["!Square",{"!float":"4.0"}]]}
+        ]}"#,
+    ));
+    let shapes: Vec<Shape> = ys.load("...").unwrap();
+    assert_eq!(
+        shapes,
+        [
+            Shape::Empty,
+            Shape::Circle { r: 1.0 },
+            Shape::Square(2.5),
+            Shape::Line(1, 2),
+            Shape::Secret("abc".into()),
+            Shape::Square(3.0),
+            Shape::Square(4.0),
+        ]
+    );
+}
+
+#[test]
+fn enums_type_tagged_scalar_text() {
+    let tagged = |tag: &str, text: &str| Value::from(TaggedValue::new(tag, text));
+    assert_eq!(from_value::<Shape>(tagged("!Square", "2")).unwrap(), Shape::Square(2.0));
+    assert_eq!(
+        from_value::<Shape>(tagged("!vault", "2")).unwrap(),
+        Shape::Secret("2".into())
+    );
+    let err = from_value::<Shape>(tagged("!Square", "wide")).unwrap_err();
+    assert!(err.to_string().contains("invalid type: string \"wide\""), "{err}");
+}
+
+#[test]
+fn enums_unknown_tag_is_an_error() {
+    let value = Value::from(TaggedValue::new("!Triangle", 1));
+    let err = from_value::<Shape>(value).unwrap_err();
+    assert!(err.to_string().contains("unknown variant `!Triangle`"), "{err}");
+}
+
+#[test]
+fn enums_dump_as_tags() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::DumpTagged, "")
+            .respond_data(MockOp::DumpTagged, ""),
+    );
+    let ys = YAMLStar::builder()
+        .tagged_enums(true)
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    ys.dump(&Shape::Circle { r: 1.5 }).unwrap();
+    ys.dump(&[Shape::Empty, Shape::Square(2.0), Shape::Line(1, 2)]).unwrap();
+    let calls = mock.calls();
+    assert_eq!(calls[0].1, r#"{"!tag":["!Circle",{"!map":[["r",{"!float":"1.5"}]]}]}"#);
+    assert_eq!(
+        calls[1].1,
+        r#"["Empty",{"!tag":["!Square",{"!float":"2.0"}]},{"!tag":["!Line",[1,2]]}]"#
+    );
+}
+
+#[test]
+fn enums_dump_as_tags_needs_tagged_exports() {
+    let result = YAMLStar::builder()
+        .tagged_enums(true)
+        .build_with_backend(MockBackend::new());
+    assert!(matches!(result, Err(Error::Unsupported("tagged_enums"))));
+}
+
+#[test]
+fn enums_dump_as_maps() {
+    let value = to_value(&[Shape::Empty, Shape::Square(2.0)]).unwrap();
+    assert_eq!(value[0], "Empty");
+    assert_eq!(value[1]["Square"], 2.0);
+
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::DumpTagged, ""),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    ys.dump(&Shape::Circle { r: 1.5 }).unwrap();
+    let (_, data) = mock.calls().pop().unwrap();
+    assert_eq!(data, r#"{"!map":[["Circle",{"!map":[["r",{"!float":"1.5"}]]}]]}"#);
+}
+
+#[test]
+fn enums_roundtrip_through_library() {
+    let ys = YAMLStar::new().unwrap();
+    let shapes: Vec<Shape> = ys.load("- Empty\n- !Circle {r: 1.0}\n- !Square 2.0\n").unwrap();
+    assert_eq!(
+        shapes,
+        [Shape::Empty, Shape::Circle { r: 1.0 }, Shape::Square(2.0)]
+    );
+    let yaml = ys.dump(&shapes).unwrap();
+    assert_eq!(ys.load::<Vec<Shape>>(&yaml).unwrap(), shapes);
+
+    let ys = YAMLStar::builder().tagged_enums(true).build().unwrap();
+    let yaml = ys.dump(&shapes).unwrap();
+    assert_eq!(yaml, "- Empty\n- !Circle\n  r: 1.0\n- !Square 2.0\n");
+    assert_eq!(ys.load::<Vec<Shape>>(&yaml).unwrap(), shapes);
+}
@@ -0,0 +1,190 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use yamlstar::{
+    Capabilities, EnvProvider, Error, MockBackend, MockOp, Schema, StdEnv, Value, YAMLStar,
+};
+
+/// An environment with a few variables set.
+fn env() -> HashMap<String, String> {
+    [("HOST", "db.local"), ("PORT", "5432"), ("EMPTY", ""), ("DEBUG", "true")]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+/// An instance that answers every load with `response`.
+fn mock(response: &str) -> YAMLStar {
+    YAMLStar::builder()
+        .env_provider(env())
+        .build_with_backend(
+            MockBackend::new()
+                .with_capabilities(Capabilities::ALL)
+                .respond(MockOp::LoadTagged, response)
+                .respond(MockOp::LoadAllTagged, response),
+        )
+        .unwrap()
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Database {
+    url: String,
+    port: u16,
+    debug: bool,
+    user: String,
+}
+
+#[test]
+fn env_tags_are_typed_and_strings_interpolated() {
+    let ys = mock(
+        r#"{"data":{"!map":[
+            ["url","postgres://${HOST}:${PORT}/app"],
+            ["port",{"!tag":["!env","PORT"]}],
+            ["debug",{"!tag":["!env","DEBUG"]}],
+            ["user",{"!tag":["!env","DB_USER:-admin"]}]
+        ]}}"#,
+    );
+    let db: Database = ys.load("...").unwrap();
+    assert_eq!(
+        db,
+        Database {
+            url: "postgres://db.local:5432/app".into(),
+            port: 5432,
+            debug: true,
+            user: "admin".into(),
+        }
+    );
+}
+
+#[test]
+fn env_defaults_escapes_and_literals() {
+    let ys = mock(
+        r#"{"data":[
+            "${EMPTY:-fallback}",
+            "${MISSING:-}",
+            "cost: $${HOST} and $5",
+            "${HOST",
+            {"!tag":["!env","EMPTY"]},
+            {"!tag":["!other","${HOST}"]}
+        ]}"#,
+    );
+    let value: Value = ys.load("...").unwrap();
+    assert_eq!(value[0], "fallback");
+    assert_eq!(value[1], "");
+    assert_eq!(value[2], "cost: ${HOST} and $5");
+    assert_eq!(value[3], "${HOST");
+    assert_eq!(value[4], Value::Null);
+    assert_eq!(value[5].tag(), Some("!other"));
+    assert_eq!(*value[5].untagged(), "db.local");
+}
+
+#[test]
+fn env_missing_variable_has_location() {
+    let ys = mock(r#"{"data":[{"!map":[["a",1]]},{"!map":[["db",{"!map":[["password","${DB_PASSWORD}"]]}]]}]}"#);
+    let err = ys.load_all::<Value>("...").unwrap_err();
+    let Error::Env {
+        name,
+        path,
+        document,
+    } = &err
+    else {
+        panic!("expected Error::Env, got {err:?}");
+    };
+    assert_eq!((name.as_str(), path.as_str(), *document), ("DB_PASSWORD", "/db/password", 1));
+    assert_eq!(
+        err.to_string(),
+        "Environment variable DB_PASSWORD is not set (at /db/password in document 1)"
+    );
+}
+
+#[test]
+fn env_tags_are_typed_by_the_schema() {
+    let env: HashMap<String, String> = [
+        ("FLAG", "yes"),
+        ("MODE", "0755"),
+        ("TIME", "1:30"),
+        ("SIZE", "1_000"),
+        ("RATIO", "2.5"),
+        ("NONE", "null"),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value.to_string()))
+    .collect();
+    let typed = |schema| -> Vec<Value> {
+        let ys = YAMLStar::builder()
+            .schema(schema)
+            .env_provider(env.clone())
+            .build_with_backend(MockBackend::new().with_capabilities(Capabilities::ALL).respond(
+                MockOp::LoadTagged,
+                r#"{"data":[
+                    {"!tag":["!env","FLAG"]},
+                    {"!tag":["!env","MODE"]},
+                    {"!tag":["!env","TIME"]},
+                    {"!tag":["!env","SIZE"]},
+                    {"!tag":["!env","RATIO"]},
+                    {"!tag":["!env","NONE"]}
+                ]}"#,
+            ))
+            .unwrap();
+        ys.load("...").unwrap()
+    };
+    let text = |text: &str| Value::from(text);
+    assert_eq!(
+        typed(Schema::Failsafe),
+        [text("yes"), text("0755"), text("1:30"), text("1_000"), text("2.5"), text("null")]
+    );
+    assert_eq!(
+        typed(Schema::Json),
+        [text("yes"), text("0755"), text("1:30"), text("1_000"), 2.5.into(), Value::Null]
+    );
+    assert_eq!(
+        typed(Schema::Core),
+        [text("yes"), 755.into(), text("1:30"), text("1_000"), 2.5.into(), Value::Null]
+    );
+    assert_eq!(
+        typed(Schema::Yaml11),
+        [true.into(), 0o755.into(), 90.into(), 1000.into(), 2.5.into(), Value::Null]
+    );
+}
+
+#[test]
+fn env_off_by_default() {
+    let ys = YAMLStar::with_backend(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(MockOp::LoadTagged, r#"{"data":{"!tag":["!env","PORT"]}}"#),
+    );
+    let value: Value = ys.load("...").unwrap();
+    assert_eq!(value.tag(), Some("!env"));
+}
+
+#[test]
+fn env_needs_tagged_exports() {
+    let result = YAMLStar::builder()
+        .env(true)
+        .build_with_backend(MockBackend::new().with_capabilities(Capabilities {
+            tagged: false,
+            ..Capabilities::ALL
+        }));
+    assert!(matches!(result, Err(Error::Unsupported("env"))));
+}
+
+#[test]
+fn env_std_provider_reads_process_environment() {
+    assert_eq!(StdEnv.var("YAMLSTAR_SURELY_UNSET_VARIABLE"), None);
+    assert!(StdEnv.var("PATH").is_some());
+}
+
+#[test]
+fn env_through_library() {
+    let ys = YAMLStar::builder().env_provider(env()).build().unwrap();
+    let value: Value = ys
+        .load("port: !env PORT\nurl: http://${HOST}:${PORT}/\nname: !env NAME:-app\n")
+        .unwrap();
+    assert_eq!(value["port"], 5432);
+    assert_eq!(value["url"], "http://db.local:5432/");
+    assert_eq!(value["name"], "app");
+}
@@ -0,0 +1,132 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use yamlstar::{Capabilities, Error, MockBackend, MockOp, Value, YAMLStar};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Limits {
+    low: f64,
+    high: f64,
+    step: f64,
+}
+
+/// A library with the operations with options but not the tagged ones, so
+/// that plain JSON goes through the former.
+fn options_backend() -> MockBackend {
+    MockBackend::new().with_capabilities(Capabilities {
+        tagged: false,
+        ..Capabilities::ALL
+    })
+}
+
+#[test]
+fn floats_load_plain_sentinels() {
+    let ys = YAMLStar::with_backend(
+        options_backend()
+            .respond(
+                MockOp::Load,
+                r#"{"data":[{"!float":".inf"},{"!float":"-.inf"},{"!float":".nan"},1.5]}"#,
+            )
+            .respond(
+                MockOp::Load,
+                r#"{"data":{"low":{"!float":"-.inf"},"high":{"!float":".Inf"},"step":0.5}}"#,
+            ),
+    );
+    let floats: Vec<f64> = ys.load("...").unwrap();
+    assert_eq!(floats[0], f64::INFINITY);
+    assert_eq!(floats[1], f64::NEG_INFINITY);
+    assert!(floats[2].is_nan());
+    assert_eq!(floats[3], 1.5);
+    let limits: Limits = ys.load("...").unwrap();
+    assert_eq!(limits.low, f64::NEG_INFINITY);
+    assert_eq!(limits.high, f64::INFINITY);
+    assert_eq!(limits.step, 0.5);
+}
+
+#[test]
+fn floats_dump_plain_sentinels() {
+    let mock = Arc::new(
+        options_backend()
+            .respond_data(MockOp::Dump, "...")
+            .respond_data(MockOp::DumpAll, "..."),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    let limits = Limits {
+        low: f64::NEG_INFINITY,
+        high: f64::INFINITY,
+        step: 0.5,
+    };
+    ys.dump(&limits).unwrap();
+    ys.dump_all(&[f64::NAN, 2.0]).unwrap();
+    let calls = mock.calls();
+    assert_eq!(
+        calls[0].1,
+        r#"{"low":{"!float":"-.inf"},"high":{"!float":".inf"},"step":0.5}"#
+    );
+    assert_eq!(calls[1].1, r#"[{"!float":".nan"},2.0]"#);
+    assert!(mock.options().iter().all(Option::is_some));
+}
+
+#[test]
+fn floats_plain_keys_are_not_sentinels() {
+    let mock = Arc::new(
+        options_backend()
+            .respond(
+                MockOp::Load,
+                r#"{"data":{"!!float":".inf","a":{"!float":".nan"},"!!vault":{"!!float":"x"}}}"#,
+            )
+            .respond_data(MockOp::Dump, "..."),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    let value: Value = ys.load("...").unwrap();
+    assert_eq!(value["!float"], ".inf");
+    assert!(value["a"].as_f64().unwrap().is_nan());
+    assert_eq!(value["!vault"]["!float"], "x");
+    ys.dump(&value).unwrap();
+    assert_eq!(
+        mock.calls()[1].1,
+        r#"{"!!float":".inf","a":{"!float":".nan"},"!!vault":{"!!float":"x"}}"#
+    );
+}
+
+#[test]
+fn floats_plain_exports_have_no_sentinels() {
+    // The plain exports of a library without those with options, or of an
+    // older one, neither mark floats nor escape keys.
+    let mock = Arc::new(
+        MockBackend::new()
+            .respond(MockOp::Load, r#"{"data":{"!x":1,"!float":".inf","a":{"!float":".nan"}}}"#)
+            .respond_data(MockOp::Dump, "..."),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    let value: Value = ys.load("...").unwrap();
+    assert_eq!(value["!x"], 1);
+    assert_eq!(value["!float"], ".inf");
+    assert_eq!(value["a"]["!float"], ".nan");
+    ys.dump(&value).unwrap();
+    assert_eq!(
+        mock.calls()[1].1,
+        r#"{"!x":1,"!float":".inf","a":{"!float":".nan"}}"#
+    );
+    assert_eq!(mock.options(), [None, None]);
+    let err = ys.dump(&f64::INFINITY).unwrap_err();
+    assert!(matches!(&err, Error::Serde(e) if e.to_string().contains(".inf")), "{err:?}");
+}
+
+#[test]
+fn floats_roundtrip_through_library() {
+    let ys = YAMLStar::new().unwrap();
+    assert_eq!(ys.load::<f64>(".inf").unwrap(), f64::INFINITY);
+    assert_eq!(ys.load::<f64>("-.Inf").unwrap(), f64::NEG_INFINITY);
+    assert!(ys.load::<f64>(".nan").unwrap().is_nan());
+    assert_eq!(ys.dump(&f64::INFINITY).unwrap(), ".inf\n");
+    assert_eq!(ys.dump(&f64::NEG_INFINITY).unwrap(), "-.inf\n");
+    assert_eq!(ys.dump(&f64::NAN).unwrap(), ".nan\n");
+    let limits: Limits = ys.load("low: -.inf\nhigh: .inf\nstep: 0.5\n").unwrap();
+    assert_eq!(ys.dump(&limits).unwrap(), "low: -.inf\nhigh: .inf\nstep: 0.5\n");
+    let yaml = "'!float': .inf\n";
+    assert_eq!(ys.dump(&ys.load::<Value>(yaml).unwrap()).unwrap(), yaml);
+}
@@ -0,0 +1,139 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use yamlstar::{Capabilities, Error, MockBackend, MockOp, Value, YAMLStar};
+
+/// Write `files`, relative paths and their text, under a new directory named
+/// for `test`, and return its canonical path.
+fn fixture(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "yamlstar-include-{test}-{}",
+        std::process::id()
+    ));
+    for (path, text) in files {
+        let file = dir.join(path);
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(file, text).unwrap();
+    }
+    dir.canonicalize().unwrap()
+}
+
+/// The include options a mock received for each call.
+fn sent_includes(mock: &MockBackend) -> Vec<serde_json::Value> {
+    mock.options()
+        .into_iter()
+        .map(|options| {
+            let options: serde_json::Value =
+                serde_json::from_str(&options.expect("options were sent")).unwrap();
+            options["include"].clone()
+        })
+        .collect()
+}
+
+#[test]
+fn include_root_and_file_are_sent() {
+    let root = fixture("options", &[("main.yaml", "a: !include a.yaml\n")]);
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::LoadTagged, &1)
+            .respond_data(MockOp::LoadTagged, &2),
+    );
+    let ys = YAMLStar::builder()
+        .include_root(&root)
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    ys.load_file::<Value>(root.join("main.yaml")).unwrap();
+    ys.load::<Value>("!include a.yaml").unwrap();
+
+    let root_text = root.to_string_lossy();
+    let file_text = root.join("main.yaml").to_string_lossy().into_owned();
+    assert_eq!(
+        sent_includes(&mock),
+        [
+            serde_json::json!({"root": root_text, "file": file_text}),
+            serde_json::json!({"root": root_text}),
+        ]
+    );
+    assert_eq!(mock.calls()[0].1, "a: !include a.yaml\n");
+}
+
+#[test]
+fn include_file_outside_root_is_rejected() {
+    let root = fixture("outside", &[("inner/main.yaml", "a: 1\n"), ("other.yaml", "b: 2\n")]);
+    let ys = YAMLStar::builder()
+        .include_root(root.join("inner"))
+        .build_with_backend(MockBackend::new().with_capabilities(Capabilities::ALL))
+        .unwrap();
+    let err = ys.load_file::<Value>(root.join("inner/../other.yaml")).unwrap_err();
+    assert!(matches!(&err, Error::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied));
+    assert!(err.to_string().contains("is outside the include root"));
+}
+
+#[test]
+fn include_root_must_exist() {
+    let result = YAMLStar::builder()
+        .include_root("/nonexistent/yamlstar/root")
+        .build_with_backend(MockBackend::new().with_capabilities(Capabilities::ALL));
+    assert!(matches!(result, Err(Error::Io(_))));
+}
+
+#[test]
+fn include_root_needs_options_capability() {
+    let result = YAMLStar::builder()
+        .include_root(std::env::temp_dir())
+        .build_with_backend(MockBackend::new().with_capabilities(Capabilities {
+            options: false,
+            ..Capabilities::ALL
+        }));
+    assert!(matches!(result, Err(Error::Unsupported("include_root"))));
+}
+
+#[test]
+fn load_file_without_include_root() {
+    let root = fixture("plain", &[("main.yaml", "a: 1\n")]);
+    let mock = Arc::new(MockBackend::new().respond_data(MockOp::Load, &1));
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    ys.load_file::<Value>(root.join("main.yaml")).unwrap();
+    assert_eq!(mock.options(), [None]);
+    assert!(matches!(
+        ys.load_file::<Value>(root.join("missing.yaml")),
+        Err(Error::Io(_))
+    ));
+}
+
+/// An instance that follows includes under `root`.
+fn library(root: &Path) -> YAMLStar {
+    YAMLStar::builder().include_root(root).build().unwrap()
+}
+
+#[test]
+fn include_through_library() {
+    let root = fixture(
+        "library",
+        &[
+            ("main.yaml", "db: !include common/db.yaml\n"),
+            ("common/db.yaml", "host: localhost\nport: !include port.yaml\n"),
+            ("common/port.yaml", "5432\n"),
+            ("loop/a.yaml", "b: !include b.yaml\n"),
+            ("loop/b.yaml", "a: !include a.yaml\n"),
+        ],
+    );
+    let ys = library(&root);
+    let value: Value = ys.load_file(root.join("main.yaml")).unwrap();
+    assert_eq!(value["db"]["host"], "localhost");
+    assert_eq!(value["db"]["port"], 5432);
+
+    let err = ys.load::<Value>("!include ../escape.yaml").unwrap_err();
+    assert!(err.to_string().contains("escapes the include root"));
+
+    let err = ys.load_file::<Value>(root.join("loop/a.yaml")).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Include cycle (include chain: loop/a.yaml -> loop/b.yaml -> loop/a.yaml)"));
+}
@@ -0,0 +1,130 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::sync::Arc;
+
+use yamlstar::{Capabilities, MockBackend, MockOp, Number, Value, YAMLStar};
+
+/// Integers just past each Rust integer type, in the tagged encoding.
+const BIG_RESPONSE: &str = r#"{"data":[{"!int":"18446744073709551615"},{"!int":"-170141183460469231731687303715884105728"},{"!int":"123456789012345678901234567890123456789012"},5]}"#;
+
+#[test]
+fn integers_parse_as_numbers() {
+    let n: Number = "+007".parse().unwrap();
+    assert_eq!(n.as_str(), "7");
+    assert_eq!(n.as_i64(), Some(7));
+    assert_eq!("-0".parse::<Number>().unwrap().as_str(), "0");
+    assert_eq!(Number::from(u128::MAX).as_u128(), Some(u128::MAX));
+    assert_eq!(Number::from(i64::MIN).as_u64(), None);
+    assert!("1.5".parse::<Number>().is_err());
+    assert!("".parse::<Number>().is_err());
+    assert!("-".parse::<Number>().is_err());
+}
+
+#[test]
+fn integers_need_arbitrary_precision_to_load() {
+    let ys = YAMLStar::with_backend(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(MockOp::LoadTagged, BIG_RESPONSE),
+    );
+    let err = ys.load::<Value>("...").unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("integer 18446744073709551615 does not fit in an i64"));
+}
+
+#[test]
+fn integers_dump_beyond_i64() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::DumpTagged, "...")
+            .respond_data(MockOp::DumpTagged, "..."),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    ys.dump(&(u64::MAX, i128::MIN, 5_u64)).unwrap();
+    let huge: Number = "123456789012345678901234567890123456789012".parse().unwrap();
+    ys.dump(&huge).unwrap();
+    let calls = mock.calls();
+    assert_eq!(
+        calls[0].1,
+        r#"[{"!int":"18446744073709551615"},{"!int":"-170141183460469231731687303715884105728"},5]"#
+    );
+    assert_eq!(
+        calls[1].1,
+        r#"{"!int":"123456789012345678901234567890123456789012"}"#
+    );
+}
+
+#[test]
+fn integers_load_hex_and_octal_through_library() {
+    let ys = YAMLStar::new().unwrap();
+    assert_eq!(ys.load::<i64>("0x7FFFFFFFFFFFFFFF").unwrap(), i64::MAX);
+    assert_eq!(ys.load::<i64>("0o777777777777777777777").unwrap(), i64::MAX);
+    assert!(ys.load::<Value>("0xFFFFFFFFFFFFFFFF").is_err());
+}
+
+#[cfg(feature = "arbitrary_precision")]
+#[test]
+fn integers_load_beyond_i64() {
+    let ys = YAMLStar::builder()
+        .arbitrary_precision(true)
+        .build_with_backend(
+            MockBackend::new()
+                .with_capabilities(Capabilities::ALL)
+                .respond(MockOp::LoadTagged, BIG_RESPONSE)
+                .respond(MockOp::LoadTagged, BIG_RESPONSE)
+                .respond(MockOp::LoadTagged, BIG_RESPONSE),
+        )
+        .unwrap();
+    let huge: Number = "123456789012345678901234567890123456789012".parse().unwrap();
+
+    let values: Vec<Value> = ys.load("...").unwrap();
+    assert_eq!(
+        values,
+        [
+            Value::BigInt(u64::MAX.into()),
+            Value::BigInt(i128::MIN.into()),
+            Value::BigInt(huge.clone()),
+            Value::Int(5),
+        ]
+    );
+
+    let (a, b, c, d): (u64, i128, Number, Number) = ys.load("...").unwrap();
+    assert_eq!((a, b, c, d), (u64::MAX, i128::MIN, huge, 5.into()));
+
+    let json: serde_json::Value = ys.load("...").unwrap();
+    assert_eq!(json[0].to_string(), "18446744073709551615");
+    assert_eq!(json[2].to_string(), "123456789012345678901234567890123456789012");
+}
+
+#[cfg(feature = "arbitrary_precision")]
+#[test]
+fn integers_need_tagged_exports() {
+    let backend = MockBackend::new().with_capabilities(yamlstar::Capabilities {
+        tagged: false,
+        ..yamlstar::Capabilities::ALL
+    });
+    let result = YAMLStar::builder()
+        .arbitrary_precision(true)
+        .build_with_backend(backend);
+    assert!(matches!(
+        result,
+        Err(yamlstar::Error::Unsupported("arbitrary_precision"))
+    ));
+}
+
+#[cfg(feature = "arbitrary_precision")]
+#[test]
+fn integers_roundtrip_through_library() {
+    let ys = YAMLStar::builder().arbitrary_precision(true).build().unwrap();
+    assert_eq!(ys.load::<u64>("18446744073709551615").unwrap(), u64::MAX);
+    assert_eq!(ys.load::<i128>("-9223372036854775809").unwrap(), -9_223_372_036_854_775_809);
+    assert_eq!(ys.load::<u64>("0xFFFFFFFFFFFFFFFF").unwrap(), u64::MAX);
+    assert_eq!(ys.load::<u128>("0o3777777777777777777777777777777777777777777").unwrap(), u128::MAX);
+    let yaml = "id: 123456789012345678901234567890\n";
+    let value: Value = ys.load(yaml).unwrap();
+    assert_eq!(value["id"].as_number().unwrap().as_str(), "123456789012345678901234567890");
+    assert_eq!(ys.dump(&value).unwrap(), yaml);
+}
@@ -1,51 +1,121 @@
 // Copyright 2024 yaml.org
 // MIT License
 
-#[test]
-fn load_simple_scalar() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+#![allow(clippy::approx_constant)]
+
+use yamlstar::{BackendKind, YAMLStar};
+
+/// Run every test against each backend, in a module named after it.
+macro_rules! backend_tests {
+    ($($name:ident),* $(,)?) => {
+        mod library {
+            $(
+                #[test]
+                fn $name() {
+                    let ys = super::YAMLStar::builder().backend(super::BackendKind::Library);
+                    super::$name(&ys.build().unwrap());
+                }
+            )*
+        }
+
+        mod cli {
+            $(
+                #[test]
+                fn $name() {
+                    let ys = super::YAMLStar::builder().backend(super::BackendKind::Cli);
+                    super::$name(&ys.build().unwrap());
+                }
+            )*
+        }
+    };
+}
+
+backend_tests!(
+    load_simple_scalar,
+    load_integer,
+    load_core_schema_integers,
+    load_integer_like_strings,
+    load_float,
+    load_boolean_true,
+    load_boolean_false,
+    load_null,
+    load_simple_mapping,
+    load_mapping_to_struct,
+    load_sequence,
+    load_flow_sequence,
+    load_flow_mapping,
+    load_nested_structure,
+    load_type_coercion,
+    load_all_single_document,
+    load_all_multiple_documents,
+    load_all_mixed_types,
+    dump_simple_mapping,
+    dump_roundtrip,
+    dump_all_documents,
+    version,
+    error_handling_malformed_yaml,
+    load_multiple_times,
+    load_quoted_strings,
+);
+
+fn load_simple_scalar(ys: &YAMLStar) {
     let ret = ys.load::<String>("hello").unwrap();
     assert_eq!(ret, "hello");
 }
 
-#[test]
-fn load_integer() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_integer(ys: &YAMLStar) {
     let ret = ys.load::<i64>("42").unwrap();
     assert_eq!(ret, 42);
 }
 
-#[test]
-fn load_float() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_core_schema_integers(ys: &YAMLStar) {
+    for (yaml, expected) in [
+        ("0", 0),
+        ("-19", -19),
+        ("+12", 12),
+        ("012", 12),
+        ("0o17", 15),
+        ("0o0", 0),
+        ("0x1F", 31),
+        ("0x1f", 31),
+        ("0xDEADBEEF", 0xDEAD_BEEF),
+        ("!!int 0x1F", 31),
+        ("!!int 0o17", 15),
+        ("!!int 42", 42),
+    ] {
+        assert_eq!(ys.load::<i64>(yaml).unwrap(), expected, "{yaml}");
+    }
+}
+
+fn load_integer_like_strings(ys: &YAMLStar) {
+    for yaml in ["0o18", "0xG1", "-0x1F", "+0o17", "0X1F", "0b101", "1_000"] {
+        assert_eq!(ys.load::<String>(yaml).unwrap(), yaml);
+    }
+    assert_eq!(ys.dump(&"0x1F").unwrap(), "'0x1F'\n");
+    assert_eq!(ys.dump(&"0o17").unwrap(), "'0o17'\n");
+}
+
+fn load_float(ys: &YAMLStar) {
     let ret = ys.load::<f64>("3.14").unwrap();
     assert!((ret - 3.14).abs() < 0.001);
 }
 
-#[test]
-fn load_boolean_true() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_boolean_true(ys: &YAMLStar) {
     let ret = ys.load::<bool>("true").unwrap();
     assert!(ret);
 }
 
-#[test]
-fn load_boolean_false() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_boolean_false(ys: &YAMLStar) {
     let ret = ys.load::<bool>("false").unwrap();
     assert!(!ret);
 }
 
-#[test]
-fn load_null() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_null(ys: &YAMLStar) {
     let ret = ys.load::<Option<String>>("null").unwrap();
     assert!(ret.is_none());
 }
 
-#[test]
-fn load_simple_mapping() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_simple_mapping(ys: &YAMLStar) {
     let ret = ys.load::<serde_json::Value>("key: value").unwrap();
     let obj = ret.as_object().unwrap();
     assert_eq!(obj.get("key").unwrap().as_str().unwrap(), "value");
@@ -57,38 +127,29 @@ struct Config {
     port: u16,
 }
 
-#[test]
-fn load_mapping_to_struct() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_mapping_to_struct(ys: &YAMLStar) {
     let config = ys.load::<Config>("host: localhost\nport: 8080").unwrap();
     assert_eq!(config.host, "localhost");
     assert_eq!(config.port, 8080);
 }
 
-#[test]
-fn load_sequence() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_sequence(ys: &YAMLStar) {
     let ret = ys.load::<Vec<String>>("- a\n- b\n- c").unwrap();
     assert_eq!(ret, vec!["a", "b", "c"]);
 }
 
-#[test]
-fn load_flow_sequence() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_flow_sequence(ys: &YAMLStar) {
     let ret = ys.load::<Vec<String>>("[a, b, c]").unwrap();
     assert_eq!(ret, vec!["a", "b", "c"]);
 }
 
-#[test]
-fn load_flow_mapping() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_flow_mapping(ys: &YAMLStar) {
     let ret = ys.load::<serde_json::Value>("{key: value}").unwrap();
     let obj = ret.as_object().unwrap();
     assert_eq!(obj.get("key").unwrap().as_str().unwrap(), "value");
 }
 
-#[test]
-fn load_nested_structure() {
+fn load_nested_structure(ys: &YAMLStar) {
     let yaml = r#"
 users:
   - name: Alice
@@ -97,7 +158,6 @@ users:
     age: 25
 "#;
 
-    let ys = yamlstar::YAMLStar::new().unwrap();
     let ret = ys.load::<serde_json::Value>(yaml).unwrap();
     let obj = ret.as_object().unwrap();
     let users = obj.get("users").unwrap().as_array().unwrap();
@@ -109,8 +169,7 @@ users:
     assert_eq!(users[0].as_object().unwrap().get("age").unwrap(), 30);
 }
 
-#[test]
-fn load_type_coercion() {
+fn load_type_coercion(ys: &YAMLStar) {
     #[derive(serde::Deserialize)]
     struct TypeTest {
         string: String,
@@ -130,7 +189,6 @@ bool_false: false
 null_value: null
 "#;
 
-    let ys = yamlstar::YAMLStar::new().unwrap();
     let ret = ys.load::<TypeTest>(yaml).unwrap();
     assert_eq!(ret.string, "hello");
     assert_eq!(ret.integer, 42);
@@ -140,42 +198,32 @@ null_value: null
     assert!(ret.null_value.is_none());
 }
 
-#[test]
-fn load_all_single_document() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_all_single_document(ys: &YAMLStar) {
     let ret = ys.load_all::<String>("hello").unwrap();
     assert_eq!(ret, vec!["hello"]);
 }
 
-#[test]
-fn load_all_multiple_documents() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn load_all_multiple_documents(ys: &YAMLStar) {
     let ret = ys.load_all::<String>("---\ndoc1\n---\ndoc2\n---\ndoc3").unwrap();
     assert_eq!(ret, vec!["doc1", "doc2", "doc3"]);
 }
 
-#[test]
-fn load_all_mixed_types() {
+fn load_all_mixed_types(ys: &YAMLStar) {
     let yaml = "---\n42\n---\nhello\n---\ntrue";
-    let ys = yamlstar::YAMLStar::new().unwrap();
     let ret = ys.load_all::<serde_json::Value>(yaml).unwrap();
     assert_eq!(ret.len(), 3);
     assert_eq!(ret[0].as_i64().unwrap(), 42);
     assert_eq!(ret[1].as_str().unwrap(), "hello");
-    assert_eq!(ret[2].as_bool().unwrap(), true);
+    assert!(ret[2].as_bool().unwrap());
 }
 
-#[test]
-fn dump_simple_mapping() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn dump_simple_mapping(ys: &YAMLStar) {
     let value = serde_json::json!({"key": "value"});
     let ret = ys.dump(&value).unwrap();
     assert_eq!(ret, "key: value\n");
 }
 
-#[test]
-fn dump_roundtrip() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn dump_roundtrip(ys: &YAMLStar) {
     let value = serde_json::json!({
         "items": ["a", "b"],
         "flag": true,
@@ -186,33 +234,24 @@ fn dump_roundtrip() {
     assert_eq!(ret, value);
 }
 
-#[test]
-fn dump_all_documents() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn dump_all_documents(ys: &YAMLStar) {
     let values = vec![serde_json::json!("doc1"), serde_json::json!({"a": 1})];
     let ret = ys.dump_all(&values).unwrap();
     assert_eq!(ret, "---\ndoc1\n---\na: 1\n");
 }
 
-#[test]
-fn version() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn version(ys: &YAMLStar) {
     let ver = ys.version().unwrap();
     assert!(!ver.is_empty());
     assert!(ver.contains('.')); // Version should contain dots like "0.1.18-SNAPSHOT"
 }
 
-#[test]
-fn error_handling_malformed_yaml() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
+fn error_handling_malformed_yaml(ys: &YAMLStar) {
     let result = ys.load::<serde_json::Value>("key: \"unclosed");
     assert!(result.is_err());
 }
 
-#[test]
-fn load_multiple_times() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
-
+fn load_multiple_times(ys: &YAMLStar) {
     let ret1 = ys.load::<String>("hello").unwrap();
     assert_eq!(ret1, "hello");
 
@@ -224,17 +263,10 @@ fn load_multiple_times() {
     assert_eq!(ret3.port, 9000);
 }
 
-#[test]
-fn load_quoted_strings() {
-    let ys = yamlstar::YAMLStar::new().unwrap();
-
+fn load_quoted_strings(ys: &YAMLStar) {
     let ret = ys.load::<String>("\"hello world\"").unwrap();
     assert_eq!(ret, "hello world");
 
     let ret = ys.load::<String>("'single quoted'").unwrap();
     assert_eq!(ret, "single quoted");
 }
-
-// Note: Special float values (.inf, -.inf, .nan) are valid YAML 1.2 but
-// cannot be serialized to JSON by libyamlstar's JSON encoder.
-// This is a known limitation of the underlying library.
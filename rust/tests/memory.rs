@@ -0,0 +1,73 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+//! Check that buffers returned by libyamlstar are released.
+
+#![cfg(target_os = "linux")]
+
+/// Read the resident set size of this process in bytes.
+fn resident_bytes() -> usize {
+    let statm = std::fs::read_to_string("/proc/self/statm").unwrap();
+    let pages: usize = statm.split_whitespace().nth(1).unwrap().parse().unwrap();
+    let page_size = usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).unwrap();
+    pages * page_size
+}
+
+#[test]
+fn load_does_not_leak_results() {
+    // Each response is roughly 16KiB of JSON, so leaking them would grow the
+    // process by about 48MiB over the loop below.
+    let yaml = (0..500)
+        .map(|i| format!("key{i}: value-{i}-abcdefghijklmnop\n"))
+        .collect::<String>();
+
+    let ys = yamlstar::YAMLStar::new().unwrap();
+    assert!(
+        ys.capabilities().free,
+        "the library built from this tree must export yamlstar_free"
+    );
+
+    // Warm up the runtime so its heap reaches a steady state.
+    for _ in 0..200 {
+        ys.load::<serde_json::Value>(&yaml).unwrap();
+    }
+    let before = resident_bytes();
+
+    for _ in 0..3000 {
+        ys.load::<serde_json::Value>(&yaml).unwrap();
+    }
+    let after = resident_bytes();
+
+    let growth = after.saturating_sub(before);
+    assert!(
+        growth < 24 * 1024 * 1024,
+        "resident memory grew by {growth} bytes over 3000 loads"
+    );
+}
+
+#[test]
+fn free_accepts_every_result() {
+    // Hand a buffer from each export back to yamlstar_free many times over,
+    // so an allocator mismatch shows up as a crash here.
+    let plain = yamlstar::YAMLStar::new().unwrap();
+    assert!(
+        plain.capabilities().free,
+        "the library built from this tree must export yamlstar_free"
+    );
+    let options = yamlstar::YAMLStar::builder()
+        .schema(yamlstar::Schema::Yaml11)
+        .build()
+        .unwrap();
+    let docs = vec![serde_json::json!({"a": [1, 2.5, "x"]}); 2];
+
+    for ys in [&plain, &options] {
+        for _ in 0..1000 {
+            ys.load::<serde_json::Value>("a: [1, 2.5, x]").unwrap();
+            ys.load::<yamlstar::Value>("a: !tag [1, 2.5, x]").unwrap();
+            ys.load_all::<serde_json::Value>("--- 1\n--- 2\n").unwrap();
+            ys.dump(&docs[0]).unwrap();
+            ys.dump_all(&docs).unwrap();
+            ys.version().unwrap();
+        }
+    }
+}
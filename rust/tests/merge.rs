@@ -0,0 +1,119 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use yamlstar::{Capabilities, Error, MockBackend, MockOp, Schema, Value, YAMLStar};
+
+/// Services sharing settings through merge keys, one merging a single
+/// mapping and one a sequence of them.
+const SERVICES: &str = "\
+base: &base {image: app, port: 80, debug: false}
+extra: &extra {port: 8080, debug: true}
+web:
+  <<: *base
+  image: web
+worker:
+  <<: [*extra, *base]
+  name: jobs
+";
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Service {
+    image: String,
+    port: u16,
+    debug: bool,
+}
+
+#[test]
+fn merge_keys_are_sent_as_options() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(MockOp::LoadTagged, r#"{"data":{"!map":[["a",1],["b",2]]}}"#)
+            .respond(MockOp::DumpTagged, r#"{"data":"'<<': 1\n"}"#),
+    );
+    let ys = YAMLStar::builder()
+        .merge_keys(true)
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    let value: Value = ys.load("<<: {a: 1}\nb: 2\n").unwrap();
+    assert_eq!(value["a"], 1);
+    ys.dump(&Value::Null).unwrap();
+    let options = mock
+        .options()
+        .into_iter()
+        .map(|options| serde_json::from_str(&options.expect("options were sent")).unwrap())
+        .collect::<Vec<serde_json::Value>>();
+    assert_eq!(
+        options,
+        [
+            serde_json::json!({"tagged": true, "schema": "core", "merge": true}),
+            serde_json::json!({"tagged": true, "schema": "core", "merge": true}),
+        ]
+    );
+}
+
+#[test]
+fn merge_keys_off_by_default() {
+    let mock = Arc::new(MockBackend::new().respond(MockOp::Load, r#"{"data":null}"#));
+    let ys = YAMLStar::builder()
+        .merge_keys(false)
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    ys.load::<Value>("~").unwrap();
+    assert_eq!(mock.options(), [None]);
+}
+
+#[test]
+fn merge_keys_need_options_capability() {
+    let result = YAMLStar::builder()
+        .merge_keys(true)
+        .build_with_backend(MockBackend::new().with_capabilities(Capabilities {
+            options: false,
+            ..Capabilities::ALL
+        }));
+    assert!(matches!(result, Err(Error::Unsupported("merge_keys"))));
+}
+
+#[test]
+fn merge_keys_through_library() {
+    #[derive(Deserialize)]
+    struct Services {
+        web: Service,
+        worker: Service,
+    }
+
+    let ys = YAMLStar::builder().merge_keys(true).build().unwrap();
+    let services: Services = ys.load(SERVICES).unwrap();
+    assert_eq!(
+        services.web,
+        Service {
+            image: "web".into(),
+            port: 80,
+            debug: false,
+        }
+    );
+    assert_eq!(
+        services.worker,
+        Service {
+            image: "app".into(),
+            port: 8080,
+            debug: true,
+        }
+    );
+
+    let value: Value = ys.load(SERVICES).unwrap();
+    assert_eq!(value["worker"]["name"], "jobs");
+    assert!(value["web"].get("<<").is_none());
+
+    let failsafe = YAMLStar::builder()
+        .schema(Schema::Failsafe)
+        .merge_keys(true)
+        .build()
+        .unwrap();
+    let value: Value = failsafe.load("<<: {a: 1}\n'<<': 2\n").unwrap();
+    assert_eq!(value["a"], "1");
+    assert_eq!(value["<<"], "2");
+}
@@ -0,0 +1,109 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::sync::Arc;
+
+use yamlstar::{Backend, Capabilities, Error, MockBackend, MockOp, YAMLStar};
+
+#[test]
+fn mock_returns_canned_data() {
+    let ys = YAMLStar::with_backend(
+        MockBackend::new()
+            .respond_data(MockOp::Load, &serde_json::json!({"key": "value"}))
+            .respond_data(MockOp::LoadAll, &[1, 2])
+            .respond_data(MockOp::Dump, "key: value\n")
+            .respond(MockOp::DumpAll, r#"{"data":"--- 1\n--- 2\n"}"#),
+    );
+    let value: serde_json::Value = ys.load("key: value").unwrap();
+    assert_eq!(value, serde_json::json!({"key": "value"}));
+    assert_eq!(ys.load_all::<i64>("--- 1\n--- 2").unwrap(), vec![1, 2]);
+    assert_eq!(ys.dump(&value).unwrap(), "key: value\n");
+    assert_eq!(ys.dump_all(&[1, 2]).unwrap(), "--- 1\n--- 2\n");
+}
+
+#[test]
+fn mock_returns_library_errors() {
+    let ys = YAMLStar::with_backend(MockBackend::new().respond_error(
+        MockOp::Load,
+        "mapping values are not allowed here",
+        "ParseError",
+    ));
+    let Err(Error::YAMLStar(err)) = ys.load::<serde_json::Value>("a: b: c") else {
+        panic!("expected a library error");
+    };
+    assert_eq!(err.cause, "mapping values are not allowed here");
+    assert_eq!(err.type_, "ParseError");
+}
+
+#[test]
+fn mock_records_calls() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .respond_data(MockOp::Load, &1)
+            .respond_data(MockOp::Dump, "2\n"),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    ys.load::<i64>("1").unwrap();
+    ys.dump(&2).unwrap();
+    assert_eq!(
+        mock.calls(),
+        vec![(MockOp::Load, "1".to_string()), (MockOp::Dump, "2".to_string())]
+    );
+}
+
+#[test]
+fn mock_reports_version_and_capabilities() {
+    let ys = YAMLStar::with_backend(MockBackend::new().with_version("9.8.7"));
+    assert_eq!(ys.version().unwrap(), "9.8.7");
+    assert_eq!(ys.capabilities(), Capabilities::PLAIN);
+
+    let ys = YAMLStar::with_backend(MockBackend::new().with_capabilities(Capabilities {
+        dump: false,
+        version: false,
+        ..Capabilities::PLAIN
+    }));
+    assert!(matches!(ys.dump(&1), Err(Error::Unsupported("dump"))));
+    assert!(matches!(ys.version(), Err(Error::Unsupported("version"))));
+}
+
+#[test]
+fn mock_tagged_operations_need_the_capability() {
+    let mock = MockBackend::new().respond(MockOp::LoadTagged, r#"{"data":{"!float":"1.0"}}"#);
+    assert!(matches!(
+        Backend::load_tagged(&mock, "1.0"),
+        Err(Error::Unsupported("load_tagged"))
+    ));
+    assert!(mock.calls().is_empty());
+
+    // Tagged operations and options asking for the tagged encoding answer
+    // from the tagged queue; plain ones from their own.
+    let mock = Arc::new(
+        mock.with_capabilities(Capabilities::ALL)
+            .respond(MockOp::LoadTagged, r#"{"data":{"!float":"2.0"}}"#)
+            .respond_data(MockOp::Load, &3.0),
+    );
+    assert_eq!(
+        Backend::load_tagged(&*mock, "1.0").unwrap(),
+        r#"{"data":{"!float":"1.0"}}"#
+    );
+    assert_eq!(
+        Backend::load_with_options(&*mock, "2.0", r#"{"tagged":true}"#).unwrap(),
+        r#"{"data":{"!float":"2.0"}}"#
+    );
+    assert_eq!(Backend::load(&*mock, "3.0").unwrap(), r#"{"data":3.0}"#);
+    assert_eq!(
+        mock.calls(),
+        [
+            (MockOp::LoadTagged, "1.0".to_string()),
+            (MockOp::LoadTagged, "2.0".to_string()),
+            (MockOp::Load, "3.0".to_string()),
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "no response queued for load")]
+fn mock_panics_without_response() {
+    let ys = YAMLStar::with_backend(MockBackend::new());
+    let _ = ys.load::<i64>("1");
+}
@@ -0,0 +1,111 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use yamlstar::{Capabilities, MockBackend, MockOp, Value, YAMLStar};
+
+/// More keys than fit in a Clojure array-map, none in sorted order.
+const KEYS: [&str; 10] = [
+    "zeta", "alpha", "mu", "beta", "omega", "delta", "kappa", "gamma", "psi", "eta",
+];
+
+/// A mapping of `KEYS` to their positions, as YAML.
+fn ordered_yaml() -> String {
+    KEYS.iter()
+        .enumerate()
+        .map(|(i, key)| format!("{key}: {i}\n"))
+        .collect()
+}
+
+/// The tagged response for `ordered_yaml`.
+fn ordered_response() -> String {
+    let pairs: Vec<String> = KEYS
+        .iter()
+        .enumerate()
+        .map(|(i, key)| format!(r#"["{key}",{i}]"#))
+        .collect();
+    format!(r#"{{"data":{{"!map":[{}]}}}}"#, pairs.join(","))
+}
+
+#[test]
+fn order_kept_by_value() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(MockOp::LoadTagged, ordered_response())
+            .respond_data(MockOp::DumpTagged, "..."),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    let value: Value = ys.load("...").unwrap();
+    let keys: Vec<_> = value.as_mapping().unwrap().keys().collect();
+    assert_eq!(keys, KEYS.map(Value::from).iter().collect::<Vec<_>>());
+    ys.dump(&value).unwrap();
+    assert!(mock.calls()[1].1.starts_with(r#"{"!map":[["zeta",0],["alpha",1],["mu",2]"#));
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Service {
+    name: String,
+    image: String,
+    command: Vec<String>,
+    replicas: u32,
+}
+
+#[test]
+fn order_kept_by_structs() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::DumpTagged, "..."),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    let service = Service {
+        name: "web".to_string(),
+        image: "nginx".to_string(),
+        command: vec![],
+        replicas: 2,
+    };
+    ys.dump(&service).unwrap();
+    assert_eq!(
+        mock.calls()[0].1,
+        r#"{"!map":[["name","web"],["image","nginx"],["command",[]],["replicas",2]]}"#
+    );
+}
+
+#[cfg(feature = "preserve_order")]
+#[test]
+fn order_kept_by_json_values() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(MockOp::LoadTagged, ordered_response())
+            .respond_data(MockOp::DumpTagged, "..."),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    let json: serde_json::Value = ys.load("...").unwrap();
+    let keys: Vec<_> = json.as_object().unwrap().keys().map(String::as_str).collect();
+    assert_eq!(keys, KEYS);
+    ys.dump(&serde_json::json!({"b": 1, "a": 2})).unwrap();
+    assert_eq!(mock.calls()[1].1, r#"{"!map":[["b",1],["a",2]]}"#);
+}
+
+#[test]
+fn order_roundtrips_through_library() {
+    let ys = YAMLStar::new().unwrap();
+    let yaml = ordered_yaml();
+    let value: Value = ys.load(&yaml).unwrap();
+    assert_eq!(ys.dump(&value).unwrap(), yaml);
+    let docs: Vec<Value> = ys.load_all(&format!("---\n{yaml}---\n{yaml}")).unwrap();
+    assert_eq!(ys.dump_all(&docs).unwrap(), format!("---\n{yaml}---\n{yaml}"));
+}
+
+#[cfg(feature = "preserve_order")]
+#[test]
+fn order_roundtrips_json_through_library() {
+    let ys = YAMLStar::new().unwrap();
+    let yaml = ordered_yaml();
+    let json: serde_json::Value = ys.load(&yaml).unwrap();
+    assert_eq!(ys.dump(&json).unwrap(), yaml);
+}
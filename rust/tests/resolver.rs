@@ -0,0 +1,163 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::sync::Arc;
+
+use yamlstar::{Capabilities, Error, MockBackend, MockOp, Resolver, TaggedValue, Value, YAMLStar};
+
+/// A tagged string.
+fn tagged(tag: &str, text: &str) -> Value {
+    TaggedValue::new(tag, text).into()
+}
+
+/// The options a mock received for each call, parsed.
+fn sent_options(mock: &MockBackend) -> Vec<serde_json::Value> {
+    mock.options()
+        .into_iter()
+        .map(|options| serde_json::from_str(&options.expect("options were sent")).unwrap())
+        .collect()
+}
+
+#[test]
+fn resolver_patterns_are_sent_as_options() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(
+                MockOp::LoadTagged,
+                r#"{"data":{"!map":[["timeout",{"!tag":["!duration","10s"]}]]}}"#,
+            ),
+    );
+    let ys = YAMLStar::builder()
+        .resolver(Resolver::pattern("!duration", r"\d+(ms|s|m|h)"))
+        .resolver(Resolver::pattern("!port", r":\d+"))
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    let value: Value = ys.load("timeout: 10s").unwrap();
+    assert_eq!(value["timeout"], tagged("!duration", "10s"));
+    assert_eq!(
+        sent_options(&mock),
+        [serde_json::json!({
+            "tagged": true,
+            "schema": "core",
+            "resolvers": [
+                {"tag": "!duration", "pattern": r"\d+(ms|s|m|h)"},
+                {"tag": "!port", "pattern": r":\d+"},
+            ],
+        })]
+    );
+}
+
+#[test]
+fn resolver_predicates_tag_plain_scalars() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(
+                MockOp::LoadAllTagged,
+                r#"{"data":[{"!map":[[{"!plain":["a","a"]},{"!plain":["RED","RED"]}]]},[{"!plain":["12",12]},"RED"]]}"#,
+            ),
+    );
+    let ys = YAMLStar::builder()
+        .resolver(Resolver::predicate("!color", |text| {
+            ["RED", "GREEN", "BLUE"].contains(&text)
+        }))
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    let docs: Vec<Value> = ys.load_all("...").unwrap();
+    assert_eq!(docs[0]["a"], tagged("!color", "RED"));
+    assert_eq!(docs[1][0], 12);
+    assert_eq!(docs[1][1], "RED");
+    assert_eq!(
+        sent_options(&mock),
+        [serde_json::json!({"tagged": true, "schema": "core", "plain": true})]
+    );
+}
+
+#[test]
+fn resolver_predicates_quote_matching_strings() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::DumpTagged, "..."),
+    );
+    let ys = YAMLStar::builder()
+        .resolver(Resolver::predicate("!color", |text| text == "RED"))
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    let value = Value::Sequence(vec![
+        "RED".into(),
+        "PINK".into(),
+        tagged("!color", "RED"),
+    ]);
+    ys.dump(&value).unwrap();
+    assert_eq!(
+        mock.calls()[0].1,
+        r#"[{"!tag":["!","RED"]},"PINK",{"!tag":["!color","RED"]}]"#
+    );
+}
+
+#[test]
+fn resolver_scoped_to_instance() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::LoadTagged, "10s")
+            .respond_data(MockOp::LoadTagged, "10s"),
+    );
+    let with = YAMLStar::builder()
+        .resolver(Resolver::pattern("!duration", r"\d+s"))
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    let without = YAMLStar::builder()
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    with.load::<Value>("10s").unwrap();
+    without.load::<Value>("10s").unwrap();
+    let options = mock.options();
+    assert!(options[0].is_some());
+    assert_eq!(options[1], None);
+}
+
+#[test]
+fn resolver_needs_tagged_exports() {
+    let backend = MockBackend::new().with_capabilities(Capabilities {
+        tagged: false,
+        ..Capabilities::ALL
+    });
+    let result = YAMLStar::builder()
+        .resolver(Resolver::predicate("!x", |_| true))
+        .build_with_backend(backend);
+    assert!(matches!(result, Err(Error::Unsupported("resolver"))));
+}
+
+#[test]
+fn resolver_debug() {
+    let pattern = Resolver::pattern("!duration", r"\d+s");
+    assert_eq!(pattern.tag(), "!duration");
+    assert_eq!(
+        format!("{pattern:?}"),
+        r#"Resolver { tag: "!duration", pattern: "\\d+s" }"#
+    );
+    let predicate = Resolver::predicate("!x", |_| true);
+    assert_eq!(format!("{predicate:?}"), r#"Resolver { tag: "!x", predicate: .. }"#);
+}
+
+#[test]
+fn resolver_roundtrip_through_library() {
+    let ys = YAMLStar::builder()
+        .resolver(Resolver::pattern("!duration", r"\d+(ms|s|m|h)"))
+        .resolver(Resolver::predicate("!color", |text| text == "RED"))
+        .build()
+        .unwrap();
+    let yaml = "timeout: 10s\ncolor: RED\nname: '10s'\ncount: 10\n";
+    let value: Value = ys.load(yaml).unwrap();
+    assert_eq!(value["timeout"], tagged("!duration", "10s"));
+    assert_eq!(value["color"], tagged("!color", "RED"));
+    assert_eq!(value["name"], "10s");
+    assert_eq!(value["count"], 10);
+    assert_eq!(
+        ys.dump(&["10s", "RED", "blue"]).unwrap(),
+        "- '10s'\n- 'RED'\n- blue\n"
+    );
+}
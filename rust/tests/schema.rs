@@ -0,0 +1,156 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use yamlstar::{Capabilities, Error, MockBackend, MockOp, Schema, Value, YAMLStar};
+
+/// The options a mock received for each call, parsed.
+fn sent_options(mock: &MockBackend) -> Vec<serde_json::Value> {
+    mock.options()
+        .into_iter()
+        .map(|options| serde_json::from_str(&options.expect("options were sent")).unwrap())
+        .collect()
+}
+
+#[test]
+fn schema_is_sent_as_options() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::LoadTagged, &true)
+            .respond_data(MockOp::LoadAllTagged, &[1, 2])
+            .respond_data(MockOp::DumpTagged, "'yes'\n")
+            .respond_data(MockOp::DumpAllTagged, "..."),
+    );
+    let ys = YAMLStar::builder()
+        .schema(Schema::Yaml11)
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    assert!(ys.load::<bool>("yes").unwrap());
+    assert_eq!(ys.load_all::<i64>("--- 1\n--- 2\n").unwrap(), [1, 2]);
+    assert_eq!(ys.dump(&"yes").unwrap(), "'yes'\n");
+    ys.dump_all(&["on"]).unwrap();
+
+    let options = serde_json::json!({"schema": "yaml11", "tagged": true});
+    assert_eq!(sent_options(&mock), vec![options; 4]);
+    assert_eq!(mock.calls()[2], (MockOp::DumpTagged, r#""yes""#.to_string()));
+}
+
+#[test]
+fn schema_core_uses_plain_operations() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::LoadTagged, &1),
+    );
+    let ys = YAMLStar::builder()
+        .schema(Schema::Core)
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    ys.load::<i64>("1").unwrap();
+    assert_eq!(mock.options(), [None]);
+}
+
+#[test]
+fn schema_options_without_tagged_exports() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities {
+                tagged: false,
+                ..Capabilities::ALL
+            })
+            .respond_data(MockOp::Load, &"1"),
+    );
+    let ys = YAMLStar::builder()
+        .schema(Schema::Failsafe)
+        .build_with_backend(Arc::clone(&mock))
+        .unwrap();
+    assert_eq!(ys.load::<String>("1").unwrap(), "1");
+    assert_eq!(
+        sent_options(&mock),
+        [serde_json::json!({"schema": "failsafe", "tagged": false})]
+    );
+}
+
+#[test]
+fn schema_needs_options_capability() {
+    let backend = MockBackend::new().with_capabilities(Capabilities {
+        options: false,
+        ..Capabilities::ALL
+    });
+    let result = YAMLStar::builder()
+        .schema(Schema::Json)
+        .build_with_backend(backend);
+    assert!(matches!(result, Err(Error::Unsupported("schema"))));
+}
+
+#[test]
+fn schema_names() {
+    assert_eq!(Schema::default(), Schema::Core);
+    let names = [Schema::Failsafe, Schema::Json, Schema::Core, Schema::Yaml11].map(Schema::as_str);
+    assert_eq!(names, ["failsafe", "json", "core", "yaml11"]);
+}
+
+/// A YAMLStar instance using the library with `schema`.
+fn library(schema: Schema) -> YAMLStar {
+    YAMLStar::builder().schema(schema).build().unwrap()
+}
+
+#[test]
+fn schema_failsafe_through_library() {
+    let ys = library(Schema::Failsafe);
+    let map: BTreeMap<String, String> = ys.load("a: 1\nb: true\nc: null\n").unwrap();
+    assert_eq!(map["a"], "1");
+    assert_eq!(map["b"], "true");
+    assert_eq!(map["c"], "null");
+    assert_eq!(ys.load::<i64>("!!int 7").unwrap(), 7);
+}
+
+#[test]
+fn schema_json_through_library() {
+    let ys = library(Schema::Json);
+    let value: Value = ys.load(r#"{"a": 1, "b": [true, null, -2.5]}"#).unwrap();
+    assert_eq!(value["a"], 1);
+    assert_eq!(value["b"][0], true);
+    assert!(matches!(ys.load::<Value>("yes"), Err(Error::YAMLStar(_))));
+    assert!(matches!(ys.load::<Value>("0x1F"), Err(Error::YAMLStar(_))));
+    assert_eq!(ys.dump(&BTreeMap::from([("a", "b")])).unwrap(), "\"a\": \"b\"\n");
+}
+
+#[test]
+fn schema_core_through_library() {
+    let ys = library(Schema::Core);
+    assert_eq!(ys.load::<String>("yes").unwrap(), "yes");
+    assert_eq!(ys.load::<i64>("010").unwrap(), 10);
+    assert_eq!(ys.load::<String>("1_000").unwrap(), "1_000");
+}
+
+#[test]
+fn schema_yaml11_through_library() {
+    let ys = library(Schema::Yaml11);
+    let flags: Vec<bool> = ys.load("[yes, No, ON, off, true]").unwrap();
+    assert_eq!(flags, [true, false, true, false, true]);
+    assert_eq!(ys.load::<Vec<String>>("[y, n]").unwrap(), ["y", "n"]);
+    for (yaml, expected) in [
+        ("0755", 0o755),
+        ("-010", -8),
+        ("0b1010", 10),
+        ("0x_FF", 255),
+        ("1_000_000", 1_000_000),
+        ("1:30:00", 5400),
+        ("-1:00", -60),
+    ] {
+        assert_eq!(ys.load::<i64>(yaml).unwrap(), expected, "{yaml}");
+    }
+    assert_eq!(ys.load::<f64>("1_000.5").unwrap(), 1000.5);
+    assert_eq!(ys.load::<f64>("1:30.5").unwrap(), 90.5);
+    assert_eq!(ys.load::<String>("0o17").unwrap(), "0o17");
+
+    let yaml = "base: &base {host: localhost, port: 80}\nsite:\n  <<: *base\n  port: 8080\n";
+    let value: Value = ys.load(yaml).unwrap();
+    assert_eq!(value["site"]["host"], "localhost");
+    assert_eq!(value["site"]["port"], 8080);
+
+    assert_eq!(ys.dump(&["yes", "0755", "plain"]).unwrap(), "- 'yes'\n- '0755'\n- plain\n");
+}
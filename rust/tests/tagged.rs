@@ -0,0 +1,162 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use yamlstar::{
+    from_value, to_value, Capabilities, MaybeTagged, MockBackend, MockOp, Schema, Tagged,
+    TaggedValue, Value, YAMLStar,
+};
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+struct Config {
+    secret: Tagged<String>,
+    ports: Vec<MaybeTagged<u16>>,
+    backup: Option<Tagged<Vec<String>>>,
+}
+
+#[test]
+fn tagged_keeps_tags_when_loading() {
+    let ys = YAMLStar::with_backend(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(
+                MockOp::LoadTagged,
+                r#"{"data":{"!map":[
+                    ["secret",{"!tag":["!vault","s3cr3t"]}],
+                    ["ports",[80,{"!tag":["!internal","8080"]}]],
+                    ["backup",{"!tag":["tag:yaml.org,2002:set",["a","b"]]}]
+                ]}}"#,
+            ),
+    );
+    let config: Config = ys.load("...").unwrap();
+    assert_eq!(config.secret, Tagged::new("!vault", "s3cr3t".to_string()));
+    assert_eq!(config.ports[0], MaybeTagged::Untagged(80));
+    assert_eq!(config.ports[1].tag(), Some("!internal"));
+    assert_eq!(*config.ports[1].value(), 8080);
+    assert_eq!(config.ports[1].clone().into_value(), 8080);
+    let backup = config.backup.unwrap();
+    assert_eq!(backup.tag, "tag:yaml.org,2002:set");
+    assert_eq!(backup.value, ["a", "b"]);
+}
+
+#[test]
+fn tagged_scalar_text_is_typed_for_other_types() {
+    let tagged = |text: &str| Value::from(TaggedValue::new("!x", text));
+    assert_eq!(from_value::<Tagged<String>>(tagged("8080")).unwrap().value, "8080");
+    assert_eq!(from_value::<Tagged<u16>>(tagged("8080")).unwrap().value, 8080);
+    assert_eq!(from_value::<Tagged<f64>>(tagged("2.5")).unwrap().value, 2.5);
+    assert!(from_value::<Tagged<bool>>(tagged("true")).unwrap().value);
+    assert_eq!(from_value::<Tagged<Option<u16>>>(tagged("~")).unwrap().value, None);
+    assert_eq!(from_value::<Tagged<Option<u16>>>(tagged("1")).unwrap().value, Some(1));
+    assert_eq!(from_value::<Tagged<Value>>(tagged("1")).unwrap().value, "1");
+    assert_eq!(from_value::<Value>(tagged("1")).unwrap(), tagged("1"));
+    let err = from_value::<Tagged<u16>>(tagged("port")).unwrap_err();
+    assert!(err.to_string().contains("invalid type: string \"port\""), "{err}");
+}
+
+#[test]
+fn tagged_scalar_text_is_typed_with_the_instance_schema() {
+    let load = |schema: Schema| {
+        let ys = YAMLStar::builder()
+            .schema(schema)
+            .build_with_backend(
+                MockBackend::new()
+                    .with_capabilities(Capabilities::ALL)
+                    .respond(
+                        MockOp::LoadTagged,
+                        r#"{"data":[{"!tag":["!x","yes"]},{"!tag":["!x","8080"]}]}"#,
+                    ),
+            )
+            .unwrap();
+        ys.load::<(Tagged<bool>, Tagged<u16>)>("...")
+    };
+    let (flag, port) = load(Schema::Yaml11).unwrap();
+    assert!(flag.value);
+    assert_eq!(port.value, 8080);
+    assert!(load(Schema::Json).is_err());
+    assert!(load(Schema::Failsafe).is_err());
+}
+
+#[test]
+fn tagged_needs_a_tag() {
+    let err = from_value::<Tagged<String>>(Value::from("plain")).unwrap_err();
+    assert!(err.to_string().contains("expected a tagged node"), "{err}");
+
+    let value: Option<Tagged<String>> = from_value(Value::Null).unwrap();
+    assert_eq!(value, None);
+    let value: MaybeTagged<String> = from_value(Value::from("plain")).unwrap();
+    assert_eq!(value.into_tagged(), None);
+}
+
+#[test]
+fn tagged_converts_to_tagged_values() {
+    let value = to_value(&Tagged::new("tag:yaml.org,2002:set", vec!["a"])).unwrap();
+    assert_eq!(
+        value,
+        Value::from(TaggedValue::new("tag:yaml.org,2002:set", Value::Sequence(vec!["a".into()])))
+    );
+    let back: Tagged<Vec<String>> = from_value(value).unwrap();
+    assert_eq!(back, Tagged::new("tag:yaml.org,2002:set", vec!["a".to_string()]));
+
+    assert_eq!(to_value(&MaybeTagged::Untagged(1)).unwrap(), 1);
+
+    // Other formats see a single-entry map.
+    assert_eq!(
+        serde_json::to_value(Tagged::new("!vault", "x")).unwrap(),
+        serde_json::json!({"!vault": "x"})
+    );
+}
+
+#[test]
+fn tagged_dumps_the_tag() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::DumpTagged, ""),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    let config = Config {
+        secret: Tagged::new("!vault", "s3cr3t".into()),
+        ports: vec![MaybeTagged::Untagged(80), Tagged::new("!internal", 8080).into()],
+        backup: None,
+    };
+    ys.dump(&config).unwrap();
+    let (_, data) = mock.calls().pop().unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&data).unwrap(),
+        serde_json::json!({"!map": [
+            ["secret", {"!tag": ["!vault", "s3cr3t"]}],
+            ["ports", [80, {"!tag": ["!internal", 8080]}]],
+            ["backup", null],
+        ]})
+    );
+}
+
+#[test]
+fn bang_keys_dump_as_keys() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::DumpTagged, ""),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    ys.dump(&serde_json::json!({"!important": true})).unwrap();
+    let (_, data) = mock.calls().pop().unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&data).unwrap(),
+        serde_json::json!({"!map": [["!important", true]]})
+    );
+}
+
+#[test]
+fn tagged_roundtrip_through_library() {
+    let ys = YAMLStar::new().unwrap();
+    let yaml = "secret: !vault s3cr3t\nports:\n- 80\n- !internal 8080\nbackup: !backup\n- a\n";
+    let config: Config = ys.load(yaml).unwrap();
+    assert_eq!(config.secret.tag, "!vault");
+    assert_eq!(ys.dump(&config.secret).unwrap(), "!vault s3cr3t\n");
+    let again: Config = ys.load(&ys.dump(&config).unwrap()).unwrap();
+    assert_eq!(again, config);
+}
@@ -0,0 +1,143 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::error::Error as _;
+
+use yamlstar::{Capabilities, Error, Mapping, MockBackend, MockOp, TaggedValue, Value, YAMLStar};
+
+/// The tagged response for a document with custom tags on a scalar, a
+/// sequence and a mapping, one nested in another.
+const TAGGED_RESPONSE: &str = r#"{"data":{"!map":[
+    ["name",{"!tag":["!upper","abc"]}],
+    ["set",{"!tag":["tag:yaml.org,2002:set",{"!map":[["a",null],["b",null]]}]}],
+    ["point",{"!tag":["!point",[1,{"!tag":["!double",2]}]]}],
+    ["secret",{"!tag":["!vault","xyz"]}]
+]}}"#;
+
+/// A constructor for `!upper` scalars.
+fn upper(node: TaggedValue) -> Result<Value, String> {
+    match node.value {
+        Value::String(s) => Ok(Value::String(s.to_uppercase())),
+        other => Err(format!("!upper needs a string, not {other}")),
+    }
+}
+
+#[test]
+fn tags_construct_scalars_and_collections() {
+    let mut ys = YAMLStar::with_backend(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(MockOp::LoadTagged, TAGGED_RESPONSE),
+    );
+    ys.register_tag("!upper", upper)
+        .unwrap()
+        .register_tag("!!set", |node: TaggedValue| {
+            let Value::Mapping(map) = node.value else {
+                return Err("!!set needs a mapping");
+            };
+            Ok(Value::Sequence(map.keys().cloned().collect()))
+        })
+        .unwrap()
+        .register_tag("!double", |node: TaggedValue| {
+            node.value
+                .as_i64()
+                .map(|n| Value::Int(n * 2))
+                .ok_or("!double needs an integer")
+        })
+        .unwrap()
+        .register_tag("!point", |node: TaggedValue| {
+            // The children are constructed first.
+            assert_eq!(node.value[1], 4);
+            let mut map = Mapping::new();
+            map.insert("x", node.value[0].clone());
+            map.insert("y", node.value[1].clone());
+            Ok::<_, String>(Value::Mapping(map))
+        })
+        .unwrap();
+
+    let value: Value = ys.load("...").unwrap();
+    assert_eq!(value["name"], "ABC");
+    assert_eq!(value["set"], Value::Sequence(vec!["a".into(), "b".into()]));
+    assert_eq!(value["point"]["x"], 1);
+    assert_eq!(value["point"]["y"], 4);
+    assert_eq!(value["secret"].tag(), Some("!vault"));
+}
+
+#[test]
+fn tags_errors_carry_tag_and_location() {
+    let mut ys = YAMLStar::with_backend(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(
+                MockOp::LoadTagged,
+                r#"{"data":{"!map":[["servers",[{"!map":[["a/b",{"!tag":["!upper",[1]]}]]}]]]}}"#,
+            )
+            .respond(
+                MockOp::LoadAllTagged,
+                r#"{"data":[{"!tag":["!upper","ok"]},{"!tag":["!upper",5]}]}"#,
+            ),
+    );
+    ys.register_tag("!upper", upper).unwrap();
+
+    let err = ys.load::<Value>("...").unwrap_err();
+    let Error::Tag {
+        tag,
+        path,
+        document,
+        ..
+    } = &err
+    else {
+        panic!("expected Error::Tag, got {err:?}");
+    };
+    assert_eq!(tag, "!upper");
+    assert_eq!(path, "/servers/0/a~1b");
+    assert_eq!(*document, 0);
+    assert_eq!(
+        err.to_string(),
+        "Failed to construct !upper at /servers/0/a~1b in document 0: !upper needs a string, not - 1"
+    );
+    assert!(err.source().is_some());
+
+    let err = ys.load_all::<Value>("...").unwrap_err();
+    assert!(matches!(err, Error::Tag { document: 1, ref path, .. } if path.is_empty()));
+    assert!(err.to_string().contains("at / in document 1"));
+}
+
+#[test]
+fn tags_replace_earlier_constructor() {
+    let mut ys = YAMLStar::with_backend(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(MockOp::LoadTagged, r#"{"data":{"!tag":["!x","a"]}}"#),
+    );
+    ys.register_tag("!x", |_: TaggedValue| Ok::<_, String>(Value::Int(1)))
+        .unwrap()
+        .register_tag("!x", |_: TaggedValue| Ok::<_, String>(Value::Int(2)))
+        .unwrap();
+    assert_eq!(ys.load::<i64>("...").unwrap(), 2);
+}
+
+#[test]
+fn tags_need_tagged_exports() {
+    let mut ys = YAMLStar::with_backend(MockBackend::new().with_capabilities(Capabilities {
+        tagged: false,
+        ..Capabilities::ALL
+    }));
+    assert!(matches!(
+        ys.register_tag("!upper", upper),
+        Err(Error::Unsupported("register_tag"))
+    ));
+}
+
+#[test]
+fn tags_construct_through_library() {
+    let mut ys = YAMLStar::new().unwrap();
+    ys.register_tag("!upper", upper).unwrap();
+    let err = ys.load::<Value>("a: !upper abc\nb: !upper [1]\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Failed to construct !upper at /b in document 0: !upper needs a string, not - 1"
+    );
+    let names: Vec<String> = ys.load("[!upper abc, !upper def]").unwrap();
+    assert_eq!(names, ["ABC", "DEF"]);
+}
@@ -0,0 +1,99 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::sync::Arc;
+
+#[test]
+fn yamlstar_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<yamlstar::YAMLStar>();
+}
+
+#[test]
+fn share_instance_across_threads() {
+    let ys = Arc::new(yamlstar::YAMLStar::new().unwrap());
+    let handles = (0..8)
+        .map(|i| {
+            let ys = Arc::clone(&ys);
+            std::thread::spawn(move || {
+                for j in 0..50 {
+                    let ret = ys.load::<i64>(&format!("{}", i * 1000 + j)).unwrap();
+                    assert_eq!(ret, i * 1000 + j);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn global_instance_is_shared() {
+    let a = yamlstar::YAMLStar::global().unwrap();
+    let b = std::thread::spawn(|| yamlstar::YAMLStar::global().unwrap() as *const _ as usize)
+        .join()
+        .unwrap();
+    assert_eq!(a as *const _ as usize, b);
+}
+
+#[test]
+fn from_str_and_to_string() {
+    let value: serde_json::Value = yamlstar::from_str("key: value").unwrap();
+    assert_eq!(value, serde_json::json!({"key": "value"}));
+    assert_eq!(yamlstar::to_string(&value).unwrap(), "key: value\n");
+}
+
+#[test]
+fn per_thread_isolate_threads() {
+    let ys = Arc::new(yamlstar::YAMLStar::with_thread_mode(yamlstar::ThreadMode::PerThread).unwrap());
+    let handles = (0..4)
+        .map(|i| {
+            let ys = Arc::clone(&ys);
+            std::thread::spawn(move || {
+                let ret = ys.load::<Vec<i64>>(&format!("[{i}, {i}]")).unwrap();
+                assert_eq!(ret, vec![i, i]);
+                assert_eq!(ys.dump(&ret).unwrap(), format!("- {i}\n- {i}\n"));
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    // The creating thread keeps using the isolate's own thread.
+    assert_eq!(ys.load::<String>("main").unwrap(), "main");
+}
+
+#[test]
+fn single_mode_stays_on_its_thread() {
+    let ys = Arc::new(yamlstar::YAMLStar::with_thread_mode(yamlstar::ThreadMode::Single).unwrap());
+    let other = Arc::clone(&ys);
+    let err = std::thread::spawn(move || other.load::<i64>("1").unwrap_err())
+        .join()
+        .unwrap();
+    assert!(matches!(err, yamlstar::Error::WrongThread), "{err:?}");
+    assert_eq!(ys.load::<i64>("1").unwrap(), 1);
+}
+
+#[test]
+fn per_thread_drop_on_another_thread() {
+    let ys = yamlstar::YAMLStar::with_thread_mode(yamlstar::ThreadMode::PerThread).unwrap();
+    std::thread::spawn(move || {
+        assert_eq!(ys.load::<i64>("1").unwrap(), 1);
+        drop(ys);
+    })
+    .join()
+    .unwrap();
+
+    let ys = yamlstar::YAMLStar::with_thread_mode(yamlstar::ThreadMode::PerThread).unwrap();
+    std::thread::spawn(move || drop(ys)).join().unwrap();
+}
+
+#[test]
+fn default_mode_attaches_per_thread() {
+    let ys = yamlstar::YAMLStar::new().unwrap();
+    assert!(ys.capabilities().per_thread);
+    std::thread::scope(|scope| {
+        scope.spawn(|| assert_eq!(ys.load::<i64>("1").unwrap(), 1));
+    });
+}
@@ -0,0 +1,239 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::{collections::HashMap, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use yamlstar::{Capabilities, Mapping, MockBackend, MockOp, Tagged, TaggedValue, Value, YAMLStar};
+
+/// A value with everything plain JSON loses.
+fn rich_value() -> Value {
+    let mut map = Mapping::new();
+    map.insert(1, "one");
+    map.insert("ratio", f64::INFINITY);
+    map.insert("secret", TaggedValue::new("!vault", "abc"));
+    map.insert(vec![1, 2], Value::Null);
+    Value::Mapping(map)
+}
+
+#[test]
+fn value_loads_tagged_response() {
+    let ys = YAMLStar::with_backend(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(
+                MockOp::LoadTagged,
+                r#"{"data":{"!map":[[1,"one"],["ratio",{"!float":".inf"}],["secret",{"!tag":["!vault","abc"]}],[[1,2],null]]}}"#,
+            ),
+    );
+    let value: Value = ys.load("...").unwrap();
+    assert_eq!(value, rich_value());
+    assert_eq!(value[1], "one");
+    assert_eq!(value["ratio"], f64::INFINITY);
+    assert_eq!(value["secret"].tag(), Some("!vault"));
+    assert_eq!(value["secret"].untagged(), "abc");
+    assert!(value["missing"].is_null());
+    assert_eq!(
+        value.as_mapping().unwrap().keys().collect::<Vec<_>>(),
+        [&Value::Int(1), &"ratio".into(), &"secret".into(), &vec![1, 2].into()]
+    );
+}
+
+#[test]
+fn value_dumps_tagged_encoding() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond_data(MockOp::DumpTagged, "..."),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    ys.dump(&rich_value()).unwrap();
+    assert_eq!(
+        mock.calls()[0].1,
+        r#"{"!map":[[1,"one"],["ratio",{"!float":".inf"}],["secret",{"!tag":["!vault","abc"]}],[[1,2],null]]}"#
+    );
+}
+
+#[test]
+fn value_falls_back_to_plain_json() {
+    let mock = Arc::new(
+        MockBackend::new()
+            .with_capabilities(Capabilities {
+                tagged: false,
+                ..Capabilities::ALL
+            })
+            .respond(MockOp::Load, r#"{"data":{"a":[1,2.5]}}"#)
+            .respond_data(MockOp::Dump, "..."),
+    );
+    let ys = YAMLStar::with_backend(Arc::clone(&mock));
+    let value: Value = ys.load("...").unwrap();
+    assert_eq!(value["a"][1], 2.5);
+    ys.dump(&value).unwrap();
+    assert_eq!(mock.calls()[1].1, r#"{"a":[1,2.5]}"#);
+}
+
+#[test]
+fn value_keys_deserialize_leniently() {
+    let ys = YAMLStar::with_backend(
+        MockBackend::new()
+            .with_capabilities(Capabilities::ALL)
+            .respond(MockOp::LoadTagged, r#"{"data":{"!map":[[1,"a"],[true,"b"],[null,"c"]]}}"#)
+            .respond(MockOp::LoadTagged, r#"{"data":{"!map":[["1","a"],[2,"b"]]}}"#),
+    );
+    let map: HashMap<String, String> = ys.load("...").unwrap();
+    assert_eq!(map["1"], "a");
+    assert_eq!(map["true"], "b");
+    assert_eq!(map["null"], "c");
+    let map: HashMap<i64, String> = ys.load("...").unwrap();
+    assert_eq!(map[&1], "a");
+    assert_eq!(map[&2], "b");
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Point,
+    Circle(f64),
+    Rect { width: u32, height: u32 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Drawing {
+    name: String,
+    shapes: Vec<Shape>,
+    layer: Option<u8>,
+}
+
+#[test]
+fn value_converts_rust_types() {
+    let drawing = Drawing {
+        name: "plan".to_string(),
+        shapes: vec![
+            Shape::Point,
+            Shape::Circle(1.5),
+            Shape::Rect {
+                width: 2,
+                height: 3,
+            },
+        ],
+        layer: None,
+    };
+    let value = yamlstar::to_value(&drawing).unwrap();
+    assert_eq!(value["shapes"][0], "Point");
+    assert_eq!(value["shapes"][1]["Circle"], 1.5);
+    assert_eq!(value.pointer("/shapes/2/Rect/height"), Some(&Value::Int(3)));
+    assert!(value["layer"].is_null());
+    assert_eq!(yamlstar::from_value::<Drawing>(value).unwrap(), drawing);
+
+    assert_eq!(yamlstar::to_value(&u64::MAX).unwrap(), Value::BigInt(u64::MAX.into()));
+    assert_eq!(
+        yamlstar::to_value(&Tagged::new("!vault", "abc")).unwrap(),
+        Value::from(TaggedValue::new("!vault", "abc"))
+    );
+    assert_eq!(
+        yamlstar::to_value(&Value::from(TaggedValue::new("!vault", "abc"))).unwrap(),
+        Value::from(TaggedValue::new("!vault", "abc"))
+    );
+    let value = yamlstar::to_value(&HashMap::from([("!vault", "abc")])).unwrap();
+    assert_eq!(value.tag(), None);
+    assert_eq!(value["!vault"], "abc");
+}
+
+#[test]
+fn value_converts_json() {
+    let json = serde_json::json!({"a": [1, 2.5, null, true, "x"]});
+    let value = Value::from(json.clone());
+    assert_eq!(value["a"][0], 1);
+    assert_eq!(serde_json::Value::try_from(value).unwrap(), json);
+
+    let json = serde_json::Value::try_from(rich_value().get("secret").unwrap().clone()).unwrap();
+    assert_eq!(json, "abc");
+    let mut map = Mapping::new();
+    map.insert(1, true);
+    assert_eq!(
+        serde_json::Value::try_from(Value::Mapping(map)).unwrap(),
+        serde_json::json!({"1": true})
+    );
+    assert!(serde_json::Value::try_from(Value::Float(f64::NAN)).is_err());
+    assert!(serde_json::Value::try_from(rich_value()).is_err());
+}
+
+#[test]
+fn value_mapping_keeps_order_and_finds_keys() {
+    let mut map: Mapping = (0..10_000).map(|i| (i, i * 2)).collect();
+    assert_eq!(map.get(&Value::Int(9_999)), Some(&Value::Int(19_998)));
+    assert_eq!(map.remove(&Value::Int(5)), Some(Value::Int(10)));
+    assert_eq!(map.remove(&Value::Int(5)), None);
+    assert_eq!(map.get(&Value::Int(6)), Some(&Value::Int(12)));
+    assert_eq!(map.keys().nth(5), Some(&Value::Int(6)));
+    assert_eq!(map.insert(3, "three"), Some(Value::Int(6)));
+    assert_eq!(map.insert(5, "five"), None);
+    assert_eq!(map.keys().last(), Some(&Value::Int(5)));
+    assert_eq!(map.len(), 10_000);
+
+    let mut map = Mapping::new();
+    map.insert(-0.0, "zero");
+    map.insert(vec![1, 2], "pair");
+    map.insert(TaggedValue::new("!x", 1), "tagged");
+    map.insert(f64::NAN, "nan");
+    map.insert(f64::NAN, "another nan");
+    assert_eq!(map.get(&Value::Float(0.0)), Some(&"zero".into()));
+    assert_eq!(map.get(&vec![1, 2].into()), Some(&"pair".into()));
+    assert_eq!(map.get(&TaggedValue::new("!x", 1).into()), Some(&"tagged".into()));
+    assert_eq!(map.get(&Value::Float(f64::NAN)), None);
+    assert_eq!(map.len(), 5);
+}
+
+#[test]
+fn value_index_and_pointer() {
+    let mut value = Value::Null;
+    value["server"]["ports"] = vec![80, 443].into();
+    value["server"]["name"] = "web".into();
+    value["server"]["ports"][1] = 8443.into();
+    *value.pointer_mut("/server/name").unwrap() = "api".into();
+    assert_eq!(value.pointer("/server/ports/1"), Some(&Value::Int(8443)));
+    assert_eq!(value.pointer("/server/name"), Some(&"api".into()));
+    assert_eq!(value.pointer("/server/ports/01"), None);
+    assert_eq!(value.pointer("server"), None);
+    assert_eq!(value.pointer(""), Some(&value));
+    assert_eq!(rich_value().pointer("/1"), Some(&"one".into()));
+    assert_eq!(rich_value().get("missing"), None);
+}
+
+#[test]
+fn value_displays_as_yaml() {
+    let mut value = rich_value();
+    value["list"] = vec![Value::from("a b"), Value::from("true"), Value::from("x\ny")].into();
+    value["nested"]["deep"] = TaggedValue::new("tag:yaml.org,2002:set", Mapping::new()).into();
+    value["items"] = vec![rich_value()["secret"].clone(), Value::Sequence(vec![])].into();
+    assert_eq!(
+        value.to_string(),
+        "1: one\n\
+         ratio: .inf\n\
+         secret: !vault abc\n\
+         [1, 2]: null\n\
+         list:\n\
+         - a b\n\
+         - 'true'\n\
+         - \"x\\ny\"\n\
+         nested:\n  deep: !!set {}\n\
+         items:\n\
+         - !vault abc\n\
+         - []"
+    );
+    assert_eq!(Value::Float(1.0).to_string(), "1.0");
+    assert_eq!(Value::Float(f64::NAN).to_string(), ".nan");
+}
+
+#[test]
+fn value_roundtrips_through_library() {
+    let ys = YAMLStar::new().unwrap();
+    assert!(ys.capabilities().tagged);
+    let yaml = "1: one\nratio: .inf\nbig: 9007199254740993\nsecret: !vault abc\n? [1, 2]\n: pair\n";
+    let value: Value = ys.load(yaml).unwrap();
+    assert_eq!(value[1], "one");
+    assert_eq!(value["ratio"], f64::INFINITY);
+    assert_eq!(value["big"], 9_007_199_254_740_993_i64);
+    assert_eq!(value["secret"], Value::from(TaggedValue::new("!vault", "abc")));
+    assert_eq!(value[Value::from(vec![1, 2])], "pair");
+    assert_eq!(ys.load::<Value>(&ys.dump(&value).unwrap()).unwrap(), value);
+}
@@ -0,0 +1,62 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use yamlstar::{Version, VersionPolicy};
+
+fn v(s: &str) -> Version {
+    s.parse().unwrap()
+}
+
+#[test]
+fn parse_version() {
+    assert_eq!(
+        v("0.1.18-SNAPSHOT"),
+        Version {
+            major: 0,
+            minor: 1,
+            patch: 18,
+            pre: Some("SNAPSHOT".to_string()),
+        }
+    );
+    assert_eq!(v("1.2.3").pre, None);
+    assert_eq!(v("0.1.18-SNAPSHOT").to_string(), "0.1.18-SNAPSHOT");
+}
+
+#[test]
+fn parse_invalid_version() {
+    for s in ["", "1", "1.2", "1.2.3.4", "1.2.x", "1.2.3-", "v1.2.3"] {
+        assert!(s.parse::<Version>().is_err(), "{s:?} should not parse");
+    }
+}
+
+#[test]
+fn version_policies() {
+    let expected = v("0.1.18");
+    assert!(VersionPolicy::Exact.accepts(&expected, &v("0.1.18-SNAPSHOT")));
+    assert!(!VersionPolicy::Exact.accepts(&expected, &v("0.1.17")));
+    assert!(VersionPolicy::SameMinor.accepts(&expected, &v("0.1.2")));
+    assert!(!VersionPolicy::SameMinor.accepts(&expected, &v("0.2.18")));
+    assert!(VersionPolicy::Any.accepts(&expected, &v("9.9.9")));
+}
+
+#[test]
+fn library_version_is_compatible() {
+    let ys = yamlstar::YAMLStar::builder()
+        .version_policy(VersionPolicy::Exact)
+        .build()
+        .unwrap();
+    let found = ys.library_version().unwrap();
+    assert!(VersionPolicy::Exact.accepts(&v(yamlstar::LIBYAMLSTAR_VERSION), &found));
+}
+
+#[test]
+fn version_mismatch_message() {
+    let err = yamlstar::Error::VersionMismatch {
+        expected: "0.1.18".to_string(),
+        found: "0.1.17".to_string(),
+    };
+    assert_eq!(
+        err.to_string(),
+        "Incompatible libyamlstar version 0.1.17 (expected 0.1.18)"
+    );
+}
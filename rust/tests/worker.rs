@@ -0,0 +1,160 @@
+// Copyright 2024 yaml.org
+// MIT License
+
+use std::sync::Arc;
+
+use yamlstar::{BackendKind, Error, YAMLStar};
+
+/// A builder for the worker backend, using this crate's worker binary.
+fn worker_builder() -> yamlstar::Builder {
+    YAMLStar::builder()
+        .backend(BackendKind::Worker)
+        .worker_program(env!("CARGO_BIN_EXE_yamlstar-worker"))
+}
+
+#[test]
+fn worker_load_and_dump() {
+    let ys = worker_builder().workers(2).build().unwrap();
+    let value: serde_json::Value = ys.load("key: [1, 2]").unwrap();
+    assert_eq!(value, serde_json::json!({"key": [1, 2]}));
+    assert_eq!(ys.dump(&value).unwrap(), "key:\n- 1\n- 2\n");
+    assert!(matches!(ys.load::<i64>("key: \"unclosed"), Err(Error::YAMLStar(_))));
+}
+
+#[test]
+fn worker_load_with_schema() {
+    let ys = worker_builder().schema(yamlstar::Schema::Yaml11).build().unwrap();
+    assert!(ys.capabilities().options);
+    assert!(ys.load::<bool>("yes").unwrap());
+    assert_eq!(ys.dump(&"yes").unwrap(), "'yes'\n");
+}
+
+#[test]
+fn worker_program_defaults_to_yamlstar_worker() {
+    // Test binaries run from target/*/deps, below the yamlstar-worker binary.
+    let ys = YAMLStar::builder().backend(BackendKind::Worker).build().unwrap();
+    assert_eq!(ys.load::<i64>("1").unwrap(), 1);
+}
+
+#[test]
+fn worker_pool_across_threads() {
+    let ys = Arc::new(worker_builder().workers(2).build().unwrap());
+    let handles = (0..4)
+        .map(|i| {
+            let ys = Arc::clone(&ys);
+            std::thread::spawn(move || {
+                for j in 0..10 {
+                    assert_eq!(ys.load::<i64>(&format!("{}", i * 100 + j)).unwrap(), i * 100 + j);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn worker_crash_is_reported_and_restarted() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // A worker that reports ready with every capability but the tagged ones
+    // and those with options, then exits while reading its first request.
+    let dir = std::env::temp_dir().join(format!("yamlstar-worker-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("crashing-worker");
+    std::fs::write(
+        &program,
+        "#!/bin/sh\nprintf '\\000\\002\\000\\000\\00063'\nhead -c 1 >/dev/null\nexit 3\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let ys = YAMLStar::builder()
+        .backend(BackendKind::Worker)
+        .worker_program(&program)
+        .version_policy(yamlstar::VersionPolicy::Any)
+        .workers(1)
+        .build()
+        .unwrap();
+    assert!(!ys.capabilities().tagged);
+    for _ in 0..2 {
+        match ys.load::<i64>("1") {
+            Err(Error::WorkerCrashed(status)) => assert_eq!(status.code(), Some(3)),
+            other => panic!("expected a crash, got {other:?}"),
+        }
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn worker_that_hangs_times_out_and_is_replaced() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+
+    // A worker that reports ready, then blocks without using CPU once it has
+    // read its first request. Each one counts itself in a file as it starts.
+    let dir = std::env::temp_dir().join(format!("yamlstar-worker-hang-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("hanging-worker");
+    let started = dir.join("started");
+    std::fs::write(
+        &program,
+        format!(
+            "#!/bin/sh\necho >> '{}'\nprintf '\\000\\002\\000\\000\\00063'\n\
+            head -c 1 >/dev/null\nexec sleep 60\n",
+            started.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let ys = YAMLStar::builder()
+        .backend(BackendKind::Worker)
+        .worker_program(&program)
+        .version_policy(yamlstar::VersionPolicy::Any)
+        .workers(1)
+        .worker_cpu_time(Duration::from_secs(1))
+        .worker_timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    let start = Instant::now();
+    for _ in 0..2 {
+        match ys.load::<i64>("1") {
+            Err(Error::WorkerTimedOut(limit)) => assert_eq!(limit, Duration::from_millis(200)),
+            other => panic!("expected a timeout, got {other:?}"),
+        }
+    }
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_eq!(std::fs::read_to_string(&started).unwrap().lines().count(), 2);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn worker_without_capabilities_fails_to_build() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // A worker that reports ready without saying what it supports.
+    let dir = std::env::temp_dir().join(format!("yamlstar-worker-caps-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("silent-worker");
+    std::fs::write(&program, "#!/bin/sh\nprintf '\\000\\000\\000\\000\\000'\ncat >/dev/null\n").unwrap();
+    std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let result = YAMLStar::builder()
+        .backend(BackendKind::Worker)
+        .worker_program(&program)
+        .version_policy(yamlstar::VersionPolicy::Any)
+        .build();
+    match result {
+        Err(Error::Ffi(message)) => assert!(message.contains("capabilities"), "{message}"),
+        other => panic!("expected a start failure, got {:?}", other.err()),
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}